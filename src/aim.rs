@@ -0,0 +1,249 @@
+//! Aiming with the mouse or a gamepad's right stick, with optional aim assist.
+//!
+//! With aiming fixed, the duck always shoots straight ahead as it always has.
+//! Aiming with the mouse, a mouse button fires too, either once per click or
+//! for as long as it's held.
+//!
+//! An optional aim line shows where shots will go while aiming freely. Its
+//! dashes march outward, holding still when reducing motion.
+
+use bevy::{prelude::*, window::PrimaryWindow};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    GameState, InputSet, IsPlayer, MUZZLE_DISTANCE, MUZZLE_OFFSET, PlayerInput, read_player_input,
+    settings::ReduceMotion,
+};
+
+/// Stick deflection below which the previous aim is kept.
+const STICK_DEADZONE: f32 = 0.3;
+const AIM_LINE_LENGTH: f32 = 400.0;
+const AIM_LINE_DASH: f32 = 12.0;
+const AIM_LINE_GAP: f32 = 12.0;
+/// Speed the dashes march outward at.
+const AIM_LINE_SPEED: f32 = 40.0;
+const AIM_LINE_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.35);
+
+pub struct AimPlugin;
+
+impl Plugin for AimPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AimMode>()
+            .init_resource::<MouseFire>()
+            .init_resource::<AimLine>()
+            .init_resource::<AimAssist>()
+            .add_systems(
+                Update,
+                (
+                    read_aim.in_set(InputSet).after(read_player_input),
+                    draw_aim_line
+                        .after(InputSet)
+                        .run_if(|aim_line: Res<AimLine>| aim_line.0),
+                )
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum AimMode {
+    /// Always shoot straight ahead.
+    #[default]
+    Fixed,
+    Mouse,
+    Stick,
+}
+
+impl AimMode {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Fixed => Self::Mouse,
+            Self::Mouse => Self::Stick,
+            Self::Stick => Self::Fixed,
+        }
+    }
+}
+
+/// How the mouse fires while aiming with it. Space fires as well either way.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MouseFire {
+    pub button: FireButton,
+    /// Keep firing at the auto-fire rate while the button is held, rather
+    /// than once per click.
+    pub hold: bool,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FireButton {
+    #[default]
+    Left,
+    /// Leaves the left button free for clicking through menus.
+    Right,
+}
+
+impl FireButton {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Left => Self::Right,
+            Self::Right => Self::Left,
+        }
+    }
+}
+
+impl From<FireButton> for MouseButton {
+    fn from(button: FireButton) -> Self {
+        match button {
+            FireButton::Left => Self::Left,
+            FireButton::Right => Self::Right,
+        }
+    }
+}
+
+/// Draws a dashed line along the aim while aiming with the mouse or stick.
+#[derive(Resource, Default)]
+pub struct AimLine(pub bool);
+
+/// Nudges shots toward the nearest enemy near the aim direction. Only applies
+/// when aiming by mouse or stick.
+#[derive(Resource)]
+pub struct AimAssist {
+    pub enabled: bool,
+    /// Half-angle, in radians, of the cone around the aim searched for targets.
+    cone: f32,
+    /// How much of the way toward the target a shot is turned, from 0.0 to 1.0.
+    strength: f32,
+}
+
+impl Default for AimAssist {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            cone: 12.0_f32.to_radians(),
+            strength: 0.5,
+        }
+    }
+}
+
+impl AimAssist {
+    /// Turns `aim` part of the way toward the closest target within the cone.
+    pub fn adjust(&self, aim: Vec2, origin: Vec2, targets: impl Iterator<Item = Vec2>) -> Vec2 {
+        let nearest = targets
+            .map(|target| target - origin)
+            .filter(|offset| aim.angle_to(*offset).abs() <= self.cone)
+            .min_by(|a, b| a.length_squared().total_cmp(&b.length_squared()));
+        match nearest {
+            Some(offset) => Vec2::from_angle(aim.to_angle() + aim.angle_to(offset) * self.strength),
+            None => aim,
+        }
+    }
+}
+
+pub fn read_aim(
+    mode: Res<AimMode>,
+    assist: Res<AimAssist>,
+    mut input: ResMut<PlayerInput>,
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    camera_query: Query<(&Camera, &GlobalTransform)>,
+    player_query: Query<&Transform, With<IsPlayer>>,
+    gamepads: Query<&Gamepad>,
+) {
+    let aim = match *mode {
+        AimMode::Fixed => Some(Vec2::X),
+        AimMode::Mouse => {
+            let cursor = window_query
+                .single()
+                .ok()
+                .and_then(|window| window.cursor_position());
+            match (cursor, camera_query.single(), player_query.single()) {
+                (Some(cursor), Ok((camera, camera_transform)), Ok(player_transform)) => camera
+                    .viewport_to_world_2d(camera_transform, cursor)
+                    .ok()
+                    .and_then(|target| {
+                        (target - player_transform.translation.truncate()).try_normalize()
+                    }),
+                _ => None,
+            }
+        }
+        AimMode::Stick => gamepads
+            .iter()
+            .map(|gamepad| gamepad.right_stick())
+            .find(|stick| stick.length() >= STICK_DEADZONE)
+            .and_then(Vec2::try_normalize),
+    };
+    // Without a fresh direction, keep aiming where we last did.
+    if let Some(aim) = aim {
+        input.aim = aim;
+    }
+    input.assisted = *mode != AimMode::Fixed && assist.enabled;
+}
+
+/// The dashes of the aim line from `muzzle` along `aim`, shifted `offset`
+/// along the dash pattern.
+fn aim_line_dashes(muzzle: Vec2, aim: Vec2, offset: f32) -> impl Iterator<Item = (Vec2, Vec2)> {
+    let period = AIM_LINE_DASH + AIM_LINE_GAP;
+    let first = offset.rem_euclid(period) - period;
+    (0..)
+        .map(move |i| first + i as f32 * period)
+        .take_while(|start| *start < AIM_LINE_LENGTH)
+        .map(|start| (start.max(0.0), (start + AIM_LINE_DASH).min(AIM_LINE_LENGTH)))
+        // A dash scrolled wholly behind the muzzle isn't drawn.
+        .filter(|(from, to)| from < to)
+        .map(move |(from, to)| (muzzle + aim * from, muzzle + aim * to))
+}
+
+fn draw_aim_line(
+    mut gizmos: Gizmos,
+    mode: Res<AimMode>,
+    input: Res<PlayerInput>,
+    player_query: Query<&Transform, With<IsPlayer>>,
+    reduce_motion: Res<ReduceMotion>,
+    time: Res<Time>,
+) {
+    if *mode == AimMode::Fixed {
+        return;
+    }
+    let Ok(player_transform) = player_query.single() else {
+        return;
+    };
+    let Some(aim) = input.aim.try_normalize() else {
+        return;
+    };
+    let muzzle = player_transform.translation.truncate() + MUZZLE_OFFSET + aim * MUZZLE_DISTANCE;
+    let offset = if reduce_motion.0 {
+        0.0
+    } else {
+        time.elapsed_secs() * AIM_LINE_SPEED
+    };
+    for (from, to) in aim_line_dashes(muzzle, aim, offset) {
+        gizmos.line_2d(from, to, AIM_LINE_COLOR);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_aim_line_runs_out_along_the_aim() {
+        assert!(!AimLine::default().0);
+
+        let muzzle = Vec2::new(30.0, -20.0);
+        let aim = Vec2::new(3.0, 4.0).normalize();
+        // However far the dashes have marched.
+        for offset in [0.0, 6.0, 13.0, 20.0] {
+            let dashes: Vec<_> = aim_line_dashes(muzzle, aim, offset).collect();
+            for (from, to) in &dashes {
+                for point in [from, to] {
+                    let along = (*point - muzzle).dot(aim);
+                    assert!(((*point - muzzle) - aim * along).length() < 1e-3);
+                    assert!((-1e-3..=AIM_LINE_LENGTH + 1e-3).contains(&along));
+                }
+                assert!((*to - muzzle).length() > (*from - muzzle).length());
+            }
+            let (_, end) = dashes.last().unwrap();
+            let reach = (*end - muzzle).length();
+            assert!(reach > AIM_LINE_LENGTH - AIM_LINE_DASH - AIM_LINE_GAP);
+        }
+    }
+}
@@ -0,0 +1,112 @@
+//! Flipbook animation for sprites drawn from an atlas, stepping through a run
+//! of cells at a fixed rate. Animations only advance during play, so they
+//! freeze along with everything else when the game is paused.
+
+use std::ops::Range;
+
+use bevy::prelude::*;
+
+use crate::GameState;
+
+pub struct AnimationPlugin;
+
+impl Plugin for AnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, animate_sprites.run_if(in_state(GameState::Playing)));
+    }
+}
+
+/// Cycles the sprite's atlas index through `frames`, wrapping back to the
+/// first one after the last.
+#[derive(Component)]
+pub struct AnimationTimer {
+    pub timer: Timer,
+    pub frames: Range<usize>,
+}
+
+impl AnimationTimer {
+    /// Shows each of `frames` for `frame_time` seconds.
+    pub fn new(frames: Range<usize>, frame_time: f32) -> Self {
+        Self {
+            timer: Timer::from_seconds(frame_time, TimerMode::Repeating),
+            frames,
+        }
+    }
+
+    /// The frame `steps` on from `index`.
+    fn advance(&self, index: usize, steps: usize) -> usize {
+        let offset = index.saturating_sub(self.frames.start) + steps;
+        self.frames.start + offset % self.frames.len().max(1)
+    }
+}
+
+fn animate_sprites(mut query: Query<(&mut AnimationTimer, &mut Sprite)>, time: Res<Time>) {
+    for (mut animation, mut sprite) in query.iter_mut() {
+        animation.timer.tick(time.delta());
+        let steps = animation.timer.times_finished_this_tick() as usize;
+        if steps == 0 {
+            continue;
+        }
+        if let Some(atlas) = &mut sprite.texture_atlas {
+            atlas.index = animation.advance(atlas.index, steps);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{state::app::StatesPlugin, time::TimeUpdateStrategy};
+
+    use super::*;
+
+    #[test]
+    fn frames_cycle_and_wrap() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, AnimationPlugin))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                100,
+            )))
+            .insert_state(GameState::Playing);
+        let sprite = app
+            .world_mut()
+            .spawn((
+                Sprite::from_atlas_image(
+                    Handle::default(),
+                    TextureAtlas {
+                        layout: Handle::default(),
+                        index: 4,
+                    },
+                ),
+                AnimationTimer::new(4..7, 0.1),
+            ))
+            .id();
+        let index = |app: &App| {
+            app.world()
+                .get::<Sprite>(sprite)
+                .unwrap()
+                .texture_atlas
+                .as_ref()
+                .unwrap()
+                .index
+        };
+
+        let mut seen = Vec::new();
+        for _ in 0..8 {
+            app.update();
+            seen.push(index(&app));
+        }
+        // The first updates have no time to step by.
+        let start = seen.iter().position(|&index| index != 4).unwrap();
+        assert_eq!(seen[start..start + 5], [5, 6, 4, 5, 6]);
+    }
+
+    #[test]
+    fn a_long_frame_skips_ahead() {
+        let animation = AnimationTimer::new(2..5, 0.1);
+        assert_eq!(animation.advance(3, 4), 4);
+        // An index from before the range starts it over.
+        assert_eq!(animation.advance(0, 1), 3);
+    }
+}
@@ -0,0 +1,332 @@
+//! A screen opened from the main menu listing every enemy the duck can meet,
+//! with what each is worth, how much it takes to bring down and how it
+//! behaves. The values come from the same constants the spawners use, so the
+//! list can't drift from the game.
+//!
+//! The list scrolls with the arrow keys, the D-pad or the mouse wheel, and
+//! Escape goes back to the menu.
+
+use bevy::{
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+};
+
+use crate::{
+    EnemyAtlas, EnemySprite, FISH_HP, FISH_POINTS, GameState, MINE_COLOR, MINE_HP, SEEKER_TINT,
+    boss::{BOSS_HP, BOSS_POINTS},
+    menu_nav::{MenuAction, MenuNav, MenuNavSet},
+    power_ups::GUNFISH_TINT,
+};
+
+/// Pixels per second the list moves while a scroll key is held.
+const SCROLL_SPEED: f32 = 600.0;
+/// Pixels the list moves per notch of a mouse wheel.
+const SCROLL_LINE_HEIGHT: f32 = 40.0;
+const ICON_SIZE: Vec2 = Vec2::new(110.0, 80.0);
+
+pub struct BestiaryPlugin;
+
+impl Plugin for BestiaryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                spawn_bestiary.run_if(resource_added::<BestiaryOpen>),
+                (close_bestiary.after(MenuNavSet), scroll_bestiary)
+                    .run_if(resource_exists::<BestiaryOpen>),
+                despawn_bestiary.run_if(resource_removed::<BestiaryOpen>),
+            )
+                .run_if(in_state(GameState::Menu)),
+        );
+    }
+}
+
+/// Present while the bestiary is showing in place of the main menu.
+#[derive(Resource)]
+pub struct BestiaryOpen;
+
+#[derive(Component)]
+struct BestiaryUi;
+
+/// The scrolling part of the screen.
+#[derive(Component)]
+struct BestiaryList;
+
+/// How an entry is drawn.
+enum EntryIcon {
+    /// Art from the enemy atlas, tinted as it is in play.
+    Sprite(EnemySprite, Color),
+    /// Mines have no art, only a colored circle.
+    Circle(Color),
+}
+
+struct BestiaryEntry {
+    name: &'static str,
+    icon: EntryIcon,
+    points: u32,
+    health: u32,
+    behavior: &'static str,
+}
+
+/// Every kind of enemy, in the order they turn up in a run.
+fn entries() -> [BestiaryEntry; 5] {
+    [
+        BestiaryEntry {
+            name: "Fish",
+            icon: EntryIcon::Sprite(EnemySprite::Fish, Color::WHITE),
+            points: FISH_POINTS,
+            health: FISH_HP,
+            behavior: "Swims in from the right in ever greater numbers. The one nearest \
+                       the duck shoots back now and then.",
+        },
+        BestiaryEntry {
+            name: "Seeker Fish",
+            icon: EntryIcon::Sprite(EnemySprite::Fish, SEEKER_TINT),
+            points: FISH_POINTS,
+            health: FISH_HP,
+            behavior: "A fish that slowly rises or dives toward the duck's height as it \
+                       swims, weaving in to cut it off.",
+        },
+        BestiaryEntry {
+            name: "Gunfish",
+            icon: EntryIcon::Sprite(EnemySprite::Fish, GUNFISH_TINT),
+            points: FISH_POINTS,
+            health: FISH_HP,
+            behavior: "A rare, glowing fish. Killing it always drops a power-up, giving the \
+                       duck spread shots, rapid fire or piercing bullets for a while.",
+        },
+        BestiaryEntry {
+            name: "Mine",
+            icon: EntryIcon::Circle(MINE_COLOR),
+            points: 0,
+            health: MINE_HP,
+            behavior: "Drifts across and hurts the duck on contact. Shooting it sets it \
+                       off, destroying everything caught in the blast.",
+        },
+        BestiaryEntry {
+            name: "Boss",
+            icon: EntryIcon::Sprite(EnemySprite::Boss, Color::WHITE),
+            points: BOSS_POINTS,
+            health: BOSS_HP,
+            behavior: "A giant fish that follows the duck, then fires bursts of bullets, \
+                       then charges while spraying a spiral as its health runs down.",
+        },
+    ]
+}
+
+fn spawn_bestiary(mut commands: Commands, enemy_atlas: Res<EnemyAtlas>) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(16.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+            // Only for Escape; the list scrolls rather than highlighting.
+            MenuNav::new(0),
+            BestiaryUi,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Bestiary"),
+                TextFont {
+                    font_size: 50.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+            parent
+                .spawn((
+                    Node {
+                        width: Val::Px(720.0),
+                        max_height: Val::Percent(65.0),
+                        flex_direction: FlexDirection::Column,
+                        row_gap: Val::Px(20.0),
+                        overflow: Overflow::scroll_y(),
+                        ..default()
+                    },
+                    ScrollPosition::default(),
+                    BestiaryList,
+                ))
+                .with_children(|list| {
+                    for entry in entries() {
+                        spawn_entry(list, &enemy_atlas, entry);
+                    }
+                });
+            parent.spawn((
+                Text::new("Esc - Back"),
+                TextFont {
+                    font_size: 24.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.7, 0.7, 0.7)),
+            ));
+        });
+}
+
+fn spawn_entry(list: &mut ChildSpawnerCommands, enemy_atlas: &EnemyAtlas, entry: BestiaryEntry) {
+    list.spawn(Node {
+        flex_direction: FlexDirection::Row,
+        align_items: AlignItems::Center,
+        column_gap: Val::Px(24.0),
+        flex_shrink: 0.0,
+        ..default()
+    })
+    .with_children(|row| {
+        let icon_node = Node {
+            width: Val::Px(ICON_SIZE.x),
+            height: Val::Px(ICON_SIZE.y),
+            flex_shrink: 0.0,
+            ..default()
+        };
+        match entry.icon {
+            EntryIcon::Sprite(kind, tint) => {
+                row.spawn((icon_node, enemy_atlas.image_node(kind).with_color(tint)));
+            }
+            EntryIcon::Circle(color) => {
+                row.spawn((
+                    Node {
+                        width: Val::Px(ICON_SIZE.y),
+                        margin: UiRect::horizontal(Val::Px((ICON_SIZE.x - ICON_SIZE.y) / 2.0)),
+                        ..icon_node
+                    },
+                    BackgroundColor(color),
+                    BorderRadius::MAX,
+                ));
+            }
+        }
+        row.spawn(Node {
+            flex_direction: FlexDirection::Column,
+            row_gap: Val::Px(4.0),
+            ..default()
+        })
+        .with_children(|details| {
+            details.spawn((
+                Text::new(entry.name),
+                TextFont {
+                    font_size: 30.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+            let points = if entry.points == 0 {
+                "none".to_string()
+            } else {
+                entry.points.to_string()
+            };
+            details.spawn((
+                Text::new(format!("Points: {points}    Health: {}", entry.health)),
+                TextFont {
+                    font_size: 20.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(1.0, 0.85, 0.2)),
+            ));
+            details.spawn((
+                Text::new(entry.behavior),
+                TextFont {
+                    font_size: 18.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.85, 0.85, 0.85)),
+            ));
+        });
+    });
+}
+
+fn close_bestiary(mut commands: Commands, mut actions: EventReader<MenuAction>) {
+    if actions.read().any(|action| *action == MenuAction::Back) {
+        commands.remove_resource::<BestiaryOpen>();
+    }
+}
+
+fn scroll_bestiary(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut wheel: EventReader<MouseWheel>,
+    mut query: Query<&mut ScrollPosition, With<BestiaryList>>,
+    time: Res<Time>,
+) {
+    let held = |key: KeyCode, button: GamepadButton| {
+        keyboard_input.pressed(key) || gamepads.iter().any(|gamepad| gamepad.pressed(button))
+    };
+    let mut delta = 0.0;
+    if held(KeyCode::ArrowDown, GamepadButton::DPadDown) {
+        delta += SCROLL_SPEED * time.delta_secs();
+    }
+    if held(KeyCode::ArrowUp, GamepadButton::DPadUp) {
+        delta -= SCROLL_SPEED * time.delta_secs();
+    }
+    for event in wheel.read() {
+        delta -= match event.unit {
+            MouseScrollUnit::Line => event.y * SCROLL_LINE_HEIGHT,
+            MouseScrollUnit::Pixel => event.y,
+        };
+    }
+    if delta == 0.0 {
+        return;
+    }
+    // Layout keeps the offset from running past the end of the list.
+    for mut scroll in query.iter_mut() {
+        scroll.offset_y = (scroll.offset_y + delta).max(0.0);
+    }
+}
+
+fn despawn_bestiary(mut commands: Commands, query: Query<Entity, With<BestiaryUi>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{EnemyHealth, FishKind, PointValue, boss::boss, fish, tuning::Tuning};
+
+    fn entry(name: &str) -> BestiaryEntry {
+        entries()
+            .into_iter()
+            .find(|entry| entry.name == name)
+            .unwrap_or_else(|| panic!("no entry for {name}"))
+    }
+
+    #[test]
+    fn every_kind_of_enemy_is_listed_with_what_it_is_worth() {
+        let atlas = EnemyAtlas {
+            image: Handle::default(),
+            layout: Handle::default(),
+        };
+        let tuning = Tuning::default();
+        let mut world = World::new();
+        let spawned_fish = world
+            .spawn(fish(&atlas, &tuning, Vec2::ZERO, Vec2::ZERO, FISH_HP))
+            .id();
+        let spawned_boss = world.spawn(boss(&atlas, Vec2::ZERO, BOSS_HP)).id();
+        let listed = |entity: Entity| {
+            let points = world.get::<PointValue>(entity).unwrap().0;
+            let health = world.get::<EnemyHealth>(entity).unwrap().0;
+            (points, health)
+        };
+
+        let fish_kinds = [FishKind::Plain, FishKind::Seeker, FishKind::Gunfish];
+        for kind in fish_kinds {
+            let name = match kind {
+                FishKind::Plain => "Fish",
+                FishKind::Seeker => "Seeker Fish",
+                FishKind::Gunfish => "Gunfish",
+            };
+            let entry = entry(name);
+            assert_eq!((entry.points, entry.health), listed(spawned_fish));
+        }
+        let boss = entry("Boss");
+        assert_eq!((boss.points, boss.health), listed(spawned_boss));
+        let mine = entry("Mine");
+        assert_eq!((mine.points, mine.health), (0, MINE_HP));
+        assert_eq!(entries().len(), fish_kinds.len() + 2);
+    }
+}
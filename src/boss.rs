@@ -0,0 +1,567 @@
+//! A giant fish that shows up every `BOSS_SCORE_INTERVAL` points in standard
+//! and daily runs.
+//!
+//! The boss fights in phases that it moves through as its health drops: first
+//! it slowly follows the duck, then it adds radial bursts of bullets, then it
+//! charges while spraying a spiral. Each change of phase is telegraphed by a
+//! flash.
+//!
+//! Before the fight a name banner sweeps across the screen. The boss holds
+//! still and other spawns wait until it's gone.
+//!
+//! The killing blow sets off a burst of explosions and slows the game clock
+//! for a moment. The duck, its gun and the spawners all wait for it to finish.
+
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::{
+    BodyRadius, EnemyAtlas, EnemyHealth, EnemyKilled, EnemySprite, GameState, IsEnemy, IsPlayer,
+    PointValue, RunMode, Score, Velocity, apply_explosions,
+    bounds::BoundsBehavior,
+    enemy_fire::{BulletPattern, PatternTimer},
+    game_time::GameTime,
+    particles::Explosion,
+    play_area::PlayArea,
+    settings::ReduceMotion,
+    sound::SoundAssets,
+    start_run,
+};
+
+const BOSS_SCORE_INTERVAL: u32 = 100;
+pub const BOSS_HP: u32 = 60;
+pub const BOSS_POINTS: u32 = 50;
+const BOSS_RADIUS: f32 = 60.0;
+const BOSS_SCALE: f32 = 0.25;
+const PHASE_FLASH_DURATION: f32 = 0.6;
+const PHASE_FLASH_INTERVAL: f32 = 0.1;
+const BOSS_NAME: &str = "The Kraken";
+const INTRO_DURATION: f32 = 2.5;
+/// Fraction of the intro spent sweeping the banner in, and again out.
+const BANNER_SWEEP: f32 = 0.25;
+/// Real seconds the slow motion lasts after a boss is destroyed.
+const DEATH_SEQUENCE_DURATION: f32 = 1.0;
+/// How fast the game clock runs during the death sequence.
+const DEATH_TIME_SCALE: f32 = 0.25;
+/// Explosions scattered over the boss as it goes.
+const DEATH_BLASTS: u32 = 6;
+
+pub struct BossPlugin;
+
+impl Plugin for BossPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(BossSpawner {
+            next_score: BOSS_SCORE_INTERVAL,
+        })
+        .add_systems(OnExit(GameState::Menu), reset_boss_spawner.after(start_run))
+        // Carrying on past victory in endless mode.
+        .add_systems(
+            OnTransition {
+                exited: GameState::Victory,
+                entered: GameState::Playing,
+            },
+            carry_boss_spawner,
+        )
+        .add_systems(
+            OnEnter(GameState::Menu),
+            (despawn_banners, end_death_sequence),
+        )
+        .add_systems(
+            Update,
+            (
+                spawn_boss.run_if(has_bosses.and(not(in_boss_death))),
+                play_boss_intro,
+                (advance_boss_phase, move_boss).chain(),
+                flash_boss,
+                start_death_sequence.after(apply_explosions),
+                tick_death_sequence.run_if(in_boss_death),
+            )
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(Update, scale_game_clock);
+    }
+}
+
+fn has_bosses(mode: Res<RunMode>) -> bool {
+    matches!(*mode, RunMode::Standard | RunMode::Daily { .. })
+}
+
+/// Score at which the next boss appears.
+#[derive(Resource)]
+struct BossSpawner {
+    next_score: u32,
+}
+
+#[derive(Component)]
+pub struct Boss {
+    max_health: u32,
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum BossPhase {
+    /// Slowly follows the player.
+    Tracking,
+    /// Follows a little faster and fires radial bursts.
+    Barrage,
+    /// Charges at the player while firing a spiral.
+    Charge,
+}
+
+/// How the boss moves and attacks in a phase.
+struct PhaseParams {
+    speed: f32,
+    /// What the phase fires, if anything, and the seconds between volleys.
+    attack: Option<(BulletPattern, f32)>,
+}
+
+impl BossPhase {
+    /// The phase for a boss with `fraction` of its health left.
+    fn for_health(fraction: f32) -> Self {
+        if fraction > 2.0 / 3.0 {
+            Self::Tracking
+        } else if fraction > 1.0 / 3.0 {
+            Self::Barrage
+        } else {
+            Self::Charge
+        }
+    }
+
+    fn params(self) -> PhaseParams {
+        match self {
+            Self::Tracking => PhaseParams {
+                speed: 40.0,
+                attack: None,
+            },
+            Self::Barrage => PhaseParams {
+                speed: 60.0,
+                attack: Some((BulletPattern::Radial { count: 8 }, 2.5)),
+            },
+            Self::Charge => PhaseParams {
+                speed: 160.0,
+                attack: Some((BulletPattern::spiral(2, 25.0_f32.to_radians()), 0.35)),
+            },
+        }
+    }
+}
+
+/// Present on a boss while its intro plays, holding it still and harmless.
+#[derive(Component)]
+pub struct BossIntro(Timer);
+
+/// Other spawns hold off while a boss is being introduced.
+pub fn in_boss_intro(query: Query<(), With<BossIntro>>) -> bool {
+    !query.is_empty()
+}
+
+#[derive(Component)]
+struct BossBanner;
+
+/// Present while a destroyed boss's slow motion plays out, counting down in
+/// real time.
+#[derive(Resource)]
+pub struct BossDeathSequence(Timer);
+
+/// The duck and the spawners hold off while a boss's death plays out.
+pub fn in_boss_death(sequence: Option<Res<BossDeathSequence>>) -> bool {
+    sequence.is_some()
+}
+
+/// Blinks the boss after it changes phase.
+#[derive(Component)]
+struct PhaseFlash(Timer);
+
+fn reset_boss_spawner(mut spawner: ResMut<BossSpawner>) {
+    spawner.next_score = BOSS_SCORE_INTERVAL;
+}
+
+/// Levels runs have no bosses, so by the time one carries on in endless mode
+/// the threshold is far behind the score. Counts the next one from here.
+fn carry_boss_spawner(mut spawner: ResMut<BossSpawner>, score: Res<Score>) {
+    spawner.next_score = score.0 + BOSS_SCORE_INTERVAL;
+}
+
+/// Brings in a boss once the score passes the next threshold, but never while
+/// one is still alive.
+fn spawn_boss(
+    mut commands: Commands,
+    mut spawner: ResMut<BossSpawner>,
+    score: Res<Score>,
+    enemy_atlas: Res<EnemyAtlas>,
+    sounds: Res<SoundAssets>,
+    play_area: Res<PlayArea>,
+    boss_query: Query<(), With<Boss>>,
+) {
+    if score.0 < spawner.next_score || !boss_query.is_empty() {
+        return;
+    }
+    spawner.next_score = score.0 + BOSS_SCORE_INTERVAL;
+    summon_boss(&mut commands, &enemy_atlas, &sounds, &play_area);
+}
+
+/// A boss at `position` with `hp` left, ready to fight without an intro.
+/// Its phase catches up with its health on the next frame.
+pub fn boss(enemy_atlas: &EnemyAtlas, position: Vec2, hp: u32) -> impl Bundle {
+    (
+        enemy_atlas.sprite(EnemySprite::Boss),
+        Transform::from_translation(position.extend(0.0)).with_scale(Vec3::splat(BOSS_SCALE)),
+        Velocity::default(),
+        IsEnemy,
+        EnemyHealth(hp),
+        PointValue(BOSS_POINTS),
+        BodyRadius(BOSS_RADIUS),
+        Boss {
+            max_health: BOSS_HP,
+        },
+        BossPhase::Tracking,
+        // Kept on screen for the whole fight.
+        BoundsBehavior::Clamp,
+    )
+}
+
+/// Spawns the boss at the right edge, with its intro banner and sting.
+pub fn summon_boss(
+    commands: &mut Commands,
+    enemy_atlas: &EnemyAtlas,
+    sounds: &SoundAssets,
+    play_area: &PlayArea,
+) {
+    commands.spawn((
+        boss(
+            enemy_atlas,
+            Vec2::new(play_area.half_width - BOSS_RADIUS, 0.0),
+            BOSS_HP,
+        ),
+        BossIntro(Timer::from_seconds(INTRO_DURATION, TimerMode::Once)),
+    ));
+
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Percent(40.0),
+            left: Val::Percent(-100.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        Text::new(format!("BOSS: {BOSS_NAME}")),
+        TextFont {
+            font_size: 56.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 0.3, 0.3)),
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+        BossBanner,
+    ));
+    commands.spawn((
+        AudioPlayer::new(sounds.boss_sting.clone()),
+        PlaybackSettings::DESPAWN,
+    ));
+}
+
+/// Sweeps the banner in, holds it, and sweeps it out, then lets the boss
+/// loose.
+fn play_boss_intro(
+    mut commands: Commands,
+    mut boss_query: Query<(Entity, &mut BossIntro, &mut Velocity)>,
+    mut banner_query: Query<(Entity, &mut Node), With<BossBanner>>,
+    time: Res<GameTime>,
+) {
+    for (entity, mut intro, mut vel) in boss_query.iter_mut() {
+        vel.0 = Vec2::ZERO;
+        let progress = intro.0.tick(time.delta()).fraction();
+        // -100% is off to the left, 0% centered, 100% off to the right.
+        let left = if progress < BANNER_SWEEP {
+            (progress / BANNER_SWEEP - 1.0) * 100.0
+        } else if progress > 1.0 - BANNER_SWEEP {
+            (progress - (1.0 - BANNER_SWEEP)) / BANNER_SWEEP * 100.0
+        } else {
+            0.0
+        };
+        for (_, mut node) in banner_query.iter_mut() {
+            node.left = Val::Percent(left);
+        }
+
+        if intro.0.finished() {
+            commands.entity(entity).remove::<BossIntro>();
+            for (banner, _) in banner_query.iter() {
+                commands.entity(banner).despawn();
+            }
+        }
+    }
+}
+
+fn despawn_banners(mut commands: Commands, query: Query<Entity, With<BossBanner>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn advance_boss_phase(
+    mut commands: Commands,
+    mut query: Query<(Entity, &Boss, &EnemyHealth, &mut BossPhase), Without<BossIntro>>,
+) {
+    for (entity, boss, health, mut phase) in query.iter_mut() {
+        let next = BossPhase::for_health(health.0 as f32 / boss.max_health as f32);
+        if next <= *phase {
+            continue;
+        }
+        *phase = next;
+        let mut entity = commands.entity(entity);
+        entity.insert(PhaseFlash(Timer::from_seconds(
+            PHASE_FLASH_DURATION,
+            TimerMode::Once,
+        )));
+        match next.params().attack {
+            Some((pattern, interval)) => {
+                entity.insert((
+                    pattern,
+                    PatternTimer(Timer::from_seconds(interval, TimerMode::Repeating)),
+                ));
+            }
+            None => {
+                entity.remove::<(BulletPattern, PatternTimer)>();
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn move_boss(
+    mut boss_query: Query<
+        (&Transform, &BossPhase, &mut Velocity),
+        (With<Boss>, Without<BossIntro>),
+    >,
+    player_query: Query<&Transform, With<IsPlayer>>,
+) {
+    let Ok(player_transform) = player_query.single() else {
+        return;
+    };
+    for (transform, phase, mut vel) in boss_query.iter_mut() {
+        let toward = (player_transform.translation - transform.translation)
+            .truncate()
+            .normalize_or_zero();
+        vel.0 = toward * phase.params().speed;
+    }
+}
+
+fn start_death_sequence(
+    mut commands: Commands,
+    mut kills: EventReader<EnemyKilled>,
+    mut explosions: EventWriter<Explosion>,
+) {
+    for kill in kills.read().filter(|kill| kill.boss) {
+        commands.insert_resource(BossDeathSequence(Timer::from_seconds(
+            DEATH_SEQUENCE_DURATION,
+            TimerMode::Once,
+        )));
+        for _ in 0..DEATH_BLASTS {
+            let offset = Vec2::from_angle(fastrand::f32() * TAU) * fastrand::f32() * kill.radius;
+            explosions.write(Explosion {
+                position: kill.position + offset,
+                radius: 0.0,
+            });
+        }
+    }
+}
+
+fn tick_death_sequence(
+    mut commands: Commands,
+    mut sequence: ResMut<BossDeathSequence>,
+    time: Res<Time<Real>>,
+) {
+    if sequence.0.tick(time.delta()).finished() {
+        commands.remove_resource::<BossDeathSequence>();
+    }
+}
+
+fn end_death_sequence(mut commands: Commands) {
+    commands.remove_resource::<BossDeathSequence>();
+}
+
+/// Slows the game clock only while a death sequence plays in an unpaused run,
+/// so pausing or leaving mid-sequence never leaves the game slowed.
+fn scale_game_clock(
+    mut time: ResMut<Time<Virtual>>,
+    sequence: Option<Res<BossDeathSequence>>,
+    state: Res<State<GameState>>,
+) {
+    let speed = if sequence.is_some() && *state.get() == GameState::Playing {
+        DEATH_TIME_SCALE
+    } else {
+        1.0
+    };
+    if time.relative_speed() != speed {
+        time.set_relative_speed(speed);
+    }
+}
+
+fn flash_boss(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut PhaseFlash, &mut Sprite)>,
+    reduce_motion: Res<ReduceMotion>,
+    time: Res<Time>,
+) {
+    for (entity, mut flash, mut sprite) in query.iter_mut() {
+        flash.0.tick(time.delta());
+        if flash.0.finished() {
+            sprite.color = Color::WHITE;
+            commands.entity(entity).remove::<PhaseFlash>();
+            continue;
+        }
+        // A steady tint rather than a blink when reducing motion.
+        let blinks = (flash.0.elapsed_secs() / PHASE_FLASH_INTERVAL) as u32;
+        sprite.color = if reduce_motion.0 || blinks.is_multiple_of(2) {
+            Color::srgb(1.0, 0.3, 0.3)
+        } else {
+            Color::WHITE
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{ecs::system::RunSystemOnce, state::app::StatesPlugin, time::TimeUpdateStrategy};
+
+    use super::*;
+    use crate::{
+        Health, Knockback, PlayerHit, PlayerHitbox, check_for_player_collisions,
+        game_time::GameTimePlugin, popups::ScorePopup, tuning::Tuning,
+    };
+
+    #[test]
+    fn the_boss_holds_still_until_its_intro_ends() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, GameTimePlugin))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+                0.2,
+            )))
+            .insert_state(GameState::Playing)
+            .insert_resource(Score(0))
+            .insert_resource(Knockback(0.0))
+            .insert_resource(Tuning::default())
+            .add_event::<Explosion>()
+            .add_event::<ScorePopup>()
+            .add_event::<PlayerHit>()
+            .add_systems(
+                Update,
+                (
+                    play_boss_intro,
+                    (advance_boss_phase, move_boss).chain(),
+                    check_for_player_collisions,
+                ),
+            );
+        // Close enough to the boss to be touching it.
+        let player = app
+            .world_mut()
+            .spawn((
+                Transform::from_xyz(150.0, 0.0, 0.0),
+                Velocity::default(),
+                IsPlayer,
+                PlayerHitbox(10.0),
+                Health(3),
+            ))
+            .id();
+        let boss = app
+            .world_mut()
+            .spawn((
+                Transform::from_xyz(200.0, 0.0, 0.0),
+                Velocity::default(),
+                IsEnemy,
+                BodyRadius(BOSS_RADIUS),
+                EnemyHealth(BOSS_HP),
+                Boss {
+                    max_health: BOSS_HP,
+                },
+                BossPhase::Tracking,
+                BossIntro(Timer::from_seconds(INTRO_DURATION, TimerMode::Once)),
+            ))
+            .id();
+
+        // Just short of the intro's 2.5 s.
+        for _ in 0..12 {
+            app.update();
+            assert!(app.world().get::<BossIntro>(boss).is_some());
+            assert_eq!(app.world().get::<Velocity>(boss).unwrap().0, Vec2::ZERO);
+            assert_eq!(app.world().get::<Health>(player).unwrap().0, 3);
+        }
+        for _ in 0..3 {
+            app.update();
+        }
+        assert!(app.world().get::<BossIntro>(boss).is_none());
+        assert!(app.world().get::<Velocity>(boss).unwrap().0.x < 0.0);
+        // Once it's loose, touching it hurts.
+        assert!(app.world().get::<Health>(player).unwrap().0 < 3);
+    }
+
+    /// Frames the stand-in spawner has run on.
+    #[derive(Resource, Default)]
+    struct Spawns(u32);
+
+    #[test]
+    fn spawning_waits_out_the_boss_death_sequence() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                100,
+            )))
+            .insert_state(GameState::Playing)
+            .add_event::<EnemyKilled>()
+            .add_event::<Explosion>()
+            .init_resource::<Spawns>()
+            .add_systems(
+                Update,
+                (
+                    start_death_sequence,
+                    tick_death_sequence.run_if(in_boss_death),
+                    (|mut spawns: ResMut<Spawns>| spawns.0 += 1).run_if(not(in_boss_death)),
+                    scale_game_clock,
+                )
+                    .chain(),
+            );
+        app.world_mut().send_event(EnemyKilled {
+            position: Vec2::ZERO,
+            radius: BOSS_RADIUS,
+            drop: None,
+            boss: true,
+        });
+        app.update();
+        assert!(app.world().contains_resource::<BossDeathSequence>());
+        assert_eq!(
+            app.world().resource::<Time<Virtual>>().relative_speed(),
+            DEATH_TIME_SCALE
+        );
+
+        let mut frames = 1;
+        while app.world().contains_resource::<BossDeathSequence>() {
+            assert_eq!(app.world().resource::<Spawns>().0, 0);
+            app.update();
+            frames += 1;
+            assert!(frames < 20, "the death sequence never ended");
+        }
+        // A second of real time, a tenth at a time.
+        assert!(frames >= 10);
+        // Spawning picks up in the frame the sequence ends.
+        assert_eq!(app.world().resource::<Spawns>().0, 1);
+        assert_eq!(
+            app.world().resource::<Time<Virtual>>().relative_speed(),
+            1.0
+        );
+    }
+
+    #[test]
+    fn carrying_on_counts_the_next_boss_from_the_score() {
+        let mut world = World::new();
+        world.insert_resource(BossSpawner {
+            next_score: BOSS_SCORE_INTERVAL,
+        });
+        world.insert_resource(Score(2_480));
+        world.run_system_once(carry_boss_spawner).unwrap();
+        assert_eq!(
+            world.resource::<BossSpawner>().next_score,
+            2_480 + BOSS_SCORE_INTERVAL
+        );
+    }
+}
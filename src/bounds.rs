@@ -0,0 +1,262 @@
+//! What happens to things that reach the edge of the play area.
+//!
+//! `BoundsPolicy` sets the behavior for the player, enemies and bullets, and a
+//! `BoundsBehavior` component on an entity overrides it for just that entity.
+//!
+//! With `--border-damage`, a band along the edge also hurts the player.
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    DESPAWN_MARGIN, EnemyBullet, GameState, Health, IsBullet, IsEnemy, IsPlayer, Mine, PlayerHit,
+    PlayerHitbox, Velocity, debug::god_mode, game_time::GameTime, play_area::PlayArea,
+    player_bounds, practice::in_practice, update,
+};
+
+pub struct BoundsPlugin;
+
+impl Plugin for BoundsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(BoundsPolicy {
+            player: BoundsBehavior::Clamp,
+            enemies: BoundsBehavior::Despawn,
+            bullets: BoundsBehavior::Despawn,
+        })
+        .add_systems(
+            Update,
+            (
+                apply_bounds.after(update),
+                burn_border.after(apply_bounds).run_if(
+                    resource_exists::<BorderDamage>
+                        .and(not(god_mode))
+                        .and(not(in_practice)),
+                ),
+            )
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum BoundsBehavior {
+    /// Removed once well past the edge.
+    Despawn,
+    /// Stopped at the edge.
+    Clamp,
+    /// Carried over to the opposite edge once well past it.
+    Wrap,
+    /// Turned back on reaching the edge.
+    Bounce,
+}
+
+/// The default `BoundsBehavior` for each kind of entity. Mines count as
+/// enemies, and enemy bullets as bullets.
+#[derive(Resource)]
+pub struct BoundsPolicy {
+    pub player: BoundsBehavior,
+    pub enemies: BoundsBehavior,
+    pub bullets: BoundsBehavior,
+}
+
+/// Damage dealt to the player while its hitbox is within `band` of the edge.
+/// Only present when enabled at launch.
+#[derive(Resource)]
+pub struct BorderDamage {
+    /// Width of the damaging band, in pixels.
+    band: f32,
+    /// Health lost per second spent in the band.
+    dps: f32,
+    /// Damage built up toward the next whole point of health.
+    pending: f32,
+}
+
+impl Default for BorderDamage {
+    fn default() -> Self {
+        Self {
+            band: 24.0,
+            dps: 0.5,
+            pending: 0.0,
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+pub fn apply_bounds(
+    mut commands: Commands,
+    policy: Res<BoundsPolicy>,
+    play_area: Res<PlayArea>,
+    border_damage: Option<Res<BorderDamage>>,
+    mut query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Velocity,
+            Option<&BoundsBehavior>,
+            Option<&PlayerHitbox>,
+            Has<IsPlayer>,
+            Has<IsBullet>,
+            Has<EnemyBullet>,
+        ),
+        Or<(
+            With<IsPlayer>,
+            With<IsEnemy>,
+            With<IsBullet>,
+            With<EnemyBullet>,
+            With<Mine>,
+            With<BoundsBehavior>,
+        )>,
+    >,
+) {
+    let edge = Vec2::new(play_area.half_width, play_area.half_height);
+    for (entity, mut transform, mut vel, behavior, hitbox, is_player, is_bullet, is_enemy_bullet) in
+        query.iter_mut()
+    {
+        let behavior = behavior.copied().unwrap_or(if is_player {
+            policy.player
+        } else if is_bullet || is_enemy_bullet {
+            policy.bullets
+        } else {
+            policy.enemies
+        });
+        // The player keeps its whole sprite on screen, unless the border is
+        // damaging, where only its hitbox is held back so it can stray into
+        // the band. Everything else spawns just off screen, so it's only
+        // removed or wrapped once well past it.
+        let extent = match (is_player, behavior) {
+            (true, _) => match (&border_damage, hitbox) {
+                (Some(_), Some(hitbox)) => edge - hitbox.0,
+                _ => player_bounds(&play_area),
+            },
+            (false, BoundsBehavior::Despawn | BoundsBehavior::Wrap) => edge + DESPAWN_MARGIN,
+            (false, BoundsBehavior::Clamp | BoundsBehavior::Bounce) => edge,
+        };
+        let position = transform.translation.truncate();
+
+        match behavior {
+            BoundsBehavior::Despawn => {
+                if position.x.abs() > extent.x || position.y.abs() > extent.y {
+                    commands.entity(entity).despawn();
+                }
+            }
+            BoundsBehavior::Clamp => {
+                let clamped = position.clamp(-extent, extent);
+                if clamped.x != position.x {
+                    vel.0.x = 0.0;
+                }
+                if clamped.y != position.y {
+                    vel.0.y = 0.0;
+                }
+                transform.translation = clamped.extend(transform.translation.z);
+            }
+            BoundsBehavior::Wrap => {
+                let mut wrapped = position;
+                for axis in 0..2 {
+                    if wrapped[axis] > extent[axis] {
+                        wrapped[axis] -= extent[axis] * 2.0;
+                    } else if wrapped[axis] < -extent[axis] {
+                        wrapped[axis] += extent[axis] * 2.0;
+                    }
+                }
+                transform.translation = wrapped.extend(transform.translation.z);
+            }
+            BoundsBehavior::Bounce => {
+                // Only turn things heading further out, so anything arriving
+                // from off screen can still come in.
+                for axis in 0..2 {
+                    if position[axis].abs() > extent[axis]
+                        && position[axis].signum() == vel.0[axis].signum()
+                    {
+                        vel.0[axis] = -vel.0[axis];
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Wears down the player's health while its hitbox touches the border band.
+pub fn burn_border(
+    mut border_damage: ResMut<BorderDamage>,
+    mut query: Query<(&Transform, &PlayerHitbox, &mut Health), With<IsPlayer>>,
+    mut player_hits: EventWriter<PlayerHit>,
+    mut game_state: ResMut<NextState<GameState>>,
+    play_area: Res<PlayArea>,
+    time: Res<GameTime>,
+) {
+    let Ok((transform, hitbox, mut health)) = query.single_mut() else {
+        return;
+    };
+    let reach = transform.translation.truncate().abs() + hitbox.0;
+    let inner = Vec2::new(play_area.half_width, play_area.half_height) - border_damage.band;
+    if reach.x < inner.x && reach.y < inner.y {
+        border_damage.pending = 0.0;
+        return;
+    }
+
+    border_damage.pending += border_damage.dps * time.delta_secs();
+    let damage = border_damage.pending.floor();
+    if damage < 1.0 {
+        return;
+    }
+    border_damage.pending -= damage;
+    player_hits.write(PlayerHit);
+    health.0 = health.0.saturating_sub(damage as u32);
+    if health.0 == 0 {
+        game_state.set(GameState::GameOver);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    fn bounds_world(enemies: BoundsBehavior) -> World {
+        let mut world = World::new();
+        world.insert_resource(BoundsPolicy {
+            player: BoundsBehavior::Clamp,
+            enemies,
+            bullets: BoundsBehavior::Despawn,
+        });
+        world.insert_resource(PlayArea {
+            half_width: 400.0,
+            half_height: 300.0,
+        });
+        world
+    }
+
+    #[test]
+    fn a_bouncing_enemy_turns_back_at_the_top() {
+        let mut world = bounds_world(BoundsBehavior::Bounce);
+        let fish = world
+            .spawn((
+                Transform::from_xyz(0.0, 305.0, 0.0),
+                Velocity(Vec2::new(-50.0, 80.0)),
+                IsEnemy,
+            ))
+            .id();
+        world.run_system_once(apply_bounds).unwrap();
+
+        assert_eq!(
+            world.get::<Velocity>(fish).unwrap().0,
+            Vec2::new(-50.0, -80.0)
+        );
+    }
+
+    #[test]
+    fn by_default_an_enemy_past_the_top_is_removed() {
+        let mut world = bounds_world(BoundsBehavior::Despawn);
+        let fish = world
+            .spawn((
+                Transform::from_xyz(0.0, 300.0 + DESPAWN_MARGIN + 5.0, 0.0),
+                Velocity(Vec2::new(-50.0, 80.0)),
+                IsEnemy,
+            ))
+            .id();
+        world.run_system_once(apply_bounds).unwrap();
+
+        assert!(world.get_entity(fish).is_err());
+    }
+}
@@ -0,0 +1,177 @@
+//! Keeps the duck in view when the play area is bigger than what the camera
+//! shows.
+//!
+//! The camera eases toward the duck once it strays outside a dead zone around
+//! the middle of the view, so small movements leave the view still, and never
+//! shows past the edges of the play area. When the whole play area fits on
+//! screen, as it does in the normal window, the camera just stays centered.
+//!
+//! The mouse wheel zooms in and out during a run. Zoom only changes the view;
+//! the play area, and everything spawned or kept within it, stays the same
+//! size in the world. When a run ends the camera goes back to the middle at
+//! its usual zoom, so the end screen's text is all in view.
+
+use bevy::{
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+};
+
+use crate::{GameState, IsPlayer, bounds::apply_bounds, play_area::PlayArea, tuning::Tuning};
+
+const MIN_ZOOM: f32 = 0.75;
+const MAX_ZOOM: f32 = 2.0;
+/// Factor the zoom changes by per notch of the mouse wheel.
+const ZOOM_STEP: f32 = 1.1;
+/// Pixels of smooth scrolling that count as one notch.
+const PIXELS_PER_NOTCH: f32 = 40.0;
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(CameraZoom(1.0))
+            .add_systems(
+                Update,
+                (
+                    (zoom_with_wheel, follow_player.after(apply_bounds))
+                        .chain()
+                        .run_if(in_state(GameState::Playing)),
+                    apply_zoom.run_if(resource_changed::<CameraZoom>),
+                ),
+            )
+            .add_systems(OnEnter(GameState::Menu), recenter_camera);
+        for ended in [GameState::GameOver, GameState::Victory] {
+            app.add_systems(OnEnter(ended), (reset_zoom, recenter_camera));
+        }
+    }
+}
+
+/// How far the camera is zoomed in, from `MIN_ZOOM` to `MAX_ZOOM`. Above 1.0
+/// shows less of the field, larger.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct CameraZoom(pub f32);
+
+/// Where the camera is headed: as close to `camera` as keeps `player` within
+/// `dead_zone` of it.
+fn follow_target(camera: Vec2, player: Vec2, dead_zone: Vec2) -> Vec2 {
+    let offset = player - camera;
+    camera + offset - offset.clamp(-dead_zone, dead_zone)
+}
+
+/// Limits `position` so a view `half_size` across stays inside the play area.
+fn clamp_to_play_area(position: Vec2, half_size: Vec2, play_area: &PlayArea) -> Vec2 {
+    let max = (Vec2::new(play_area.half_width, play_area.half_height) - half_size).max(Vec2::ZERO);
+    position.clamp(-max, max)
+}
+
+#[allow(clippy::type_complexity)]
+fn follow_player(
+    mut camera_query: Query<(&mut Transform, &Projection), (With<Camera2d>, Without<IsPlayer>)>,
+    player_query: Query<&Transform, With<IsPlayer>>,
+    play_area: Res<PlayArea>,
+    tuning: Res<Tuning>,
+    time: Res<Time>,
+) {
+    let (Ok((mut camera_transform, projection)), Ok(player_transform)) =
+        (camera_query.single_mut(), player_query.single())
+    else {
+        return;
+    };
+    let Projection::Orthographic(orthographic) = projection else {
+        return;
+    };
+    let camera = camera_transform.translation.truncate();
+    let dead_zone = Vec2::from(tuning.camera_dead_zone);
+    let target = follow_target(camera, player_transform.translation.truncate(), dead_zone);
+    // Framerate-independent easing, closing the same share of the gap each second.
+    let blend = 1.0 - (-tuning.camera_follow_rate * time.delta_secs()).exp();
+    let position = clamp_to_play_area(
+        camera.lerp(target, blend),
+        orthographic.area.half_size(),
+        &play_area,
+    );
+    if position != camera {
+        camera_transform.translation = position.extend(camera_transform.translation.z);
+    }
+}
+
+fn zoom_with_wheel(mut wheel: EventReader<MouseWheel>, mut zoom: ResMut<CameraZoom>) {
+    let notches: f32 = wheel
+        .read()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / PIXELS_PER_NOTCH,
+        })
+        .sum();
+    if notches != 0.0 {
+        let zoomed = (zoom.0 * ZOOM_STEP.powf(notches)).clamp(MIN_ZOOM, MAX_ZOOM);
+        zoom.set_if_neq(CameraZoom(zoomed));
+    }
+}
+
+fn apply_zoom(zoom: Res<CameraZoom>, mut camera_query: Query<&mut Projection, With<Camera2d>>) {
+    for mut projection in camera_query.iter_mut() {
+        if let Projection::Orthographic(orthographic) = &mut *projection {
+            orthographic.scale = 1.0 / zoom.0.clamp(MIN_ZOOM, MAX_ZOOM);
+        }
+    }
+}
+
+fn reset_zoom(mut zoom: ResMut<CameraZoom>) {
+    zoom.set_if_neq(CameraZoom(1.0));
+}
+
+fn recenter_camera(mut camera_query: Query<&mut Transform, With<Camera2d>>) {
+    for mut transform in camera_query.iter_mut() {
+        transform.translation.x = 0.0;
+        transform.translation.y = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::state::app::StatesPlugin;
+
+    use super::*;
+
+    #[test]
+    fn the_camera_follows_only_past_the_dead_zone() {
+        let dead_zone = Vec2::new(50.0, 30.0);
+        assert_eq!(
+            follow_target(Vec2::ZERO, Vec2::new(40.0, -20.0), dead_zone),
+            Vec2::ZERO
+        );
+        assert_eq!(
+            follow_target(Vec2::ZERO, Vec2::new(80.0, -50.0), dead_zone),
+            Vec2::new(30.0, -20.0)
+        );
+    }
+
+    #[test]
+    fn the_end_screen_is_shown_centered_and_unzoomed() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, CameraPlugin))
+            .insert_state(GameState::GameOver)
+            .insert_resource(CameraZoom(MAX_ZOOM));
+        let camera = app
+            .world_mut()
+            .spawn((
+                Camera2d,
+                Transform::from_xyz(120.0, -80.0, 0.0),
+                Projection::Orthographic(OrthographicProjection {
+                    scale: 1.0 / MAX_ZOOM,
+                    ..OrthographicProjection::default_2d()
+                }),
+            ))
+            .id();
+        app.update();
+
+        assert_eq!(*app.world().resource::<CameraZoom>(), CameraZoom(1.0));
+        let camera = app.world().entity(camera);
+        assert_eq!(camera.get::<Transform>().unwrap().translation, Vec3::ZERO);
+        let Some(Projection::Orthographic(orthographic)) = camera.get::<Projection>() else {
+            panic!("expected an orthographic camera");
+        };
+        assert_eq!(orthographic.scale, 1.0);
+    }
+}
@@ -0,0 +1,182 @@
+//! On-screen captions for important sounds, for players who can't hear them.
+//!
+//! Captions are raised by the same events that play the sounds and stack in
+//! the top right corner, below the level progress and the profiler overlay,
+//! fading out after a moment.
+
+use bevy::prelude::*;
+
+use crate::{
+    EnemyKilled, GameState, PlayerHit, apply_explosions, boss::Boss, power_ups::PowerUpCollected,
+    settings::Captions,
+};
+
+const CAPTION_DURATION: f32 = 1.5;
+/// Time at the end of a caption's life spent fading out.
+const CAPTION_FADE: f32 = 0.5;
+/// Older captions are dropped to stay within this many.
+const MAX_CAPTIONS: usize = 4;
+/// Clears the level progress and the profiler overlay, which share the
+/// corner.
+const CAPTION_AREA_TOP: f32 = 90.0;
+
+pub struct CaptionsPlugin;
+
+impl Plugin for CaptionsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, spawn_caption_area)
+            .add_systems(OnEnter(GameState::Menu), clear_captions)
+            .add_systems(
+                Update,
+                (
+                    raise_captions
+                        .after(apply_explosions)
+                        .run_if(|captions: Res<Captions>| captions.0),
+                    fade_captions,
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Holds the captions, newest at the bottom.
+#[derive(Component)]
+struct CaptionArea;
+
+#[derive(Component)]
+struct Caption(Timer);
+
+fn spawn_caption_area(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(CAPTION_AREA_TOP),
+            right: Val::Px(10.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::FlexEnd,
+            row_gap: Val::Px(4.0),
+            ..default()
+        },
+        CaptionArea,
+    ));
+}
+
+fn raise_captions(
+    mut commands: Commands,
+    mut kills: EventReader<EnemyKilled>,
+    mut hits: EventReader<PlayerHit>,
+    mut power_ups: EventReader<PowerUpCollected>,
+    boss_query: Query<(), Added<Boss>>,
+    area_query: Query<(Entity, Option<&Children>), With<CaptionArea>>,
+) {
+    // Several of the same sound in one frame are heard as one.
+    let mut texts = Vec::new();
+    if kills.read().count() > 0 {
+        texts.push("[enemy destroyed]");
+    }
+    if hits.read().count() > 0 {
+        texts.push("[player hit]");
+    }
+    if power_ups.read().count() > 0 {
+        texts.push("[power-up]");
+    }
+    if !boss_query.is_empty() {
+        texts.push("[boss appears]");
+    }
+    if texts.is_empty() {
+        return;
+    }
+    let Ok((area, children)) = area_query.single() else {
+        return;
+    };
+
+    if let Some(children) = children {
+        let overflow = (children.len() + texts.len()).saturating_sub(MAX_CAPTIONS);
+        for child in children.iter().take(overflow) {
+            commands.entity(child).despawn();
+        }
+    }
+    commands.entity(area).with_children(|parent| {
+        for text in texts {
+            parent.spawn((
+                Text::new(text),
+                TextFont {
+                    font_size: 20.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                Caption(Timer::from_seconds(CAPTION_DURATION, TimerMode::Once)),
+            ));
+        }
+    });
+}
+
+fn fade_captions(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Caption, &mut TextColor)>,
+    time: Res<Time>,
+) {
+    for (entity, mut caption, mut color) in query.iter_mut() {
+        caption.0.tick(time.delta());
+        if caption.0.finished() {
+            commands.entity(entity).despawn();
+        } else {
+            let alpha = (caption.0.remaining_secs() / CAPTION_FADE).min(1.0);
+            color.0.set_alpha(alpha);
+        }
+    }
+}
+
+fn clear_captions(mut commands: Commands, query: Query<Entity, With<Caption>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::state::app::StatesPlugin;
+
+    use super::*;
+
+    fn captioned_texts(app: &mut App) -> Vec<String> {
+        app.world_mut()
+            .query_filtered::<&Text, With<Caption>>()
+            .iter(app.world())
+            .map(|text| text.0.clone())
+            .collect()
+    }
+
+    #[test]
+    fn sounds_are_captioned_when_enabled() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, CaptionsPlugin))
+            .insert_state(GameState::Playing)
+            .insert_resource(Captions(true))
+            .add_event::<EnemyKilled>()
+            .add_event::<PlayerHit>()
+            .add_event::<PowerUpCollected>();
+        app.world_mut().send_event(PlayerHit);
+        app.world_mut().send_event(PowerUpCollected);
+        app.update();
+
+        let texts = captioned_texts(&mut app);
+        assert_eq!(texts, ["[player hit]", "[power-up]"]);
+    }
+
+    #[test]
+    fn nothing_is_captioned_when_disabled() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, CaptionsPlugin))
+            .insert_state(GameState::Playing)
+            .insert_resource(Captions(false))
+            .add_event::<EnemyKilled>()
+            .add_event::<PlayerHit>()
+            .add_event::<PowerUpCollected>();
+        app.world_mut().send_event(PlayerHit);
+        app.update();
+
+        assert!(captioned_texts(&mut app).is_empty());
+    }
+}
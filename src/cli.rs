@@ -0,0 +1,192 @@
+//! Command-line options for configuring a launch.
+
+use std::path::PathBuf;
+
+use crate::DifficultyPreset;
+
+pub const USAGE: &str = "\
+Usage: duckgame [OPTIONS]
+
+Options:
+  --seed <n>                      Seed for the gameplay RNG
+  --difficulty <easy|normal|hard> Starting difficulty (default: normal)
+  --windowed-size <W>x<H>         Window size in pixels (default: 800x600)
+  --mute                          Start with audio muted
+  --border-damage                 Hurt the player near the screen edges
+  --centered                      Keep the duck centered and scroll the field
+  --waves                         Spawn enemies on the schedule in assets/waves.ron
+  --batched-popups                Draw score popups as a single mesh
+  --replay <file>                 Play back a recorded run
+  --ghost <file>                  Race a translucent ghost of a recorded run
+  --grid-spacing <px>             Spacing of the F4 debug grid (default: 50)
+  --debug                         Enable debug cheats (always on in debug builds)
+  --stress <n>                    Benchmark with n fish and n bullets on the field
+  -h, --help                      Print this message";
+
+#[derive(Debug, Default, PartialEq)]
+pub struct LaunchConfig {
+    pub seed: Option<u64>,
+    pub difficulty: DifficultyPreset,
+    pub window_size: Option<(u32, u32)>,
+    pub mute: bool,
+    pub border_damage: bool,
+    pub centered: bool,
+    pub waves: bool,
+    pub batched_popups: bool,
+    pub replay: Option<PathBuf>,
+    pub ghost: Option<PathBuf>,
+    pub grid_spacing: Option<f32>,
+    pub debug: bool,
+    pub stress: Option<usize>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CliError {
+    Help,
+    Invalid(String),
+}
+
+pub fn parse_args(args: impl IntoIterator<Item = String>) -> Result<LaunchConfig, CliError> {
+    let mut config = LaunchConfig::default();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| CliError::Invalid(format!("{name} requires a value")))
+        };
+        match arg.as_str() {
+            "-h" | "--help" => return Err(CliError::Help),
+            "--seed" => {
+                let seed = value("--seed")?;
+                config.seed = Some(
+                    seed.parse()
+                        .map_err(|_| CliError::Invalid(format!("invalid seed `{seed}`")))?,
+                );
+            }
+            "--difficulty" => {
+                config.difficulty = match value("--difficulty")?.as_str() {
+                    "easy" => DifficultyPreset::Easy,
+                    "normal" => DifficultyPreset::Normal,
+                    "hard" => DifficultyPreset::Hard,
+                    other => {
+                        return Err(CliError::Invalid(format!("invalid difficulty `{other}`")));
+                    }
+                };
+            }
+            "--windowed-size" => {
+                let size = value("--windowed-size")?;
+                config.window_size = Some(parse_size(&size).ok_or_else(|| {
+                    CliError::Invalid(format!("invalid window size `{size}`, expected WxH"))
+                })?);
+            }
+            "--mute" => config.mute = true,
+            "--border-damage" => config.border_damage = true,
+            "--centered" => config.centered = true,
+            "--waves" => config.waves = true,
+            "--batched-popups" => config.batched_popups = true,
+            "--replay" => config.replay = Some(PathBuf::from(value("--replay")?)),
+            "--ghost" => config.ghost = Some(PathBuf::from(value("--ghost")?)),
+            "--debug" => config.debug = true,
+            "--stress" => {
+                let count = value("--stress")?;
+                config.stress = Some(
+                    count
+                        .parse()
+                        .ok()
+                        .filter(|&count| count > 0)
+                        .ok_or_else(|| {
+                            CliError::Invalid(format!("invalid stress count `{count}`"))
+                        })?,
+                );
+            }
+            "--grid-spacing" => {
+                let spacing = value("--grid-spacing")?;
+                config.grid_spacing = Some(
+                    spacing
+                        .parse()
+                        .ok()
+                        .filter(|&spacing: &f32| spacing.is_finite() && spacing > 0.0)
+                        .ok_or_else(|| {
+                            CliError::Invalid(format!("invalid grid spacing `{spacing}`"))
+                        })?,
+                );
+            }
+            other => return Err(CliError::Invalid(format!("unknown argument `{other}`"))),
+        }
+    }
+
+    Ok(config)
+}
+
+fn parse_size(size: &str) -> Option<(u32, u32)> {
+    let (width, height) = size.split_once('x')?;
+    let width = width.parse().ok().filter(|&w| w > 0)?;
+    let height = height.parse().ok().filter(|&h| h > 0)?;
+    Some((width, height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<LaunchConfig, CliError> {
+        parse_args(args.iter().map(|arg| arg.to_string()))
+    }
+
+    #[test]
+    fn flags_and_values_are_read() {
+        let config = parse(&[
+            "--seed",
+            "42",
+            "--difficulty",
+            "hard",
+            "--windowed-size",
+            "1024x768",
+            "--mute",
+            "--waves",
+            "--replay",
+            "runs/latest.ron",
+        ])
+        .unwrap();
+        assert_eq!(
+            config,
+            LaunchConfig {
+                seed: Some(42),
+                difficulty: DifficultyPreset::Hard,
+                window_size: Some((1024, 768)),
+                mute: true,
+                waves: true,
+                replay: Some(PathBuf::from("runs/latest.ron")),
+                ..LaunchConfig::default()
+            }
+        );
+        assert_eq!(parse(&[]).unwrap(), LaunchConfig::default());
+        assert_eq!(parse(&["--help"]), Err(CliError::Help));
+    }
+
+    #[test]
+    fn sizes_need_a_positive_width_and_height() {
+        assert_eq!(parse_size("800x600"), Some((800, 600)));
+        for size in ["800", "0x600", "800x", "x600", "800x-1", "axb"] {
+            assert_eq!(parse_size(size), None, "accepted {size}");
+        }
+    }
+
+    #[test]
+    fn bad_values_are_reported() {
+        for (args, message) in [
+            (&["--seed", "many"][..], "invalid seed `many`"),
+            (&["--difficulty", "brutal"], "invalid difficulty `brutal`"),
+            (
+                &["--windowed-size", "big"],
+                "invalid window size `big`, expected WxH",
+            ),
+            (&["--stress", "0"], "invalid stress count `0`"),
+            (&["--seed"], "--seed requires a value"),
+            (&["--fast"], "unknown argument `--fast`"),
+        ] {
+            assert_eq!(parse(args), Err(CliError::Invalid(message.to_string())));
+        }
+    }
+}
@@ -0,0 +1,262 @@
+//! Coins, earned one per enemy destroyed and kept between launches, and the
+//! continue they can buy after dying.
+//!
+//! The game over screen offers a continue for a short while. Taking it revives
+//! the player where they fell, at full health and briefly invincible. Each
+//! continue in a run costs more than the last.
+
+use std::fs;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    EnemyKilled, GameOverText, GameState, Health, Invincible, IsPlayer, MaxHealth, RunMode,
+    Velocity,
+    debug::cheats_used,
+    modifiers::RunModifiers,
+    practice::in_practice,
+    replay::ReplayPlayback,
+    start_run,
+    transition::{FadeTo, in_transition},
+};
+
+const COINS_PATH: &str = "coins.ron";
+const BASE_CONTINUE_COST: u32 = 10;
+/// Time the continue is on offer for.
+const CONTINUE_COUNTDOWN: f32 = 10.0;
+const CONTINUE_INVINCIBILITY: f32 = 3.0;
+
+pub struct CoinsPlugin;
+
+impl Plugin for CoinsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Coins::load())
+            .init_resource::<ContinuesUsed>()
+            .add_systems(OnExit(GameState::Menu), reset_continues.after(start_run))
+            .add_systems(
+                Update,
+                // Practice dummies come back forever, so they're worth nothing.
+                earn_coins.run_if(
+                    in_state(GameState::Playing)
+                        .and(not(resource_exists::<ReplayPlayback>))
+                        .and(not(in_practice)),
+                ),
+            );
+        for state in [GameState::Menu, GameState::GameOver, GameState::Victory] {
+            app.add_systems(OnEnter(state), save_coins.run_if(not(cheats_used)));
+        }
+        app.add_systems(
+            OnEnter(GameState::Menu),
+            discard_cheated_coins.run_if(cheats_used),
+        );
+        app.add_systems(
+            OnEnter(GameState::GameOver),
+            offer_continue.run_if(not(resource_exists::<ReplayPlayback>)),
+        )
+        .add_systems(
+            Update,
+            update_continue_prompt.run_if(in_state(GameState::GameOver).and(not(in_transition))),
+        );
+    }
+}
+
+#[derive(Resource, Default, Serialize, Deserialize)]
+pub struct Coins(pub u32);
+
+impl Coins {
+    fn load() -> Self {
+        let Ok(contents) = fs::read_to_string(COINS_PATH) else {
+            return Self::default();
+        };
+        ron::from_str(&contents).unwrap_or_else(|err| {
+            warn!("Ignoring unreadable {COINS_PATH}: {err}");
+            Self::default()
+        })
+    }
+
+    fn save(&self) {
+        let result = ron::ser::to_string(self)
+            .map_err(|err| err.to_string())
+            .and_then(|contents| fs::write(COINS_PATH, contents).map_err(|err| err.to_string()));
+        if let Err(err) = result {
+            error!("Failed to save coins to {COINS_PATH}: {err}");
+        }
+    }
+}
+
+/// Continues taken so far this run.
+#[derive(Resource, Default)]
+struct ContinuesUsed(u32);
+
+impl ContinuesUsed {
+    /// Doubles with every continue already taken.
+    fn next_cost(&self) -> u32 {
+        BASE_CONTINUE_COST.saturating_mul(2u32.saturating_pow(self.0))
+    }
+}
+
+/// The continue on offer, withdrawn once its timer runs out.
+#[derive(Component)]
+struct ContinuePrompt {
+    countdown: Timer,
+    /// Set once the player has tried to continue without enough coins.
+    refused: bool,
+}
+
+fn reset_continues(mut used: ResMut<ContinuesUsed>) {
+    used.0 = 0;
+}
+
+fn earn_coins(
+    mut coins: ResMut<Coins>,
+    mut kills: EventReader<EnemyKilled>,
+    modifiers: Res<RunModifiers>,
+) {
+    coins.0 += kills.read().count() as u32 * modifiers.coin_multiplier();
+}
+
+fn save_coins(coins: Res<Coins>) {
+    if coins.is_changed() {
+        coins.save();
+    }
+}
+
+/// Goes back to the coins saved before a run that cheated.
+fn discard_cheated_coins(mut coins: ResMut<Coins>) {
+    *coins = Coins::load();
+}
+
+/// Only a death can be continued from; running out of time or score can't.
+fn offer_continue(mut commands: Commands, player_query: Query<&Health, With<IsPlayer>>) {
+    if !player_query.iter().any(|health| health.0 == 0) {
+        return;
+    }
+    commands.spawn((
+        Text2d::default(),
+        TextFont {
+            font_size: 24.0,
+            ..default()
+        },
+        Transform::from_xyz(0.0, 100.0, 10.0),
+        ContinuePrompt {
+            countdown: Timer::from_seconds(CONTINUE_COUNTDOWN, TimerMode::Once),
+            refused: false,
+        },
+        GameOverText,
+    ));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn update_continue_prompt(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut coins: ResMut<Coins>,
+    mut used: ResMut<ContinuesUsed>,
+    mut mode: ResMut<RunMode>,
+    mut prompt_query: Query<(Entity, &mut ContinuePrompt, &mut Text2d)>,
+    mut player_query: Query<(Entity, &mut Health, &MaxHealth, &mut Velocity), With<IsPlayer>>,
+    mut fades: EventWriter<FadeTo>,
+    time: Res<Time>,
+) {
+    let Ok((prompt_entity, mut prompt, mut text)) = prompt_query.single_mut() else {
+        return;
+    };
+    if prompt.countdown.tick(time.delta()).finished() {
+        commands.entity(prompt_entity).despawn();
+        return;
+    }
+
+    let cost = used.next_cost();
+    if keyboard_input.just_pressed(KeyCode::KeyY) {
+        if coins.0 < cost {
+            prompt.refused = true;
+        } else if let Ok((entity, mut health, max_health, mut velocity)) = player_query.single_mut()
+        {
+            coins.0 -= cost;
+            used.0 += 1;
+            // A continued daily challenge is a second attempt, so it no
+            // longer counts.
+            if let RunMode::Daily { ranked, .. } = &mut *mode {
+                *ranked = false;
+            }
+            health.0 = max_health.0;
+            velocity.0 = Vec2::ZERO;
+            commands
+                .entity(entity)
+                .insert(Invincible(Timer::from_seconds(
+                    CONTINUE_INVINCIBILITY,
+                    TimerMode::Once,
+                )));
+            commands.entity(prompt_entity).despawn();
+            fades.write(FadeTo(GameState::Playing));
+            return;
+        }
+    }
+
+    let seconds = prompt.countdown.remaining_secs().ceil();
+    text.0 = if prompt.refused {
+        format!(
+            "Not enough coins to continue: {cost} needed, {} held - {seconds}",
+            coins.0
+        )
+    } else {
+        format!(
+            "Continue? Press Y for {cost} of your {} coins - {seconds}",
+            coins.0
+        )
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    /// A duck fallen with `coins` held, and the continue offered and taken.
+    fn continue_with(coins: u32) -> (World, Entity) {
+        let mut world = World::new();
+        world.insert_resource(Coins(coins));
+        world.init_resource::<ContinuesUsed>();
+        world.init_resource::<RunMode>();
+        world.init_resource::<Time>();
+        world.init_resource::<Events<FadeTo>>();
+        let mut keyboard_input = ButtonInput::<KeyCode>::default();
+        keyboard_input.press(KeyCode::KeyY);
+        world.insert_resource(keyboard_input);
+        let player = world
+            .spawn((Health(0), MaxHealth(3), Velocity::default(), IsPlayer))
+            .id();
+        world.run_system_once(offer_continue).unwrap();
+        world.run_system_once(update_continue_prompt).unwrap();
+        (world, player)
+    }
+
+    #[test]
+    fn continuing_revives_the_duck_for_its_cost() {
+        let (world, player) = continue_with(25);
+
+        assert_eq!(world.get::<Health>(player).unwrap().0, 3);
+        assert!(world.get::<Invincible>(player).is_some());
+        assert_eq!(world.resource::<Coins>().0, 25 - BASE_CONTINUE_COST);
+        assert_eq!(
+            world.resource::<ContinuesUsed>().next_cost(),
+            BASE_CONTINUE_COST * 2
+        );
+        let fades = world.resource::<Events<FadeTo>>();
+        assert!(matches!(
+            fades.iter_current_update_events().next(),
+            Some(FadeTo(GameState::Playing))
+        ));
+    }
+
+    #[test]
+    fn a_continue_is_refused_without_the_coins() {
+        let (world, player) = continue_with(BASE_CONTINUE_COST - 1);
+
+        assert_eq!(world.get::<Health>(player).unwrap().0, 0);
+        assert_eq!(world.resource::<Coins>().0, BASE_CONTINUE_COST - 1);
+        assert!(world.resource::<Events<FadeTo>>().is_empty());
+    }
+}
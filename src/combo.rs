@@ -0,0 +1,288 @@
+//! Kill streaks: kills in quick succession build a combo, announced with
+//! arcade-style callouts as it grows. Each callout comes with a sound cue.
+//!
+//! Every `KILLS_PER_MULTIPLIER` kills in a streak raise the score multiplier a
+//! level, up to `max_multiplier`. When the streak lapses the multiplier winds
+//! down a level at a time rather than dropping straight back to 1x.
+//!
+//! Taking a hit costs the whole combo: the streak ends and the multiplier
+//! drops straight back to 1x. Hits soaked up by invincibility frames never
+//! count as hits, so they leave the combo alone.
+
+use bevy::prelude::*;
+
+use crate::{
+    EnemyKilled, GameState, PlayerHit, apply_explosions, bounds::burn_border, check_for_collisions,
+    check_for_player_collisions, game_time::GameTime, settings::ReduceMotion, sound::SoundAssets,
+    start_run,
+};
+
+/// Time allowed between kills before the streak lapses.
+const COMBO_WINDOW: f32 = 1.5;
+const KILLS_PER_MULTIPLIER: u32 = 5;
+const MAX_MULTIPLIER: u32 = 5;
+/// Time between each level the multiplier loses once the streak has lapsed.
+const MULTIPLIER_DECAY_STEP: f32 = 0.5;
+const CALLOUT_DURATION: f32 = 1.0;
+const CALLOUT_BLINK_INTERVAL: f32 = 0.1;
+const CALLOUTS: [(u32, &str); 3] = [(2, "Double!"), (3, "Triple!"), (5, "Rampage!")];
+const CALLOUT_COLOR: Color = Color::srgb(1.0, 0.4, 0.1);
+const COMBO_LOST_COLOR: Color = Color::srgb(0.6, 0.6, 0.7);
+
+pub struct ComboPlugin;
+
+impl Plugin for ComboPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Combo>()
+            .add_systems(OnExit(GameState::Menu), reset_combo.after(start_run))
+            .add_systems(OnEnter(GameState::Menu), despawn_callouts)
+            .add_systems(
+                Update,
+                // Kills are scored with the multiplier as it stood at the
+                // start of the frame, before it moves, keeping replays in
+                // sync.
+                (tick_combo, count_kills, break_combo, update_callouts)
+                    .chain()
+                    .after(check_for_collisions)
+                    .after(apply_explosions)
+                    .after(check_for_player_collisions)
+                    .after(burn_border)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// The current kill streak, the time left to extend it, and the score
+/// multiplier it has built up.
+#[derive(Resource)]
+pub struct Combo {
+    pub streak: u32,
+    pub window: Timer,
+    /// Points from kills are multiplied by this. Never below 1.
+    pub multiplier: u32,
+    pub max_multiplier: u32,
+    /// Counts down to the multiplier's next step down after a lapse.
+    decay: Timer,
+}
+
+impl Combo {
+    /// A single kill doesn't make a combo; it takes a second within the window.
+    pub fn is_active(&self) -> bool {
+        self.streak >= 2
+    }
+}
+
+impl Default for Combo {
+    fn default() -> Self {
+        Self {
+            streak: 0,
+            window: Timer::from_seconds(COMBO_WINDOW, TimerMode::Once),
+            multiplier: 1,
+            max_multiplier: MAX_MULTIPLIER,
+            decay: Timer::from_seconds(MULTIPLIER_DECAY_STEP, TimerMode::Repeating),
+        }
+    }
+}
+
+#[derive(Component)]
+struct Callout(Timer);
+
+fn callout(text: &str, color: Color) -> impl Bundle {
+    (
+        Text2d::new(text),
+        TextFont {
+            font_size: 48.0,
+            ..default()
+        },
+        TextColor(color),
+        Transform::from_xyz(0.0, 100.0, 10.0),
+        Callout(Timer::from_seconds(CALLOUT_DURATION, TimerMode::Once)),
+    )
+}
+
+fn reset_combo(mut combo: ResMut<Combo>) {
+    *combo = Combo {
+        max_multiplier: combo.max_multiplier,
+        ..default()
+    };
+}
+
+fn tick_combo(mut combo: ResMut<Combo>, time: Res<GameTime>) {
+    if combo.streak > 0 {
+        if combo.window.tick(time.delta()).finished() {
+            combo.streak = 0;
+            combo.multiplier = combo.multiplier.saturating_sub(1).max(1);
+            combo.decay.reset();
+        }
+    } else if combo.multiplier > 1 && combo.decay.tick(time.delta()).just_finished() {
+        combo.multiplier -= 1;
+    }
+}
+
+fn count_kills(
+    mut commands: Commands,
+    mut kills: EventReader<EnemyKilled>,
+    mut combo: ResMut<Combo>,
+    sounds: Res<SoundAssets>,
+    callout_query: Query<Entity, With<Callout>>,
+) {
+    for _ in kills.read() {
+        combo.streak += 1;
+        combo.window.reset();
+        if combo.streak.is_multiple_of(KILLS_PER_MULTIPLIER) {
+            combo.multiplier = (combo.multiplier + 1).min(combo.max_multiplier.max(1));
+        }
+
+        let Some((_, text)) = CALLOUTS
+            .iter()
+            .find(|(threshold, _)| *threshold == combo.streak)
+        else {
+            continue;
+        };
+        // A new callout replaces the last rather than piling on top of it.
+        for entity in callout_query.iter() {
+            commands.entity(entity).despawn();
+        }
+        commands.spawn(callout(text, CALLOUT_COLOR));
+        commands.spawn((
+            AudioPlayer::new(sounds.streak.clone()),
+            PlaybackSettings::DESPAWN,
+        ));
+    }
+}
+
+fn break_combo(
+    mut commands: Commands,
+    mut hits: EventReader<PlayerHit>,
+    mut combo: ResMut<Combo>,
+    callout_query: Query<Entity, With<Callout>>,
+) {
+    if hits.read().count() == 0 || (combo.streak == 0 && combo.multiplier == 1) {
+        return;
+    }
+    *combo = Combo {
+        max_multiplier: combo.max_multiplier,
+        ..default()
+    };
+    for entity in callout_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    commands.spawn(callout("Combo Lost", COMBO_LOST_COLOR));
+}
+
+fn update_callouts(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Callout, &mut Visibility)>,
+    reduce_motion: Res<ReduceMotion>,
+    time: Res<Time>,
+) {
+    for (entity, mut callout, mut visibility) in query.iter_mut() {
+        callout.0.tick(time.delta());
+        if callout.0.finished() {
+            commands.entity(entity).despawn();
+        } else if reduce_motion.0
+            || ((callout.0.elapsed_secs() / CALLOUT_BLINK_INTERVAL) as u32).is_multiple_of(2)
+        {
+            *visibility = Visibility::Inherited;
+        } else {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
+
+fn despawn_callouts(mut commands: Commands, query: Query<Entity, With<Callout>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{ecs::system::RunSystemOnce, state::app::StatesPlugin, time::TimeUpdateStrategy};
+
+    use super::*;
+    use crate::game_time::GameTimePlugin;
+
+    #[test]
+    fn a_lapse_costs_one_multiplier_level() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, GameTimePlugin))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                100,
+            )))
+            .insert_state(GameState::Playing)
+            .insert_resource(Combo {
+                streak: 12,
+                multiplier: 4,
+                ..default()
+            })
+            .add_systems(Update, tick_combo);
+
+        let mut lapsed_at = None;
+        for _ in 0..20 {
+            app.update();
+            let combo = app.world().resource::<Combo>();
+            if combo.streak == 0 {
+                lapsed_at = Some(combo.multiplier);
+                break;
+            }
+            assert_eq!(combo.multiplier, 4);
+        }
+        assert_eq!(lapsed_at, Some(3));
+        // The next level only goes after a step of decay.
+        app.update();
+        assert_eq!(app.world().resource::<Combo>().multiplier, 3);
+    }
+
+    #[test]
+    fn a_hit_drops_the_multiplier_straight_back() {
+        let mut world = World::new();
+        world.insert_resource(Combo {
+            streak: 12,
+            multiplier: 3,
+            ..default()
+        });
+        world.init_resource::<Events<PlayerHit>>();
+        world.send_event(PlayerHit);
+        world.run_system_once(break_combo).unwrap();
+
+        let combo = world.resource::<Combo>();
+        assert_eq!((combo.streak, combo.multiplier), (0, 1));
+        let mut callouts = world.query_filtered::<&Text2d, With<Callout>>();
+        let texts: Vec<_> = callouts.iter(&world).map(|text| text.0.clone()).collect();
+        assert_eq!(texts, ["Combo Lost"]);
+    }
+
+    #[test]
+    fn three_quick_kills_call_out_a_triple() {
+        let mut world = World::new();
+        world.init_resource::<Combo>();
+        world.init_resource::<Events<EnemyKilled>>();
+        world.insert_resource(SoundAssets {
+            shot: Handle::default(),
+            pop: Handle::default(),
+            crit: Handle::default(),
+            streak: Handle::default(),
+            boss_sting: Handle::default(),
+        });
+        for _ in 0..3 {
+            world.send_event(EnemyKilled {
+                position: Vec2::ZERO,
+                radius: 10.0,
+                drop: None,
+                boss: false,
+            });
+            world.run_system_once(count_kills).unwrap();
+            world.resource_mut::<Events<EnemyKilled>>().clear();
+        }
+
+        let mut callouts = world.query_filtered::<&Text2d, With<Callout>>();
+        let texts: Vec<_> = callouts.iter(&world).map(|text| text.0.clone()).collect();
+        assert_eq!(texts, ["Triple!"]);
+        // One cue for the double, one for the triple.
+        let mut cues = world.query::<&AudioPlayer>();
+        assert_eq!(cues.iter(&world).count(), 2);
+    }
+}
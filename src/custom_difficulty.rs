@@ -0,0 +1,142 @@
+//! A custom difficulty profile, tuned with sliders on the pause menu.
+//!
+//! Each slider scales one aspect of a run on top of the `Difficulty` ramp. The
+//! profile is fixed when a run starts, so changes made mid-run apply from the
+//! next one. Daily challenges ignore it so every player gets the same run.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{GameState, PLAYER_MAX_HEALTH, RunMode, replay::ReplayPlayback, start_run};
+
+pub struct CustomDifficultyPlugin;
+
+impl Plugin for CustomDifficultyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CustomDifficulty>()
+            .insert_resource(RunDifficulty(CustomDifficulty::default()))
+            .add_systems(
+                OnExit(GameState::Menu),
+                select_run_difficulty.before(start_run),
+            );
+    }
+}
+
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CustomDifficulty {
+    /// Scales how often enemies spawn.
+    pub spawn_rate: f32,
+    /// Scales how fast enemies move.
+    pub enemy_speed: f32,
+    /// Multiplies the health of fish and mines.
+    pub enemy_health: u32,
+    /// The player's health at the start of a run.
+    pub player_health: u32,
+}
+
+impl Default for CustomDifficulty {
+    fn default() -> Self {
+        Self {
+            spawn_rate: 1.0,
+            enemy_speed: 1.0,
+            enemy_health: 1,
+            player_health: PLAYER_MAX_HEALTH,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DifficultySlider {
+    SpawnRate,
+    EnemySpeed,
+    EnemyHealth,
+    PlayerHealth,
+}
+
+impl CustomDifficulty {
+    /// Moves `slider` by `steps` notches, stopping at either end.
+    pub fn adjust(&mut self, slider: DifficultySlider, steps: i32) {
+        match slider {
+            DifficultySlider::SpawnRate => {
+                self.spawn_rate = (self.spawn_rate + 0.25 * steps as f32).clamp(0.25, 3.0);
+            }
+            DifficultySlider::EnemySpeed => {
+                self.enemy_speed = (self.enemy_speed + 0.25 * steps as f32).clamp(0.5, 2.0);
+            }
+            DifficultySlider::EnemyHealth => {
+                self.enemy_health = self.enemy_health.saturating_add_signed(steps).clamp(1, 5);
+            }
+            DifficultySlider::PlayerHealth => {
+                self.player_health = self.player_health.saturating_add_signed(steps).clamp(1, 10);
+            }
+        }
+    }
+
+    /// Moves `slider` up a notch, going back to the bottom from the top.
+    pub fn cycle(&mut self, slider: DifficultySlider) {
+        let before = *self;
+        self.adjust(slider, 1);
+        if *self == before {
+            self.adjust(slider, i32::MIN);
+        }
+    }
+
+    pub fn label(&self, slider: DifficultySlider) -> String {
+        match slider {
+            DifficultySlider::SpawnRate => format!("Spawn Rate: {:.2}x", self.spawn_rate),
+            DifficultySlider::EnemySpeed => format!("Enemy Speed: {:.2}x", self.enemy_speed),
+            DifficultySlider::EnemyHealth => format!("Enemy Health: {}x", self.enemy_health),
+            DifficultySlider::PlayerHealth => format!("Starting Health: {}", self.player_health),
+        }
+    }
+
+    /// Chance for each spawn attempt to bring in a fish at `difficulty`, where
+    /// `full_difficulty` is certain at the standard spawn rate.
+    pub fn spawn_chance(&self, difficulty: f32, full_difficulty: f32) -> f32 {
+        (difficulty / full_difficulty * self.spawn_rate).min(1.0)
+    }
+}
+
+/// The profile in effect for the current run.
+#[derive(Resource)]
+pub struct RunDifficulty(pub CustomDifficulty);
+
+pub fn select_run_difficulty(
+    mut run_difficulty: ResMut<RunDifficulty>,
+    custom: Res<CustomDifficulty>,
+    mode: Res<RunMode>,
+    playback: Option<Res<ReplayPlayback>>,
+) {
+    run_difficulty.0 = match (&playback, &*mode) {
+        (Some(playback), _) => playback.replay().custom_difficulty,
+        (None, RunMode::Daily { .. }) => CustomDifficulty::default(),
+        (None, _) => *custom,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sliders_feed_the_spawn_chance() {
+        let mut custom = CustomDifficulty::default();
+        let standard = custom.spawn_chance(1.0, 3.0);
+        custom.adjust(DifficultySlider::SpawnRate, 4);
+        assert_eq!(custom.spawn_rate, 2.0);
+        assert!((custom.spawn_chance(1.0, 3.0) - standard * 2.0).abs() < 1e-6);
+        assert_eq!(custom.spawn_chance(3.0, 3.0), 1.0);
+    }
+
+    #[test]
+    fn sliders_stop_at_either_end() {
+        let mut custom = CustomDifficulty::default();
+        custom.adjust(DifficultySlider::EnemySpeed, 100);
+        custom.adjust(DifficultySlider::EnemyHealth, -100);
+        assert_eq!(custom.enemy_speed, 2.0);
+        assert_eq!(custom.enemy_health, 1);
+        custom.cycle(DifficultySlider::EnemySpeed);
+        assert_eq!(custom.enemy_speed, 0.5);
+    }
+}
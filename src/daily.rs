@@ -0,0 +1,91 @@
+//! The daily challenge: every run started on the same UTC day shares a seed.
+
+use std::{
+    fmt,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+/// A calendar date in UTC.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    pub fn today_utc() -> Self {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        Self::from_days_since_epoch((secs / 86_400) as i64)
+    }
+
+    /// Converts a day count since 1970-01-01 to a civil date, using Howard
+    /// Hinnant's `civil_from_days` algorithm.
+    pub fn from_days_since_epoch(days: i64) -> Self {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * mp + 2) / 5 + 1) as u32;
+        let month = (if mp < 10 { mp + 3 } else { mp - 9 }) as u32;
+        let year = (year_of_era + era * 400 + i64::from(month <= 2)) as i32;
+        Self { year, month, day }
+    }
+
+    /// The seed shared by everyone playing the daily challenge on this date.
+    pub fn daily_seed(&self) -> u64 {
+        let packed =
+            (self.year as u64) * 10_000 + u64::from(self.month) * 100 + u64::from(self.day);
+        // SplitMix64 finalizer, so neighbouring dates get unrelated seeds.
+        let mut z = packed.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> Date {
+        Date { year, month, day }
+    }
+
+    #[test]
+    fn days_convert_across_leap_years() {
+        assert_eq!(Date::from_days_since_epoch(0), date(1970, 1, 1));
+        assert_eq!(Date::from_days_since_epoch(-1), date(1969, 12, 31));
+        assert_eq!(Date::from_days_since_epoch(19_782), date(2024, 2, 29));
+        assert_eq!(Date::from_days_since_epoch(19_783), date(2024, 3, 1));
+        // Centuries are only leap years every 400 years.
+        assert_eq!(Date::from_days_since_epoch(11_016), date(2000, 2, 29));
+        assert_eq!(Date::from_days_since_epoch(47_541), date(2100, 3, 1));
+        assert_eq!(Date::from_days_since_epoch(47_540), date(2100, 2, 28));
+    }
+
+    #[test]
+    fn each_day_has_its_own_stable_seed() {
+        // Changing this would split players of the same day between seeds.
+        assert_eq!(date(2024, 2, 29).daily_seed(), 0x293c_c02a_ea25_e48a);
+        let seeds: Vec<_> = (19_780..19_790)
+            .map(|days| Date::from_days_since_epoch(days).daily_seed())
+            .collect();
+        for pair in seeds.windows(2) {
+            assert_ne!(pair[0], pair[1]);
+        }
+    }
+}
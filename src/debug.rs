@@ -0,0 +1,283 @@
+//! Developer tools for checking and testing the game.
+//!
+//! F4 toggles an overlay drawing the `PlayArea` border, a reference grid and a
+//! crosshair at the center, to help see where things spawn and get clamped.
+//! It only draws; gameplay is untouched either way.
+//!
+//! The cheats below are only available in debug builds or with `--debug`:
+//!
+//! - F8 toggles god mode, where nothing hurts the player. The duck is tinted
+//!   gold while it's on.
+//! - During a run, 1 spawns a fish and 2 a mine at the right of the play area,
+//!   and B brings in the boss, regardless of what the spawners are doing.
+//! - Also during a run, 3 adds 100 points, 4 adds 10 coins and 5 gives the
+//!   player another parallel shot.
+//!
+//! A run that has had god mode on or been granted anything isn't kept: its
+//! score goes on no leaderboard and the coins it earned are thrown away.
+
+use bevy::prelude::*;
+
+use crate::{
+    EnemyAtlas, FISH_HP, GameState, IsPlayer, MAX_MULTI_SHOT, MINE_HP, MineAssets, MultiShot,
+    Score,
+    boss::{Boss, summon_boss},
+    coins::Coins,
+    custom_difficulty::RunDifficulty,
+    fish, mine,
+    play_area::PlayArea,
+    sound::SoundAssets,
+    start_run,
+    tuning::Tuning,
+};
+
+const DEFAULT_GRID_SPACING: f32 = 50.0;
+const GRID_COLOR: Color = Color::srgba(1.0, 1.0, 1.0, 0.15);
+const BORDER_COLOR: Color = Color::srgb(0.0, 1.0, 0.4);
+const CROSSHAIR_COLOR: Color = Color::srgb(1.0, 0.2, 0.2);
+const CROSSHAIR_SIZE: f32 = 12.0;
+const GOD_MODE_TINT: Color = Color::srgb(1.0, 0.85, 0.45);
+/// Where forced spawns appear, as a fraction of the way to the right edge.
+const FORCED_SPAWN_X: f32 = 0.8;
+const GRANTED_SCORE: u32 = 100;
+const GRANTED_COINS: u32 = 10;
+
+pub struct DebugPlugin;
+
+impl Plugin for DebugPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DebugOverlay>()
+            .init_resource::<GodMode>()
+            .init_resource::<CheatsUsed>()
+            .add_systems(OnExit(GameState::Menu), reset_cheats_used.after(start_run))
+            .add_systems(
+                Update,
+                (
+                    toggle_debug_overlay,
+                    draw_debug_overlay.run_if(debug_overlay_visible),
+                )
+                    .chain(),
+            )
+            .add_systems(
+                Update,
+                (
+                    toggle_god_mode.run_if(resource_exists::<DebugCheats>),
+                    tint_god_mode,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                Update,
+                (force_spawn, grant_rewards)
+                    .run_if(resource_exists::<DebugCheats>.and(in_state(GameState::Playing))),
+            )
+            .add_systems(
+                Update,
+                note_god_mode.run_if(in_state(GameState::Playing).and(god_mode)),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub struct DebugOverlay {
+    pub visible: bool,
+    /// Pixels between grid lines, set with `--grid-spacing`.
+    pub grid_spacing: f32,
+}
+
+impl Default for DebugOverlay {
+    fn default() -> Self {
+        Self {
+            visible: false,
+            grid_spacing: DEFAULT_GRID_SPACING,
+        }
+    }
+}
+
+/// Present when the debug cheats are available.
+#[derive(Resource)]
+pub struct DebugCheats;
+
+/// While on, the player can't be hurt.
+#[derive(Resource, Default)]
+pub struct GodMode(pub bool);
+
+/// Set once god mode or a granted reward has played a part in the current run.
+#[derive(Resource, Default)]
+pub struct CheatsUsed(bool);
+
+/// Whether the current run's score and coins should be thrown away.
+pub fn cheats_used(cheats: Res<CheatsUsed>) -> bool {
+    cheats.0
+}
+
+pub fn debug_overlay_visible(overlay: Res<DebugOverlay>) -> bool {
+    overlay.visible
+}
+
+fn toggle_debug_overlay(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut overlay: ResMut<DebugOverlay>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F4) {
+        overlay.visible = !overlay.visible;
+    }
+}
+
+fn draw_debug_overlay(mut gizmos: Gizmos, overlay: Res<DebugOverlay>, play_area: Res<PlayArea>) {
+    let half = Vec2::new(play_area.half_width, play_area.half_height);
+    // Lines run out from the center so one always passes through it.
+    let mut offset = overlay.grid_spacing;
+    while offset < half.x {
+        for x in [offset, -offset] {
+            gizmos.line_2d(Vec2::new(x, -half.y), Vec2::new(x, half.y), GRID_COLOR);
+        }
+        offset += overlay.grid_spacing;
+    }
+    let mut offset = overlay.grid_spacing;
+    while offset < half.y {
+        for y in [offset, -offset] {
+            gizmos.line_2d(Vec2::new(-half.x, y), Vec2::new(half.x, y), GRID_COLOR);
+        }
+        offset += overlay.grid_spacing;
+    }
+
+    // Inset by a pixel so the outline isn't clipped by the window edge.
+    gizmos.rect_2d(
+        Isometry2d::IDENTITY,
+        half * 2.0 - Vec2::splat(2.0),
+        BORDER_COLOR,
+    );
+    gizmos.line_2d(
+        Vec2::new(-CROSSHAIR_SIZE, 0.0),
+        Vec2::new(CROSSHAIR_SIZE, 0.0),
+        CROSSHAIR_COLOR,
+    );
+    gizmos.line_2d(
+        Vec2::new(0.0, -CROSSHAIR_SIZE),
+        Vec2::new(0.0, CROSSHAIR_SIZE),
+        CROSSHAIR_COLOR,
+    );
+}
+
+/// Damage to the player waits while this is true.
+pub fn god_mode(god_mode: Res<GodMode>) -> bool {
+    god_mode.0
+}
+
+fn toggle_god_mode(keyboard_input: Res<ButtonInput<KeyCode>>, mut god_mode: ResMut<GodMode>) {
+    if keyboard_input.just_pressed(KeyCode::F8) {
+        god_mode.0 = !god_mode.0;
+        info!("God mode {}", if god_mode.0 { "on" } else { "off" });
+    }
+}
+
+fn reset_cheats_used(mut cheats: ResMut<CheatsUsed>) {
+    cheats.0 = false;
+}
+
+fn note_god_mode(mut cheats: ResMut<CheatsUsed>) {
+    cheats.0 = true;
+}
+
+fn tint_god_mode(god_mode: Res<GodMode>, mut query: Query<&mut Sprite, With<IsPlayer>>) {
+    let color = if god_mode.0 {
+        GOD_MODE_TINT
+    } else {
+        Color::WHITE
+    };
+    for mut sprite in query.iter_mut() {
+        if sprite.color != color {
+            sprite.color = color;
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn force_spawn(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    enemy_atlas: Res<EnemyAtlas>,
+    mine_assets: Res<MineAssets>,
+    sounds: Res<SoundAssets>,
+    play_area: Res<PlayArea>,
+    run_difficulty: Res<RunDifficulty>,
+    tuning: Res<Tuning>,
+    boss_query: Query<(), With<Boss>>,
+) {
+    let custom = &run_difficulty.0;
+    let position = Vec2::new(play_area.half_width * FORCED_SPAWN_X, 0.0);
+    if keyboard_input.just_pressed(KeyCode::Digit1) {
+        let velocity = Vec2::new(-tuning.fish_speed.0, 0.0) * custom.enemy_speed;
+        let hp = FISH_HP * custom.enemy_health;
+        commands.spawn(fish(&enemy_atlas, &tuning, position, velocity, hp));
+    }
+    if keyboard_input.just_pressed(KeyCode::Digit2) {
+        let velocity = Vec2::new(-tuning.mine_speed.0, 0.0) * custom.enemy_speed;
+        let hp = MINE_HP * custom.enemy_health;
+        commands.spawn(mine(&mine_assets, position, velocity, hp));
+    }
+    if keyboard_input.just_pressed(KeyCode::KeyB) {
+        // Only one boss is meant to be alive at a time.
+        if boss_query.is_empty() {
+            summon_boss(&mut commands, &enemy_atlas, &sounds, &play_area);
+        } else {
+            info!("A boss is already in play");
+        }
+    }
+}
+
+fn grant_rewards(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut score: ResMut<Score>,
+    mut coins: ResMut<Coins>,
+    mut multi_shot: ResMut<MultiShot>,
+    mut cheats: ResMut<CheatsUsed>,
+) {
+    if keyboard_input.any_just_pressed([KeyCode::Digit3, KeyCode::Digit4, KeyCode::Digit5]) {
+        cheats.0 = true;
+    }
+    if keyboard_input.just_pressed(KeyCode::Digit3) {
+        score.0 += GRANTED_SCORE;
+    }
+    if keyboard_input.just_pressed(KeyCode::Digit4) {
+        coins.0 += GRANTED_COINS;
+    }
+    if keyboard_input.just_pressed(KeyCode::Digit5) {
+        multi_shot.0 = (multi_shot.0 + 1).min(MAX_MULTI_SHOT);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    fn world_with_key(key: KeyCode) -> World {
+        let mut world = World::new();
+        let mut keyboard_input = ButtonInput::<KeyCode>::default();
+        keyboard_input.press(key);
+        world.insert_resource(keyboard_input);
+        world.insert_resource(Score(0));
+        world.insert_resource(Coins(0));
+        world.insert_resource(MultiShot(1));
+        world.init_resource::<CheatsUsed>();
+        world
+    }
+
+    #[test]
+    fn granted_rewards_mark_the_run_as_cheated() {
+        let mut world = world_with_key(KeyCode::Digit3);
+        world.run_system_once(grant_rewards).unwrap();
+        assert_eq!(world.resource::<Score>().0, GRANTED_SCORE);
+        assert!(world.resource::<CheatsUsed>().0);
+    }
+
+    #[test]
+    fn other_keys_leave_the_run_clean() {
+        let mut world = world_with_key(KeyCode::Digit9);
+        world.run_system_once(grant_rewards).unwrap();
+        assert!(!world.resource::<CheatsUsed>().0);
+    }
+}
@@ -0,0 +1,175 @@
+//! Window size and display mode options.
+//!
+//! The play area follows the window, so changing either also moves the edges
+//! that things spawn at and are kept within. A replay can't reproduce that
+//! partway through, so changes made during a recorded run wait until it's
+//! over.
+
+use bevy::{
+    prelude::*,
+    window::{PrimaryWindow, VideoModeSelection, WindowMode, WindowPosition},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{GameState, replay::ReplayRecorder};
+
+pub struct DisplayPlugin;
+
+impl Plugin for DisplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Resolution>()
+            .init_resource::<DisplayMode>()
+            .add_systems(
+                Update,
+                apply_display.run_if(
+                    (resource_changed::<Resolution>.or(resource_changed::<DisplayMode>))
+                        .and(not(resource_added::<Resolution>))
+                        .and(not(resource_exists::<FixedWindowSize>)),
+                ),
+            )
+            .add_systems(
+                OnEnter(GameState::Menu),
+                apply_pending_display.run_if(resource_exists::<DisplayChangePending>),
+            );
+    }
+}
+
+/// The windowed resolutions to choose from. Ignored outside windowed mode.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Resolution {
+    #[default]
+    Small,
+    Medium,
+    Large,
+}
+
+impl Resolution {
+    pub fn size(self) -> Vec2 {
+        match self {
+            Self::Small => Vec2::new(800.0, 600.0),
+            Self::Medium => Vec2::new(1024.0, 768.0),
+            Self::Large => Vec2::new(1280.0, 960.0),
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Self::Small => Self::Medium,
+            Self::Medium => Self::Large,
+            Self::Large => Self::Small,
+        }
+    }
+
+    pub fn label(self) -> String {
+        let size = self.size();
+        format!("{}x{}", size.x, size.y)
+    }
+}
+
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum DisplayMode {
+    /// A decorated window at the chosen `Resolution`.
+    #[default]
+    Windowed,
+    /// An undecorated window covering the whole monitor.
+    Borderless,
+    /// Exclusive fullscreen at the monitor's current video mode.
+    Fullscreen,
+}
+
+impl DisplayMode {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Windowed => Self::Borderless,
+            Self::Borderless => Self::Fullscreen,
+            Self::Fullscreen => Self::Windowed,
+        }
+    }
+}
+
+/// Present when the window size was fixed at launch, by `--window-size` or a
+/// replay, so the chosen resolution and display mode aren't applied.
+#[derive(Resource)]
+pub struct FixedWindowSize;
+
+/// Present while a change to the display waits for the recorded run to end.
+#[derive(Resource)]
+struct DisplayChangePending;
+
+/// Sets `window` up for `mode`, sizing and re-centering it to `resolution`
+/// when windowed.
+pub fn configure_window(window: &mut Window, resolution: Resolution, mode: DisplayMode) {
+    match mode {
+        DisplayMode::Windowed => {
+            let size = resolution.size();
+            window.mode = WindowMode::Windowed;
+            window.decorations = true;
+            window.resolution.set(size.x, size.y);
+            window.position = WindowPosition::Centered(MonitorSelection::Current);
+        }
+        DisplayMode::Borderless => {
+            window.mode = WindowMode::BorderlessFullscreen(MonitorSelection::Current);
+            window.decorations = false;
+        }
+        DisplayMode::Fullscreen => {
+            window.mode =
+                WindowMode::Fullscreen(MonitorSelection::Current, VideoModeSelection::Current);
+            window.decorations = true;
+        }
+    }
+}
+
+fn apply_display(
+    mut commands: Commands,
+    resolution: Res<Resolution>,
+    mode: Res<DisplayMode>,
+    recorder: Option<Res<ReplayRecorder>>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if recorder.is_some() {
+        commands.insert_resource(DisplayChangePending);
+        return;
+    }
+    if let Ok(mut window) = window_query.single_mut() {
+        configure_window(&mut window, *resolution, *mode);
+    }
+}
+
+fn apply_pending_display(
+    mut commands: Commands,
+    resolution: Res<Resolution>,
+    mode: Res<DisplayMode>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    commands.remove_resource::<DisplayChangePending>();
+    if let Ok(mut window) = window_query.single_mut() {
+        configure_window(&mut window, *resolution, *mode);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::play_area::{PlayArea, PlayAreaPlugin};
+
+    #[test]
+    fn choosing_a_resolution_resizes_the_window_and_play_area() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, DisplayPlugin, PlayAreaPlugin))
+            .init_resource::<ButtonInput<KeyCode>>();
+        let window = app
+            .world_mut()
+            .spawn((Window::default(), PrimaryWindow))
+            .id();
+        app.update();
+
+        app.insert_resource(Resolution::Large);
+        // The play area catches up with the window a frame later at most.
+        app.update();
+        app.update();
+
+        let size = app.world().get::<Window>(window).unwrap().size();
+        assert_eq!(size, Resolution::Large.size());
+        assert_eq!(app.world().resource::<PlayArea>().size(), size);
+    }
+}
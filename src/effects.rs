@@ -0,0 +1,204 @@
+//! Cosmetic screen effects reacting to gameplay events.
+//!
+//! Besides the hit flash, a combo builds up an `Intensity` that the other
+//! effects scale with: a glow creeping in from the edges of the screen and
+//! bullets burning brighter. It follows the score multiplier up and eases back
+//! down as it winds down, and stays at zero when reducing motion.
+
+use bevy::prelude::*;
+
+use crate::{
+    BULLET_COLOR, BulletAssets, GameState, PlayerHit, combo::Combo, play_area::PlayArea,
+    settings::ReduceMotion,
+};
+
+const HIT_FLASH_ALPHA: f32 = 0.4;
+const HIT_FLASH_DURATION: f32 = 0.2;
+/// How much `Intensity` can change per second on its way to the multiplier.
+const INTENSITY_RATE: f32 = 1.5;
+const VIGNETTE_WIDTH: f32 = 40.0;
+const VIGNETTE_COLOR: Color = Color::srgb(1.0, 0.4, 0.05);
+/// Alpha of the glow at full intensity; it's meant to be noticed, not to get
+/// in the way.
+const VIGNETTE_ALPHA: f32 = 0.3;
+/// What bullets burn toward at full intensity.
+const HOT_BULLET_COLOR: Color = Color::srgb(1.0, 0.75, 0.2);
+
+pub struct EffectsPlugin;
+
+impl Plugin for EffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Intensity>()
+            .add_systems(Startup, (spawn_hit_flash, spawn_vignette))
+            .add_systems(
+                Update,
+                (
+                    (trigger_hit_flash, fade_hit_flash).chain(),
+                    (
+                        update_intensity,
+                        (update_vignette, heat_bullets).run_if(resource_changed::<Intensity>),
+                    )
+                        .chain(),
+                ),
+            );
+    }
+}
+
+/// How much combo flair to show, from 0.0 to 1.0.
+#[derive(Resource, Default)]
+pub struct Intensity(pub f32);
+
+impl Intensity {
+    /// Where the intensity heads for `combo`, reaching 1.0 at its highest
+    /// multiplier.
+    pub fn target(combo: &Combo, reduce_motion: bool) -> f32 {
+        if reduce_motion || combo.max_multiplier <= 1 {
+            return 0.0;
+        }
+        let levels = combo.multiplier.saturating_sub(1) as f32;
+        (levels / (combo.max_multiplier - 1) as f32).clamp(0.0, 1.0)
+    }
+}
+
+#[derive(Component)]
+struct Vignette;
+
+/// Full-screen red overlay, drawn above gameplay but below the score.
+#[derive(Component)]
+struct HitFlash;
+
+fn spawn_hit_flash(mut commands: Commands, play_area: Res<PlayArea>) {
+    commands.spawn((
+        Sprite::from_color(Color::srgba(1.0, 0.0, 0.0, 0.0), play_area.size()),
+        Transform::from_xyz(0.0, 0.0, 5.0),
+        HitFlash,
+    ));
+}
+
+fn trigger_hit_flash(
+    mut hits: EventReader<PlayerHit>,
+    mut query: Query<&mut Sprite, With<HitFlash>>,
+    reduce_motion: Res<ReduceMotion>,
+) {
+    if hits.is_empty() {
+        return;
+    }
+    hits.clear();
+    if reduce_motion.0 {
+        return;
+    }
+
+    // Refresh rather than add, so rapid hits never stack to an opaque screen.
+    for mut sprite in query.iter_mut() {
+        sprite.color.set_alpha(HIT_FLASH_ALPHA);
+    }
+}
+
+fn fade_hit_flash(
+    mut query: Query<&mut Sprite, With<HitFlash>>,
+    play_area: Res<PlayArea>,
+    time: Res<Time>,
+) {
+    for mut sprite in query.iter_mut() {
+        if play_area.is_changed() {
+            sprite.custom_size = Some(play_area.size());
+        }
+        let alpha = sprite.color.alpha();
+        if alpha > 0.0 {
+            let fade = HIT_FLASH_ALPHA / HIT_FLASH_DURATION * time.delta_secs();
+            sprite.color.set_alpha((alpha - fade).max(0.0));
+        }
+    }
+}
+
+fn spawn_vignette(mut commands: Commands) {
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            border: UiRect::all(Val::Px(VIGNETTE_WIDTH)),
+            ..default()
+        },
+        BorderColor(VIGNETTE_COLOR.with_alpha(0.0)),
+        BorderRadius::all(Val::Px(VIGNETTE_WIDTH * 2.0)),
+        Vignette,
+    ));
+}
+
+/// Eases the intensity toward where the combo puts it. Outside of a run, and
+/// whenever motion is reduced, it drops straight to zero.
+fn update_intensity(
+    mut intensity: ResMut<Intensity>,
+    combo: Res<Combo>,
+    reduce_motion: Res<ReduceMotion>,
+    state: Res<State<GameState>>,
+    time: Res<Time>,
+) {
+    let playing = matches!(state.get(), GameState::Playing | GameState::Paused);
+    let next = if reduce_motion.0 || !playing {
+        0.0
+    } else {
+        let target = Intensity::target(&combo, reduce_motion.0);
+        let step = INTENSITY_RATE * time.delta_secs();
+        intensity.0 + (target - intensity.0).clamp(-step, step)
+    };
+    if next != intensity.0 {
+        intensity.0 = next;
+    }
+}
+
+fn update_vignette(intensity: Res<Intensity>, mut query: Query<&mut BorderColor, With<Vignette>>) {
+    for mut color in query.iter_mut() {
+        color.0 = VIGNETTE_COLOR.with_alpha(VIGNETTE_ALPHA * intensity.0);
+    }
+}
+
+fn heat_bullets(
+    intensity: Res<Intensity>,
+    bullet_assets: Option<Res<BulletAssets>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let Some(bullet_assets) = bullet_assets else {
+        return;
+    };
+    if let Some(material) = materials.get_mut(&bullet_assets.material) {
+        material.color = BULLET_COLOR.mix(&HOT_BULLET_COLOR, intensity.0);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    fn combo_at(multiplier: u32) -> Combo {
+        let mut combo = Combo::default();
+        combo.multiplier = multiplier;
+        combo
+    }
+
+    #[test]
+    fn a_bigger_multiplier_raises_the_intensity() {
+        let targets =
+            [1, 2, 3, 5].map(|multiplier| Intensity::target(&combo_at(multiplier), false));
+        assert_eq!(targets[0], 0.0);
+        assert!(targets.windows(2).all(|pair| pair[0] < pair[1]));
+        assert_eq!(targets[3], 1.0);
+    }
+
+    #[test]
+    fn reduced_motion_keeps_the_intensity_at_zero() {
+        assert_eq!(Intensity::target(&combo_at(5), true), 0.0);
+
+        let mut world = World::new();
+        world.insert_resource(Intensity(0.6));
+        world.insert_resource(combo_at(5));
+        world.insert_resource(ReduceMotion(true));
+        world.insert_resource(State::new(GameState::Playing));
+        world.init_resource::<Time>();
+        world.run_system_once(update_intensity).unwrap();
+        assert_eq!(world.resource::<Intensity>().0, 0.0);
+    }
+}
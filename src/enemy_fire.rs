@@ -0,0 +1,361 @@
+//! Fish shooting back, and the score bonus for grazing their bullets.
+//!
+//! The fish closest to the duck takes a shot at it every so often. Enemies
+//! with a `BulletPattern`, such as the boss, also fire volleys of their own:
+//! radial bursts all the way around, or spirals that turn a little with every
+//! volley. A bullet that passes just outside the player's hitbox is worth a
+//! little extra, once per bullet, paid as it gets past without hitting.
+
+use std::{f32::consts::TAU, time::Duration};
+
+use bevy::prelude::*;
+
+use crate::{
+    Difficulty, GameState, Invincible, IsEnemy, IsPlayer, PlayerHitbox, Score, Velocity,
+    boss::{BossIntro, in_boss_death},
+    check_for_player_collisions,
+    game_time::GameTime,
+    particles::Explosion,
+    play_area::PlayArea,
+    popups::ScorePopup,
+    practice::in_practice,
+    tutorial::in_tutorial,
+};
+
+/// Seconds between enemy shots at a difficulty of 1.0.
+const ENEMY_FIRE_INTERVAL: f32 = 2.0;
+pub const ENEMY_BULLET_SPEED: f32 = 250.0;
+pub const ENEMY_BULLET_RADIUS: f32 = 6.0;
+/// How far outside the player's hitbox a passing bullet still counts as a graze.
+const GRAZE_MARGIN: f32 = 30.0;
+pub const GRAZE_POINTS: u32 = 1;
+/// Spirals fire often, so their bullets are slower to keep the gaps dodgeable.
+const SPIRAL_BULLET_SPEED: f32 = 150.0;
+
+pub struct EnemyFirePlugin;
+
+impl Plugin for EnemyFirePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(EnemyFire(Timer::from_seconds(
+            ENEMY_FIRE_INTERVAL,
+            TimerMode::Repeating,
+        )))
+        .add_systems(Startup, setup_enemy_bullet_assets)
+        .add_systems(OnExit(GameState::Menu), reset_enemy_fire)
+        .add_systems(
+            Update,
+            (
+                fire_at_player.run_if(
+                    not(in_tutorial)
+                        .and(not(in_practice))
+                        .and(not(in_boss_death)),
+                ),
+                fire_patterns,
+                graze.after(check_for_player_collisions),
+            )
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+/// A bullet fired by an enemy, harmful to the player.
+#[derive(Component)]
+pub struct EnemyBullet;
+
+/// Volleys of bullets an enemy fires each time its `PatternTimer` finishes.
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub enum BulletPattern {
+    /// `count` bullets spaced evenly all the way around.
+    Radial { count: u32 },
+    /// `arms` bullets spaced evenly around, turned by `step` radians more with
+    /// every volley.
+    Spiral { arms: u32, step: f32, angle: f32 },
+}
+
+impl BulletPattern {
+    pub fn spiral(arms: u32, step: f32) -> Self {
+        Self::Spiral {
+            arms,
+            step,
+            angle: 0.0,
+        }
+    }
+
+    /// Velocities of the bullets in the next volley, turning a spiral on for
+    /// the one after.
+    pub fn volley(&mut self) -> Vec<Vec2> {
+        let (count, first, speed) = match self {
+            Self::Radial { count } => (*count, 0.0, ENEMY_BULLET_SPEED),
+            Self::Spiral { arms, step, angle } => {
+                let first = *angle;
+                *angle = (*angle + *step).rem_euclid(TAU);
+                (*arms, first, SPIRAL_BULLET_SPEED)
+            }
+        };
+        (0..count)
+            .map(|i| Vec2::from_angle(first + TAU * i as f32 / count as f32) * speed)
+            .collect()
+    }
+}
+
+/// Counts down to an enemy's next `BulletPattern` volley.
+#[derive(Component)]
+pub struct PatternTimer(pub Timer);
+
+/// Marks an enemy bullet passing close to the player, to be paid for once it
+/// leaves them behind.
+#[derive(Component)]
+struct Grazing;
+
+/// Marks an enemy bullet that is done grazing the player, paid for or not.
+#[derive(Component)]
+struct Grazed;
+
+/// Counts down to the next enemy shot.
+#[derive(Resource)]
+struct EnemyFire(Timer);
+
+#[derive(Resource)]
+pub struct EnemyBulletAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<ColorMaterial>,
+}
+
+fn setup_enemy_bullet_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    commands.insert_resource(EnemyBulletAssets {
+        mesh: meshes.add(Circle::new(ENEMY_BULLET_RADIUS)),
+        material: materials.add(ColorMaterial::from(Color::srgb(0.9, 0.1, 0.3))),
+    });
+}
+
+pub fn enemy_bullet(assets: &EnemyBulletAssets, position: Vec2, velocity: Vec2) -> impl Bundle {
+    (
+        Mesh2d(assets.mesh.clone()),
+        MeshMaterial2d(assets.material.clone()),
+        Transform::from_translation(position.extend(0.0)),
+        Velocity(velocity),
+        EnemyBullet,
+    )
+}
+
+fn reset_enemy_fire(mut fire: ResMut<EnemyFire>) {
+    fire.0.reset();
+}
+
+/// Shoots from the enemy nearest the player, straight at it. Shots come more
+/// often as the difficulty climbs.
+#[allow(clippy::too_many_arguments)]
+fn fire_at_player(
+    mut commands: Commands,
+    mut fire: ResMut<EnemyFire>,
+    assets: Res<EnemyBulletAssets>,
+    difficulty: Res<Difficulty>,
+    play_area: Res<PlayArea>,
+    time: Res<GameTime>,
+    player_query: Query<&Transform, With<IsPlayer>>,
+    enemy_query: Query<&Transform, (With<IsEnemy>, Without<BossIntro>)>,
+) {
+    let interval = ENEMY_FIRE_INTERVAL / difficulty.0.max(1.0);
+    fire.0.set_duration(Duration::from_secs_f32(interval));
+    if !fire.0.tick(time.delta()).just_finished() {
+        return;
+    }
+    let Ok(player_transform) = player_query.single() else {
+        return;
+    };
+    let target = player_transform.translation.truncate();
+    // Only fish that are on screen get to shoot.
+    let shooter = enemy_query
+        .iter()
+        .map(|transform| transform.translation.truncate())
+        .filter(|position| {
+            position.x.abs() <= play_area.half_width && position.y.abs() <= play_area.half_height
+        })
+        .min_by(|a, b| {
+            a.distance_squared(target)
+                .total_cmp(&b.distance_squared(target))
+        });
+    let Some(shooter) = shooter else {
+        return;
+    };
+    let Some(direction) = (target - shooter).try_normalize() else {
+        return;
+    };
+    commands.spawn(enemy_bullet(
+        &assets,
+        shooter,
+        direction * ENEMY_BULLET_SPEED,
+    ));
+}
+
+fn fire_patterns(
+    mut commands: Commands,
+    mut query: Query<(&Transform, &mut BulletPattern, &mut PatternTimer), Without<BossIntro>>,
+    assets: Res<EnemyBulletAssets>,
+    time: Res<GameTime>,
+) {
+    for (transform, mut pattern, mut timer) in query.iter_mut() {
+        if !timer.0.tick(time.delta()).just_finished() {
+            continue;
+        }
+        let position = transform.translation.truncate();
+        for velocity in pattern.volley() {
+            commands.spawn(enemy_bullet(&assets, position, velocity));
+        }
+    }
+}
+
+/// Awards points for enemy bullets that come close and go on by without
+/// hitting. Bullets brushing past while the player is invincible don't count.
+#[allow(clippy::type_complexity)]
+fn graze(
+    mut commands: Commands,
+    mut score: ResMut<Score>,
+    mut explosions: EventWriter<Explosion>,
+    mut popups: EventWriter<ScorePopup>,
+    player_query: Query<(&Transform, &PlayerHitbox), (With<IsPlayer>, Without<Invincible>)>,
+    bullet_query: Query<(Entity, &Transform, Has<Grazing>), (With<EnemyBullet>, Without<Grazed>)>,
+) {
+    let Ok((player_transform, hitbox)) = player_query.single() else {
+        return;
+    };
+    let player = player_transform.translation.truncate();
+    let hit_radius = hitbox.0 + ENEMY_BULLET_RADIUS;
+    for (entity, transform, grazing) in bullet_query.iter() {
+        let position = transform.translation.truncate();
+        let distance = position.distance(player);
+        if distance < hit_radius {
+            // A hit, already despawned by the player's collisions.
+            commands.entity(entity).try_insert(Grazed);
+            continue;
+        }
+        if distance <= hit_radius + GRAZE_MARGIN {
+            if !grazing {
+                commands.entity(entity).insert(Grazing);
+            }
+            continue;
+        }
+        if !grazing {
+            continue;
+        }
+        commands.entity(entity).insert(Grazed);
+        score.0 += GRAZE_POINTS;
+        explosions.write(Explosion {
+            position,
+            radius: 0.0,
+        });
+        popups.write(ScorePopup {
+            position,
+            points: GRAZE_POINTS,
+            crit: false,
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    const HITBOX: f32 = 10.0;
+
+    /// Flies a bullet through `path` past a player at the origin, returning
+    /// the points grazing it paid.
+    fn graze_along(path: &[Vec2]) -> u32 {
+        let mut world = World::new();
+        world.insert_resource(Score(0));
+        world.init_resource::<Events<Explosion>>();
+        world.init_resource::<Events<ScorePopup>>();
+        world.spawn((Transform::default(), PlayerHitbox(HITBOX), IsPlayer));
+        let bullet = world.spawn((Transform::default(), EnemyBullet)).id();
+        for position in path {
+            world.get_mut::<Transform>(bullet).unwrap().translation = position.extend(0.0);
+            world.run_system_once(graze).unwrap();
+        }
+        world.resource::<Score>().0
+    }
+
+    #[test]
+    fn a_radial_burst_spreads_its_bullets_evenly() {
+        let mut world = World::new();
+        world.init_resource::<GameTime>();
+        world.insert_resource(EnemyBulletAssets {
+            mesh: Handle::default(),
+            material: Handle::default(),
+        });
+        // A timer with no duration finishes on every tick, firing at once.
+        world.spawn((
+            Transform::from_xyz(100.0, 50.0, 0.0),
+            BulletPattern::Radial { count: 8 },
+            PatternTimer(Timer::from_seconds(0.0, TimerMode::Repeating)),
+        ));
+        world.run_system_once(fire_patterns).unwrap();
+
+        let mut bullets = world.query_filtered::<(&Transform, &Velocity), With<EnemyBullet>>();
+        let mut angles: Vec<f32> = bullets
+            .iter(&world)
+            .map(|(transform, velocity)| {
+                assert_eq!(transform.translation.truncate(), Vec2::new(100.0, 50.0));
+                assert!((velocity.0.length() - ENEMY_BULLET_SPEED).abs() < 1e-3);
+                velocity.0.to_angle().rem_euclid(TAU)
+            })
+            .collect();
+        assert_eq!(angles.len(), 8);
+        angles.sort_by(f32::total_cmp);
+        for (i, angle) in angles.iter().enumerate() {
+            assert!(
+                (angle - TAU * i as f32 / 8.0).abs() < 1e-4,
+                "bullet {i} at {angle}"
+            );
+        }
+    }
+
+    #[test]
+    fn a_spiral_turns_with_every_volley() {
+        let mut pattern = BulletPattern::spiral(2, 0.5);
+        let first = pattern.volley();
+        let second = pattern.volley();
+        assert_eq!(first.len(), 2);
+        assert!((first[0].angle_to(second[0]) - 0.5).abs() < 1e-4);
+        assert!((first[0].angle_to(first[1]).abs() - TAU / 2.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn a_bullet_passing_close_by_pays_once_it_is_past() {
+        let ring = HITBOX + ENEMY_BULLET_RADIUS + GRAZE_MARGIN / 2.0;
+        let far = ring + GRAZE_MARGIN;
+        assert_eq!(graze_along(&[Vec2::new(far, ring)]), 0);
+        assert_eq!(
+            graze_along(&[Vec2::new(far, ring), Vec2::new(0.0, ring)]),
+            0
+        );
+        assert_eq!(
+            graze_along(&[
+                Vec2::new(far, ring),
+                Vec2::new(0.0, ring),
+                Vec2::new(-far, ring),
+                Vec2::new(-far * 2.0, ring),
+            ]),
+            GRAZE_POINTS
+        );
+    }
+
+    #[test]
+    fn a_bullet_that_hits_pays_nothing() {
+        let ring = HITBOX + ENEMY_BULLET_RADIUS + GRAZE_MARGIN / 2.0;
+        assert_eq!(
+            graze_along(&[
+                Vec2::new(ring, 0.0),
+                Vec2::ZERO,
+                Vec2::new(-ring, 0.0),
+                Vec2::new(-ring * 4.0, 0.0),
+            ]),
+            0
+        );
+    }
+}
@@ -0,0 +1,105 @@
+//! A clock for gameplay that only runs during play.
+//!
+//! Bevy's `Time` keeps going while the game is paused or in the menus.
+//! Gameplay timers, such as cooldowns, power-ups, invincibility, the combo
+//! window and the spawners, read `GameTime` instead. Its delta is zero outside
+//! `GameState::Playing`, so a pause freezes them all together, whatever state
+//! their systems happen to run in. It follows the virtual clock, so the slow
+//! motion of a boss's death slows it too.
+
+use std::time::Duration;
+
+use bevy::{prelude::*, time::TimeSystem};
+
+use crate::GameState;
+
+pub struct GameTimePlugin;
+
+impl Plugin for GameTimePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GameTime>()
+            .add_systems(First, advance_game_time.after(TimeSystem));
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct GameTime {
+    delta: Duration,
+    elapsed: Duration,
+}
+
+impl GameTime {
+    /// Time since the last frame, or zero if that wasn't spent playing.
+    pub fn delta(&self) -> Duration {
+        self.delta
+    }
+
+    pub fn delta_secs(&self) -> f32 {
+        self.delta.as_secs_f32()
+    }
+
+    pub fn delta_secs_f64(&self) -> f64 {
+        self.delta.as_secs_f64()
+    }
+
+    /// Total time spent playing since launch.
+    pub fn elapsed_secs(&self) -> f32 {
+        self.elapsed.as_secs_f32()
+    }
+}
+
+fn advance_game_time(
+    mut game_time: ResMut<GameTime>,
+    state: Res<State<GameState>>,
+    time: Res<Time>,
+) {
+    let delta = if *state.get() == GameState::Playing {
+        time.delta()
+    } else {
+        Duration::ZERO
+    };
+    game_time.delta = delta;
+    game_time.elapsed += delta;
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{state::app::StatesPlugin, time::TimeUpdateStrategy};
+
+    use super::*;
+
+    #[derive(Resource)]
+    struct Cooldown(Timer);
+
+    fn tick_cooldown(mut cooldown: ResMut<Cooldown>, time: Res<GameTime>) {
+        cooldown.0.tick(time.delta());
+    }
+
+    #[test]
+    fn a_pause_freezes_gameplay_timers() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, GameTimePlugin))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                100,
+            )))
+            .insert_resource(Cooldown(Timer::from_seconds(1.0, TimerMode::Once)))
+            .insert_state(GameState::Paused)
+            .add_systems(Update, tick_cooldown);
+        for _ in 0..5 {
+            app.update();
+        }
+        assert_eq!(
+            app.world().resource::<Cooldown>().0.elapsed(),
+            Duration::ZERO
+        );
+        assert_eq!(app.world().resource::<GameTime>().elapsed_secs(), 0.0);
+
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Playing);
+        for _ in 0..5 {
+            app.update();
+        }
+        assert!(app.world().resource::<Cooldown>().0.elapsed() > Duration::ZERO);
+    }
+}
@@ -0,0 +1,241 @@
+//! A translucent ghost duck replaying a recorded run alongside the live one,
+//! loaded with `--ghost`.
+//!
+//! The ghost re-simulates the player's movement from the recorded input rather
+//! than using the live movement systems, so it never collides with anything.
+
+use bevy::prelude::*;
+
+use crate::{
+    GameState, PLAYER_FRICTION,
+    game_time::GameTime,
+    play_area::PlayArea,
+    player_bounds,
+    replay::{Replay, ReplayFrame},
+    start_run,
+};
+
+const GHOST_ALPHA: f32 = 0.35;
+
+pub struct GhostPlugin;
+
+impl Plugin for GhostPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnExit(GameState::Menu),
+            spawn_ghost
+                .after(start_run)
+                .run_if(resource_exists::<GhostReplay>),
+        )
+        .add_systems(OnEnter(GameState::Menu), despawn_ghost)
+        .add_systems(
+            Update,
+            (move_ghost, update_ghost_delta)
+                .chain()
+                .run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+#[derive(Resource)]
+pub struct GhostReplay(pub Replay);
+
+#[derive(Component)]
+struct Ghost {
+    frames: Vec<ReplayFrame>,
+    cursor: usize,
+    velocity: Vec2,
+    /// Recorded time covered by the frames played so far.
+    ghost_time: f64,
+    /// Live time since the run started.
+    live_time: f64,
+    /// Length of the whole recorded run.
+    duration: f64,
+    /// `Tuning::player_speed` the run was recorded with.
+    player_speed: f32,
+}
+
+#[derive(Component)]
+struct GhostDeltaText;
+
+fn spawn_ghost(mut commands: Commands, asset_server: Res<AssetServer>, ghost: Res<GhostReplay>) {
+    let frames = ghost.0.frames.clone();
+    let duration = frames.iter().map(|frame| frame.dt).sum();
+    commands.spawn((
+        Sprite {
+            color: Color::WHITE.with_alpha(GHOST_ALPHA),
+            ..Sprite::from_image(asset_server.load("duck.png"))
+        },
+        Transform::from_xyz(0.0, 0.0, -1.0).with_scale(Vec3::splat(0.3)),
+        Ghost {
+            frames,
+            cursor: 0,
+            velocity: Vec2::ZERO,
+            ghost_time: 0.0,
+            live_time: 0.0,
+            duration,
+            player_speed: ghost.0.tuning.player_speed,
+        },
+    ));
+    commands.spawn((
+        Text::default(),
+        TextFont {
+            font_size: 18.0,
+            ..default()
+        },
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            left: Val::Px(10.0),
+            ..default()
+        },
+        GhostDeltaText,
+    ));
+}
+
+#[allow(clippy::type_complexity)]
+fn despawn_ghost(
+    mut commands: Commands,
+    query: Query<Entity, Or<(With<Ghost>, With<GhostDeltaText>)>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// Steps the ghost through every recorded frame that fits in the live time so
+/// far, mirroring `handle_input`, `update` and `apply_bounds`.
+fn move_ghost(
+    mut query: Query<(&mut Ghost, &mut Transform, &mut Visibility)>,
+    play_area: Res<PlayArea>,
+    time: Res<GameTime>,
+) {
+    let bounds = player_bounds(&play_area);
+    for (mut ghost, mut transform, mut visibility) in query.iter_mut() {
+        ghost.live_time += time.delta_secs_f64();
+
+        while let Some(frame) = ghost.frames.get(ghost.cursor).copied() {
+            if ghost.ghost_time + frame.dt > ghost.live_time {
+                break;
+            }
+            ghost.cursor += 1;
+            ghost.ghost_time += frame.dt;
+
+            let input = frame.input();
+            let speed = input.speed(ghost.player_speed);
+            ghost.velocity += input.movement() * speed;
+            let position = transform.translation.truncate() + ghost.velocity * frame.dt as f32;
+            ghost.velocity *= PLAYER_FRICTION;
+
+            let clamped = position.clamp(-bounds, bounds);
+            if clamped.x != position.x {
+                ghost.velocity.x = 0.0;
+            }
+            if clamped.y != position.y {
+                ghost.velocity.y = 0.0;
+            }
+            transform.translation = clamped.extend(transform.translation.z);
+        }
+
+        if ghost.cursor >= ghost.frames.len() {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
+
+fn update_ghost_delta(
+    ghost_query: Query<&Ghost>,
+    mut text_query: Query<&mut Text, With<GhostDeltaText>>,
+) {
+    let (Ok(ghost), Ok(mut text)) = (ghost_query.single(), text_query.single_mut()) else {
+        return;
+    };
+    // Negative while the ghost is still going, positive once it's outlasted.
+    let delta = ghost.live_time - ghost.duration;
+    text.0 = format!("Ghost {delta:+.1}s");
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{state::app::StatesPlugin, time::TimeUpdateStrategy};
+
+    use super::*;
+    use crate::{game_time::GameTimePlugin, tuning::Tuning};
+
+    /// Holds right for three frames, then up for three.
+    const REPLAY: &str = "(
+        version: 1,
+        seed: 0,
+        difficulty: 1.0,
+        frames: [
+            (dt: 0.1, input: 2), (dt: 0.1, input: 2), (dt: 0.1, input: 2),
+            (dt: 0.1, input: 4), (dt: 0.1, input: 4), (dt: 0.1, input: 4),
+        ],
+    )";
+
+    #[test]
+    fn the_ghost_retraces_the_recorded_run() {
+        let replay: Replay = ron::from_str(REPLAY).unwrap();
+        let speed = Tuning::default().player_speed;
+        // Where the duck was after each recorded frame.
+        let mut expected = vec![Vec2::ZERO];
+        let mut velocity = Vec2::ZERO;
+        for direction in [Vec2::X, Vec2::X, Vec2::X, Vec2::Y, Vec2::Y, Vec2::Y] {
+            velocity += direction * speed;
+            expected.push(expected.last().unwrap() + velocity * 0.1);
+            velocity *= PLAYER_FRICTION;
+        }
+
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, GameTimePlugin))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                100,
+            )))
+            .insert_state(GameState::Playing)
+            .insert_resource(PlayArea {
+                half_width: 400.0,
+                half_height: 300.0,
+            })
+            .add_systems(Update, move_ghost);
+        let ghost = app
+            .world_mut()
+            .spawn((
+                Transform::default(),
+                Visibility::default(),
+                Ghost {
+                    frames: replay.frames.clone(),
+                    cursor: 0,
+                    velocity: Vec2::ZERO,
+                    ghost_time: 0.0,
+                    live_time: 0.0,
+                    duration: 0.6,
+                    player_speed: speed,
+                },
+            ))
+            .id();
+
+        let mut played = 0;
+        for _ in 0..10 {
+            app.update();
+            let world = app.world();
+            played = world.get::<Ghost>(ghost).unwrap().cursor;
+            let position = world
+                .get::<Transform>(ghost)
+                .unwrap()
+                .translation
+                .truncate();
+            assert!(
+                position.distance(expected[played]) < 1e-3,
+                "at {position} after {played} frames, expected {}",
+                expected[played]
+            );
+        }
+        assert_eq!(played, replay.frames.len());
+        assert_eq!(
+            app.world().get::<Visibility>(ghost),
+            Some(&Visibility::Hidden)
+        );
+    }
+}
@@ -0,0 +1,252 @@
+//! A letter grade for the run, shown on the game over screen.
+//!
+//! Score, accuracy, best multiplier and survival time are each measured
+//! against a par, capped at full marks, and weighted into a rating from 0.0 to
+//! 1.0 that thresholds turn into S, A, B, C or D. Each modifier in play adds a
+//! bonus to the rating. The pars, weights, bonus and thresholds are all part
+//! of the tuning file.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    GameOverText, GameState, Score, combo::Combo, hud::SurvivalTime, modifiers::RunModifiers,
+    run_stats::RunStats, settings::ReduceMotion, tuning::Tuning,
+};
+
+/// Seconds the grade takes to slam into place.
+const REVEAL_DURATION: f32 = 0.5;
+/// Size the grade starts at, relative to where it settles.
+const REVEAL_SCALE: f32 = 3.0;
+const GRADE_POSITION: Vec2 = Vec2::new(280.0, -10.0);
+
+pub struct GradePlugin;
+
+impl Plugin for GradePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::GameOver), display_grade)
+            .add_systems(Update, reveal_grade.run_if(in_state(GameState::GameOver)));
+    }
+}
+
+/// How a run's stats are graded.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GradeConfig {
+    /// Score worth full marks.
+    pub par_score: f32,
+    /// Best multiplier worth full marks.
+    pub par_multiplier: f32,
+    /// Seconds survived worth full marks.
+    pub par_time: f32,
+    /// How much score, accuracy, multiplier and time each count toward the
+    /// rating, in that order.
+    pub weights: (f32, f32, f32, f32),
+    /// Added to the rating for each modifier in play, still capped at 1.0.
+    pub modifier_bonus: f32,
+    /// Lowest rating earning S, A, B and C, in that order. Anything below is
+    /// a D.
+    pub thresholds: (f32, f32, f32, f32),
+}
+
+impl Default for GradeConfig {
+    fn default() -> Self {
+        Self {
+            par_score: 500.0,
+            par_multiplier: 5.0,
+            par_time: 180.0,
+            weights: (0.4, 0.2, 0.2, 0.2),
+            modifier_bonus: 0.05,
+            thresholds: (0.9, 0.75, 0.55, 0.35),
+        }
+    }
+}
+
+impl GradeConfig {
+    /// Rejects a config that can't grade anything.
+    pub fn validate(&self) -> Result<(), String> {
+        for (name, value) in [
+            ("par_score", self.par_score),
+            ("par_multiplier", self.par_multiplier),
+            ("par_time", self.par_time),
+        ] {
+            if !(value.is_finite() && value > 0.0) {
+                return Err(format!("grades.{name} must be above zero"));
+            }
+        }
+        let (score, accuracy, multiplier, time) = self.weights;
+        if !([score, accuracy, multiplier, time]
+            .iter()
+            .all(|weight| *weight >= 0.0)
+            && score + accuracy + multiplier + time > 0.0)
+        {
+            return Err("grades.weights can't be negative or all zero".to_string());
+        }
+        if !(self.modifier_bonus.is_finite() && self.modifier_bonus >= 0.0) {
+            return Err("grades.modifier_bonus can't be negative".to_string());
+        }
+        let (s, a, b, c) = self.thresholds;
+        if !(s >= a && a >= b && b >= c) {
+            return Err("grades.thresholds must run from S down to C".to_string());
+        }
+        Ok(())
+    }
+
+    /// The weighted rating for a run, from 0.0 to 1.0, before any modifier
+    /// bonus.
+    pub fn rating(&self, score: u32, accuracy: f32, multiplier: u32, time: f32) -> f32 {
+        let (score_weight, accuracy_weight, multiplier_weight, time_weight) = self.weights;
+        let marks = [
+            (score as f32 / self.par_score, score_weight),
+            (accuracy, accuracy_weight),
+            (multiplier as f32 / self.par_multiplier, multiplier_weight),
+            (time / self.par_time, time_weight),
+        ];
+        let total: f32 = marks.iter().map(|(_, weight)| weight).sum();
+        marks
+            .iter()
+            .map(|(mark, weight)| mark.clamp(0.0, 1.0) * weight)
+            .sum::<f32>()
+            / total
+    }
+
+    /// The grade a rating earns. Landing exactly on a threshold earns it.
+    pub fn grade(&self, rating: f32) -> Grade {
+        let (s, a, b, c) = self.thresholds;
+        if rating >= s {
+            Grade::S
+        } else if rating >= a {
+            Grade::A
+        } else if rating >= b {
+            Grade::B
+        } else if rating >= c {
+            Grade::C
+        } else {
+            Grade::D
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Grade {
+    S,
+    A,
+    B,
+    C,
+    D,
+}
+
+impl Grade {
+    fn color(self) -> Color {
+        match self {
+            Self::S => Color::srgb(1.0, 0.85, 0.2),
+            Self::A => Color::srgb(0.4, 1.0, 0.5),
+            Self::B => Color::srgb(0.4, 0.8, 1.0),
+            Self::C => Color::srgb(0.85, 0.85, 0.85),
+            Self::D => Color::srgb(0.8, 0.4, 0.4),
+        }
+    }
+}
+
+/// Shrinks the grade down to its resting size.
+#[derive(Component)]
+struct GradeReveal(Timer);
+
+#[allow(clippy::too_many_arguments)]
+fn display_grade(
+    mut commands: Commands,
+    score: Res<Score>,
+    stats: Res<RunStats>,
+    combo: Res<Combo>,
+    survival_time: Res<SurvivalTime>,
+    modifiers: Res<RunModifiers>,
+    tuning: Res<Tuning>,
+    reduce_motion: Res<ReduceMotion>,
+) {
+    let config = &tuning.grades;
+    let rating = config.rating(
+        score.0,
+        stats.accuracy(),
+        stats.best_multiplier.max(combo.multiplier),
+        survival_time.0,
+    ) + modifiers.0.len() as f32 * config.modifier_bonus;
+    let grade = config.grade(rating.min(1.0));
+
+    commands.spawn((
+        Text2d::new("Rank"),
+        TextFont {
+            font_size: 24.0,
+            ..default()
+        },
+        Transform::from_translation((GRADE_POSITION + Vec2::new(0.0, 70.0)).extend(10.0)),
+        GameOverText,
+    ));
+    let mut letter = commands.spawn((
+        Text2d::new(format!("{grade:?}")),
+        TextFont {
+            font_size: 110.0,
+            ..default()
+        },
+        TextColor(grade.color()),
+        Transform::from_translation(GRADE_POSITION.extend(10.0)),
+        GameOverText,
+    ));
+    if !reduce_motion.0 {
+        letter.insert((
+            Transform::from_translation(GRADE_POSITION.extend(10.0))
+                .with_scale(Vec3::splat(REVEAL_SCALE)),
+            GradeReveal(Timer::from_seconds(REVEAL_DURATION, TimerMode::Once)),
+        ));
+    }
+}
+
+fn reveal_grade(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Transform, &mut GradeReveal)>,
+    time: Res<Time>,
+) {
+    for (entity, mut transform, mut reveal) in query.iter_mut() {
+        reveal.0.tick(time.delta());
+        // Ease out, landing hard.
+        let remaining = 1.0 - reveal.0.fraction();
+        let scale = 1.0 + (REVEAL_SCALE - 1.0) * remaining * remaining;
+        transform.scale = Vec3::splat(scale);
+        if reveal.0.finished() {
+            commands.entity(entity).remove::<GradeReveal>();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn landing_on_a_threshold_earns_its_grade() {
+        let config = GradeConfig::default();
+        let cases = [
+            (1.0, Grade::S),
+            (0.9, Grade::S),
+            (0.899, Grade::A),
+            (0.75, Grade::A),
+            (0.749, Grade::B),
+            (0.55, Grade::B),
+            (0.549, Grade::C),
+            (0.35, Grade::C),
+            (0.349, Grade::D),
+            (0.0, Grade::D),
+        ];
+        for (rating, grade) in cases {
+            assert_eq!(config.grade(rating), grade, "rating {rating}");
+        }
+    }
+
+    #[test]
+    fn marks_are_capped_at_par() {
+        let config = GradeConfig::default();
+        assert_eq!(config.rating(5_000, 1.0, 10, 600.0), 1.0);
+        assert_eq!(config.rating(0, 0.0, 0, 0.0), 0.0);
+        // Half marks on everything but the multiplier, worth 0.2 of the whole.
+        assert!((config.rating(250, 0.5, 0, 90.0) - 0.4).abs() < 1e-6);
+    }
+}
@@ -0,0 +1,624 @@
+//! Screen-space HUD widgets.
+
+use bevy::prelude::*;
+
+use crate::{
+    GameState, Health, Heat, IsPlayer, MAX_HEAT, MaxHealth, Overheated, combo::Combo,
+    game_time::GameTime, time_attack::TimeAttack,
+};
+
+const HEAT_WARNING_FRACTION: f32 = 0.8;
+/// How often the gauge blinks while the gun is cooling from an overheat.
+const COOLING_BLINK_INTERVAL: f32 = 0.25;
+const MULTIPLIER_FONT_SIZE: f32 = 36.0;
+/// Leaves the top line of the corner to a ghost run's time difference.
+const MULTIPLIER_TOP: f32 = 36.0;
+/// How much bigger the multiplier pops when it rises, and smaller when it
+/// falls, as a fraction of its size.
+const MULTIPLIER_GROW: f32 = 0.5;
+const MULTIPLIER_SHRINK: f32 = 0.25;
+/// How quickly the multiplier settles back to its size, in pulses per second.
+const MULTIPLIER_SETTLE_RATE: f32 = 4.0;
+/// Time left in a time attack at which the timer turns red.
+const TIMER_URGENT_SECS: f32 = 10.0;
+const HEART_SIZE: f32 = 14.0;
+const HEART_FULL_COLOR: Color = Color::srgb(0.9, 0.1, 0.2);
+const HEART_EMPTY_COLOR: Color = Color::srgba(0.0, 0.0, 0.0, 0.4);
+/// Time a lost heart takes to fade from a white flash to empty.
+const HEART_LOSS_DURATION: f32 = 0.4;
+
+pub struct HudPlugin;
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SurvivalTime>()
+            .add_systems(Startup, setup_hud)
+            .add_systems(OnExit(GameState::Menu), reset_survival_time)
+            .add_systems(
+                Update,
+                (
+                    update_heat_gauge,
+                    update_combo_bar,
+                    update_multiplier,
+                    update_hearts,
+                    tick_survival_time.run_if(in_state(GameState::Playing)),
+                    update_run_timer.after(tick_survival_time),
+                ),
+            );
+    }
+}
+
+/// Time spent playing the current run, not counting pauses.
+#[derive(Resource, Default)]
+pub struct SurvivalTime(pub f32);
+
+#[derive(Component)]
+struct HeatGauge;
+
+/// Says the gun is cooling down while it's overheated.
+#[derive(Component)]
+struct CoolingLabel;
+
+/// Holds the combo bar, shown only while a combo is going.
+#[derive(Component)]
+struct ComboBar;
+
+/// Shrinks as the combo window runs out and refills on each kill.
+#[derive(Component)]
+struct ComboBarFill;
+
+/// Holds a heart for each point of the player's `MaxHealth`. Hidden while
+/// there's no player.
+#[derive(Component)]
+struct HeartRow;
+
+#[derive(Component)]
+struct Heart {
+    filled: bool,
+    /// Runs while the heart fades out after being lost.
+    loss: Option<Timer>,
+}
+
+/// Counts down the time left in a time attack, or up the time survived in any
+/// other run. Hidden on the menu.
+#[derive(Component)]
+struct RunTimer;
+
+/// Shows the score multiplier, hidden at 1x. Pops bigger when it rises and
+/// dips smaller when it falls.
+#[derive(Component)]
+struct MultiplierWidget {
+    shown: u32,
+    /// 1.0 just after a rise, -1.0 just after a fall, settling back to 0.0.
+    pulse: f32,
+}
+
+fn setup_hud(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                left: Val::Px(10.0),
+                bottom: Val::Px(10.0),
+                width: Val::Px(120.0),
+                height: Val::Px(10.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.4)),
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Node {
+                    width: Val::Percent(0.0),
+                    height: Val::Percent(100.0),
+                    ..default()
+                },
+                BackgroundColor(Color::WHITE),
+                HeatGauge,
+            ));
+        });
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(138.0),
+            bottom: Val::Px(5.0),
+            ..default()
+        },
+        Text::new("Cooling"),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        TextColor(Color::srgb(0.9, 0.1, 0.1)),
+        Visibility::Hidden,
+        CoolingLabel,
+    ));
+
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                bottom: Val::Px(10.0),
+                width: Val::Percent(100.0),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            Visibility::Hidden,
+            ComboBar,
+        ))
+        .with_children(|parent| {
+            parent
+                .spawn((
+                    Node {
+                        width: Val::Px(200.0),
+                        height: Val::Px(6.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.4)),
+                ))
+                .with_children(|parent| {
+                    parent.spawn((
+                        Node {
+                            width: Val::Percent(100.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgb(1.0, 0.6, 0.1)),
+                        ComboBarFill,
+                    ));
+                });
+        });
+
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(10.0),
+            bottom: Val::Px(28.0),
+            column_gap: Val::Px(4.0),
+            ..default()
+        },
+        Visibility::Hidden,
+        HeartRow,
+    ));
+
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(60.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        Text::default(),
+        TextFont {
+            font_size: 32.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        TextLayout::new_with_justify(JustifyText::Center),
+        Visibility::Hidden,
+        RunTimer,
+    ));
+
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            left: Val::Px(10.0),
+            top: Val::Px(MULTIPLIER_TOP),
+            ..default()
+        },
+        Text::new("x1"),
+        TextFont {
+            font_size: MULTIPLIER_FONT_SIZE,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 0.6, 0.1)),
+        Visibility::Hidden,
+        MultiplierWidget {
+            shown: 1,
+            pulse: 0.0,
+        },
+    ));
+}
+
+fn update_heat_gauge(
+    heat: Res<Heat>,
+    overheated: Res<Overheated>,
+    mut query: Query<(&mut Node, &mut BackgroundColor), With<HeatGauge>>,
+    mut label_query: Query<&mut Visibility, With<CoolingLabel>>,
+    time: Res<Time>,
+) {
+    let fraction = (heat.0 / MAX_HEAT).clamp(0.0, 1.0);
+    // Blinks while cooling, so a locked gun looks different from a hot one.
+    let blink_on = ((time.elapsed_secs() / COOLING_BLINK_INTERVAL) as u32).is_multiple_of(2);
+    for (mut node, mut color) in query.iter_mut() {
+        node.width = Val::Percent(fraction * 100.0);
+        color.0 = if overheated.0 {
+            if blink_on {
+                Color::srgb(0.9, 0.1, 0.1)
+            } else {
+                Color::srgb(0.4, 0.05, 0.05)
+            }
+        } else if fraction >= HEAT_WARNING_FRACTION {
+            Color::srgb(0.9, 0.1, 0.1)
+        } else {
+            // Warms from white toward orange as heat builds.
+            Color::WHITE.mix(
+                &Color::srgb(1.0, 0.6, 0.1),
+                fraction / HEAT_WARNING_FRACTION,
+            )
+        };
+    }
+    for mut visibility in label_query.iter_mut() {
+        *visibility = if overheated.0 {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
+
+fn update_combo_bar(
+    combo: Res<Combo>,
+    mut bar_query: Query<&mut Visibility, With<ComboBar>>,
+    mut fill_query: Query<&mut Node, With<ComboBarFill>>,
+) {
+    for mut visibility in bar_query.iter_mut() {
+        *visibility = if combo.is_active() {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+    for mut node in fill_query.iter_mut() {
+        node.width = Val::Percent(combo.window.fraction_remaining() * 100.0);
+    }
+}
+
+fn update_multiplier(
+    combo: Res<Combo>,
+    mut query: Query<(
+        &mut MultiplierWidget,
+        &mut Text,
+        &mut TextFont,
+        &mut Visibility,
+    )>,
+    time: Res<Time>,
+) {
+    for (mut widget, mut text, mut font, mut visibility) in query.iter_mut() {
+        if combo.multiplier != widget.shown {
+            widget.pulse = if combo.multiplier > widget.shown {
+                1.0
+            } else {
+                -1.0
+            };
+            widget.shown = combo.multiplier;
+            text.0 = format!("x{}", combo.multiplier);
+        }
+        *visibility = if combo.multiplier > 1 {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+
+        if widget.pulse != 0.0 {
+            let settle = MULTIPLIER_SETTLE_RATE * time.delta_secs();
+            widget.pulse = if widget.pulse > 0.0 {
+                (widget.pulse - settle).max(0.0)
+            } else {
+                (widget.pulse + settle).min(0.0)
+            };
+            let scale = if widget.pulse > 0.0 {
+                1.0 + MULTIPLIER_GROW * widget.pulse
+            } else {
+                1.0 + MULTIPLIER_SHRINK * widget.pulse
+            };
+            font.font_size = MULTIPLIER_FONT_SIZE * scale;
+        }
+    }
+}
+
+fn reset_survival_time(mut survival_time: ResMut<SurvivalTime>) {
+    survival_time.0 = 0.0;
+}
+
+fn tick_survival_time(mut survival_time: ResMut<SurvivalTime>, time: Res<GameTime>) {
+    survival_time.0 += time.delta_secs();
+}
+
+/// Formats `secs` as minutes, seconds and hundredths, like "01:05.32".
+pub fn format_run_time(secs: f32) -> String {
+    let hundredths = (secs.max(0.0) * 100.0) as u32;
+    format!(
+        "{:02}:{:02}.{:02}",
+        hundredths / 6000,
+        hundredths / 100 % 60,
+        hundredths % 100
+    )
+}
+
+fn update_run_timer(
+    survival_time: Res<SurvivalTime>,
+    time_attack: Option<Res<TimeAttack>>,
+    state: Res<State<GameState>>,
+    mut query: Query<(&mut Text, &mut TextColor, &mut Visibility), With<RunTimer>>,
+) {
+    let Ok((mut text, mut color, mut visibility)) = query.single_mut() else {
+        return;
+    };
+    if matches!(state.get(), GameState::Loading | GameState::Menu) {
+        *visibility = Visibility::Hidden;
+        return;
+    }
+    *visibility = Visibility::Inherited;
+
+    let (secs, urgent) = match &time_attack {
+        Some(time_attack) => {
+            let remaining = time_attack.remaining_secs();
+            (remaining, remaining <= TIMER_URGENT_SECS)
+        }
+        None => (survival_time.0, false),
+    };
+    text.0 = format_run_time(secs);
+    color.0 = if urgent {
+        Color::srgb(0.9, 0.1, 0.1)
+    } else {
+        Color::WHITE
+    };
+}
+
+fn update_hearts(
+    mut commands: Commands,
+    player_query: Query<(&Health, &MaxHealth), With<IsPlayer>>,
+    mut row_query: Query<(Entity, &mut Visibility, Option<&Children>), With<HeartRow>>,
+    mut heart_query: Query<(&mut Heart, &mut BackgroundColor)>,
+    time: Res<Time>,
+) {
+    let Ok((row, mut visibility, children)) = row_query.single_mut() else {
+        return;
+    };
+    let Ok((health, max_health)) = player_query.single() else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    *visibility = Visibility::Inherited;
+
+    // Grow or shrink the row to match the maximum.
+    let hearts: Vec<Entity> = children.map_or_else(Vec::new, |children| children.to_vec());
+    let max = max_health.0 as usize;
+    for &heart in hearts.iter().skip(max) {
+        commands.entity(heart).despawn();
+    }
+    if hearts.len() < max {
+        commands.entity(row).with_children(|parent| {
+            for _ in hearts.len()..max {
+                parent.spawn((
+                    Node {
+                        width: Val::Px(HEART_SIZE),
+                        height: Val::Px(HEART_SIZE),
+                        ..default()
+                    },
+                    BorderRadius::MAX,
+                    BackgroundColor(HEART_FULL_COLOR),
+                    Heart {
+                        filled: true,
+                        loss: None,
+                    },
+                ));
+            }
+        });
+    }
+
+    for (index, &entity) in hearts.iter().take(max).enumerate() {
+        let Ok((mut heart, mut color)) = heart_query.get_mut(entity) else {
+            continue;
+        };
+        let filled = (index as u32) < health.0;
+        if heart.filled && !filled {
+            heart.loss = Some(Timer::from_seconds(HEART_LOSS_DURATION, TimerMode::Once));
+        } else if filled {
+            heart.loss = None;
+        }
+        heart.filled = filled;
+
+        color.0 = if filled {
+            HEART_FULL_COLOR
+        } else if let Some(loss) = &mut heart.loss {
+            // Flash white, then fade to empty.
+            loss.tick(time.delta());
+            Color::WHITE.mix(&HEART_EMPTY_COLOR, loss.fraction())
+        } else {
+            HEART_EMPTY_COLOR
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{ecs::system::RunSystemOnce, state::app::StatesPlugin, time::TimeUpdateStrategy};
+
+    use super::*;
+    use crate::{
+        Health, Heat, IsPlayer, MaxHealth, Overheated, RunMode, game_time::GameTimePlugin,
+        time_attack::TimeAttackPlugin,
+    };
+
+    #[test]
+    fn the_multiplier_shows_above_1x_and_the_bar_while_a_combo_runs() {
+        let mut world = World::new();
+        world.init_resource::<Combo>();
+        world.init_resource::<Time>();
+        let widget = world
+            .spawn((
+                Text::new("x1"),
+                TextFont {
+                    font_size: MULTIPLIER_FONT_SIZE,
+                    ..default()
+                },
+                Visibility::Hidden,
+                MultiplierWidget {
+                    shown: 1,
+                    pulse: 0.0,
+                },
+            ))
+            .id();
+        let bar = world.spawn((Visibility::Hidden, ComboBar)).id();
+        let fill = world.spawn((Node::default(), ComboBarFill)).id();
+        let update = |world: &mut World| {
+            world.run_system_once(update_multiplier).unwrap();
+            world.run_system_once(update_combo_bar).unwrap();
+        };
+
+        update(&mut world);
+        assert_eq!(world.get::<Visibility>(widget), Some(&Visibility::Hidden));
+        assert_eq!(world.get::<Visibility>(bar), Some(&Visibility::Hidden));
+
+        {
+            let mut combo = world.resource_mut::<Combo>();
+            combo.streak = 3;
+            combo.multiplier = 3;
+        }
+        update(&mut world);
+        assert_eq!(world.get::<Text>(widget).unwrap().0, "x3");
+        assert_eq!(
+            world.get::<Visibility>(widget),
+            Some(&Visibility::Inherited)
+        );
+        assert!(world.get::<TextFont>(widget).unwrap().font_size > MULTIPLIER_FONT_SIZE);
+        assert_eq!(world.get::<Visibility>(bar), Some(&Visibility::Inherited));
+        assert_eq!(world.get::<Node>(fill).unwrap().width, Val::Percent(100.0));
+
+        {
+            let mut combo = world.resource_mut::<Combo>();
+            combo.streak = 0;
+            combo.multiplier = 1;
+        }
+        update(&mut world);
+        assert_eq!(world.get::<Text>(widget).unwrap().0, "x1");
+        assert_eq!(world.get::<Visibility>(widget), Some(&Visibility::Hidden));
+        assert_eq!(world.get::<Visibility>(bar), Some(&Visibility::Hidden));
+    }
+
+    #[test]
+    fn run_times_read_as_minutes_seconds_and_hundredths() {
+        assert_eq!(format_run_time(0.0), "00:00.00");
+        assert_eq!(format_run_time(65.32), "01:05.32");
+        assert_eq!(format_run_time(600.0), "10:00.00");
+        assert_eq!(format_run_time(-1.0), "00:00.00");
+    }
+
+    #[test]
+    fn the_timer_counts_survival_time_and_hides_on_the_menu() {
+        let mut world = World::new();
+        world.insert_resource(SurvivalTime(65.32));
+        world.insert_resource(State::new(GameState::Playing));
+        let timer = world
+            .spawn((
+                Text::default(),
+                TextColor::default(),
+                Visibility::Hidden,
+                RunTimer,
+            ))
+            .id();
+
+        world.run_system_once(update_run_timer).unwrap();
+        assert_eq!(world.get::<Text>(timer).unwrap().0, "01:05.32");
+        assert_eq!(world.get::<TextColor>(timer).unwrap().0, Color::WHITE);
+        assert_eq!(world.get::<Visibility>(timer), Some(&Visibility::Inherited));
+
+        world.insert_resource(State::new(GameState::Menu));
+        world.run_system_once(update_run_timer).unwrap();
+        assert_eq!(world.get::<Visibility>(timer), Some(&Visibility::Hidden));
+    }
+
+    #[test]
+    fn the_time_attack_clock_turns_red_near_the_end() {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            StatesPlugin,
+            GameTimePlugin,
+            TimeAttackPlugin,
+        ))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs(1)))
+        .insert_resource(RunMode::TimeAttack)
+        .init_resource::<SurvivalTime>()
+        .insert_state(GameState::Menu)
+        .add_systems(Update, update_run_timer);
+        let timer = app
+            .world_mut()
+            .spawn((
+                Text::default(),
+                TextColor::default(),
+                Visibility::Hidden,
+                RunTimer,
+            ))
+            .id();
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Playing);
+        app.update();
+        let remaining = |app: &App| app.world().resource::<TimeAttack>().remaining_secs();
+        let color = |app: &App| app.world().get::<TextColor>(timer).unwrap().0;
+
+        while remaining(&app) > TIMER_URGENT_SECS {
+            assert_eq!(color(&app), Color::WHITE);
+            app.update();
+        }
+        // The clock and the countdown aren't ordered, so the clock can lag a frame.
+        app.update();
+        assert_eq!(color(&app), Color::srgb(0.9, 0.1, 0.1));
+    }
+
+    #[test]
+    fn one_heart_is_filled_per_point_of_health() {
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        let player = world.spawn((Health(3), MaxHealth(3), IsPlayer)).id();
+        let row = world
+            .spawn((Node::default(), Visibility::Hidden, HeartRow))
+            .id();
+        let filled = |world: &mut World| {
+            world.run_system_once(update_hearts).unwrap();
+            let hearts = world.get::<Children>(row).unwrap().to_vec();
+            assert_eq!(hearts.len(), 3);
+            hearts
+                .iter()
+                .filter(|&&heart| world.get::<Heart>(heart).unwrap().filled)
+                .count()
+        };
+
+        assert_eq!(filled(&mut world), 3);
+        assert_eq!(world.get::<Visibility>(row), Some(&Visibility::Inherited));
+
+        world.get_mut::<Health>(player).unwrap().0 = 1;
+        assert_eq!(filled(&mut world), 1);
+
+        world.get_mut::<Health>(player).unwrap().0 = 0;
+        assert_eq!(filled(&mut world), 0);
+    }
+
+    #[test]
+    fn the_heat_gauge_fills_with_heat_and_says_when_the_gun_is_cooling() {
+        let mut world = World::new();
+        world.init_resource::<Time>();
+        world.insert_resource(Heat(MAX_HEAT * 0.5));
+        world.init_resource::<Overheated>();
+        let gauge = world
+            .spawn((Node::default(), BackgroundColor::default(), HeatGauge))
+            .id();
+        let label = world.spawn((Visibility::Hidden, CoolingLabel)).id();
+
+        world.run_system_once(update_heat_gauge).unwrap();
+        assert_eq!(world.get::<Node>(gauge).unwrap().width, Val::Percent(50.0));
+        assert_eq!(world.get::<Visibility>(label), Some(&Visibility::Hidden));
+
+        world.insert_resource(Heat(MAX_HEAT));
+        world.insert_resource(Overheated(true));
+        world.run_system_once(update_heat_gauge).unwrap();
+        assert_eq!(world.get::<Node>(gauge).unwrap().width, Val::Percent(100.0));
+        assert_eq!(world.get::<Visibility>(label), Some(&Visibility::Inherited));
+    }
+}
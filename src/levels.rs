@@ -0,0 +1,396 @@
+//! Levels mode: a fixed run of stages, each with a limited number of enemies.
+//! Clearing a stage moves on to the next, and clearing the last one wins.
+//!
+//! A `Checkpoint` is taken as each level starts. Dying offers to retry the
+//! level from it rather than starting the whole run over.
+
+use bevy::prelude::*;
+
+use crate::{
+    CritConfig, Difficulty, EnemyBullet, GameOverText, GameState, Health, Heat, IsBullet, IsEnemy,
+    IsPlayer, Mine, MultiShot, Overheated, RunMode, Score, SpreadConfig, Velocity, spawn_enemies,
+    start_run,
+    transition::{FadeTo, in_transition},
+};
+
+pub const LEVEL_COUNT: usize = 4;
+const LEVELS: [Level; LEVEL_COUNT] = [
+    Level {
+        budget: 10,
+        difficulty: 1.0,
+    },
+    Level {
+        budget: 20,
+        difficulty: 1.5,
+    },
+    Level {
+        budget: 30,
+        difficulty: 2.0,
+    },
+    Level {
+        budget: 40,
+        difficulty: 3.0,
+    },
+];
+
+pub struct LevelsPlugin;
+
+impl Plugin for LevelsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnExit(GameState::Menu),
+            start_levels.after(start_run).run_if(in_levels),
+        )
+        .add_systems(OnEnter(GameState::Menu), despawn_levels)
+        // Carrying on past victory in endless mode.
+        .add_systems(
+            OnTransition {
+                exited: GameState::Victory,
+                entered: GameState::Playing,
+            },
+            despawn_levels,
+        )
+        .add_systems(
+            Update,
+            // Checked before spawning, so a fish spawned this frame is already
+            // in the world by the time its level could be judged clear.
+            (
+                advance_level.before(spawn_enemies),
+                record_checkpoint,
+                update_level_text,
+            )
+                .chain()
+                .run_if(in_state(GameState::Playing).and(resource_exists::<LevelProgress>)),
+        )
+        .add_systems(
+            OnEnter(GameState::GameOver),
+            display_retry_prompt.run_if(resource_exists::<Checkpoint>),
+        )
+        .add_systems(
+            Update,
+            retry_input.run_if(
+                in_state(GameState::GameOver)
+                    .and(resource_exists::<Checkpoint>)
+                    .and(not(in_transition)),
+            ),
+        )
+        .add_systems(
+            OnEnter(GameState::Playing),
+            restore_checkpoint.run_if(resource_exists::<RetryLevel>),
+        );
+    }
+}
+
+pub fn in_levels(mode: Res<RunMode>) -> bool {
+    matches!(*mode, RunMode::Levels)
+}
+
+struct Level {
+    /// Enemies spawned over the level.
+    budget: u32,
+    difficulty: f32,
+}
+
+/// The current level of a levels run. Only present during one.
+#[derive(Resource)]
+pub struct LevelProgress {
+    level: usize,
+    /// Enemies still to spawn this level.
+    pub remaining: u32,
+}
+
+#[derive(Component)]
+struct LevelText;
+
+/// The run as it stood when the current level started.
+#[derive(Resource)]
+pub struct Checkpoint {
+    level: usize,
+    score: u32,
+    health: u32,
+    multi_shot: u32,
+    spread_angle_deg: f32,
+    spread_count: u32,
+    crit_chance: f32,
+}
+
+/// Set when leaving the game over screen to retry a level, so the run is put
+/// back to its `Checkpoint` as play resumes.
+#[derive(Resource)]
+struct RetryLevel;
+
+fn start_levels(mut commands: Commands, mut difficulty: ResMut<Difficulty>) {
+    commands.insert_resource(LevelProgress {
+        level: 0,
+        remaining: LEVELS[0].budget,
+    });
+    difficulty.0 = LEVELS[0].difficulty;
+    commands.spawn((
+        Text::default(),
+        TextFont {
+            font_size: 24.0,
+            ..default()
+        },
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            right: Val::Px(10.0),
+            ..default()
+        },
+        LevelText,
+    ));
+}
+
+fn despawn_levels(mut commands: Commands, query: Query<Entity, With<LevelText>>) {
+    commands.remove_resource::<LevelProgress>();
+    commands.remove_resource::<Checkpoint>();
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+/// A level is clear once its whole budget has spawned and none are left alive.
+fn advance_level(
+    mut progress: ResMut<LevelProgress>,
+    mut difficulty: ResMut<Difficulty>,
+    enemy_query: Query<(), With<IsEnemy>>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    if progress.remaining > 0 || !enemy_query.is_empty() {
+        return;
+    }
+    let Some(next) = LEVELS.get(progress.level + 1) else {
+        game_state.set(GameState::Victory);
+        return;
+    };
+    progress.level += 1;
+    progress.remaining = next.budget;
+    difficulty.0 = next.difficulty;
+}
+
+/// Takes a checkpoint as each level, the first included, gets under way.
+#[allow(clippy::too_many_arguments)]
+fn record_checkpoint(
+    mut commands: Commands,
+    progress: Res<LevelProgress>,
+    checkpoint: Option<Res<Checkpoint>>,
+    score: Res<Score>,
+    multi_shot: Res<MultiShot>,
+    spread: Res<SpreadConfig>,
+    crit: Res<CritConfig>,
+    player_query: Query<&Health, With<IsPlayer>>,
+) {
+    if checkpoint.is_some_and(|checkpoint| checkpoint.level == progress.level) {
+        return;
+    }
+    let Ok(health) = player_query.single() else {
+        return;
+    };
+    commands.insert_resource(Checkpoint {
+        level: progress.level,
+        score: score.0,
+        health: health.0,
+        multi_shot: multi_shot.0,
+        spread_angle_deg: spread.angle_deg,
+        spread_count: spread.count,
+        crit_chance: crit.chance,
+    });
+}
+
+fn display_retry_prompt(mut commands: Commands, checkpoint: Res<Checkpoint>) {
+    commands.spawn((
+        Text2d::new(format!(
+            "Press R to retry level {}/{}",
+            checkpoint.level + 1,
+            LEVEL_COUNT
+        )),
+        TextFont {
+            font_size: 24.0,
+            ..default()
+        },
+        Transform::from_xyz(0.0, 60.0, 10.0),
+        GameOverText,
+    ));
+}
+
+fn retry_input(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut fades: EventWriter<FadeTo>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyR) {
+        commands.insert_resource(RetryLevel);
+        fades.write(FadeTo(GameState::Playing));
+    }
+}
+
+/// Clears the field and puts the score, the player's health and upgrades and
+/// the level back to how they were at the checkpoint.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn restore_checkpoint(
+    mut commands: Commands,
+    checkpoint: Res<Checkpoint>,
+    mut progress: ResMut<LevelProgress>,
+    mut difficulty: ResMut<Difficulty>,
+    mut score: ResMut<Score>,
+    mut multi_shot: ResMut<MultiShot>,
+    mut spread: ResMut<SpreadConfig>,
+    mut crit: ResMut<CritConfig>,
+    mut heat: ResMut<Heat>,
+    mut overheated: ResMut<Overheated>,
+    mut player_query: Query<(&mut Health, &mut Transform, &mut Velocity), With<IsPlayer>>,
+    field_query: Query<Entity, Or<(With<IsEnemy>, With<IsBullet>, With<EnemyBullet>, With<Mine>)>>,
+) {
+    commands.remove_resource::<RetryLevel>();
+    for entity in field_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let level = &LEVELS[checkpoint.level];
+    progress.level = checkpoint.level;
+    progress.remaining = level.budget;
+    difficulty.0 = level.difficulty;
+    score.0 = checkpoint.score;
+    multi_shot.0 = checkpoint.multi_shot;
+    spread.angle_deg = checkpoint.spread_angle_deg;
+    spread.count = checkpoint.spread_count;
+    crit.chance = checkpoint.crit_chance;
+    heat.0 = 0.0;
+    overheated.0 = false;
+    if let Ok((mut health, mut transform, mut velocity)) = player_query.single_mut() {
+        health.0 = checkpoint.health;
+        transform.translation = Vec3::ZERO;
+        velocity.0 = Vec2::ZERO;
+    }
+}
+
+fn update_level_text(
+    progress: Res<LevelProgress>,
+    enemy_query: Query<(), With<IsEnemy>>,
+    mut query: Query<&mut Text, With<LevelText>>,
+) {
+    let left = progress.remaining as usize + enemy_query.iter().count();
+    if let Ok(mut text) = query.single_mut() {
+        text.0 = format!(
+            "Level {}/{} - {left} left",
+            progress.level + 1,
+            LEVELS.len()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{ecs::system::RunSystemOnce, state::app::StatesPlugin};
+
+    use super::*;
+    use crate::{
+        hud::SurvivalTime, particles::Explosion, play_area::PlayArea, victory::VictoryPlugin,
+    };
+
+    #[test]
+    fn clearing_the_last_level_wins_with_the_final_score() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, VictoryPlugin))
+            .insert_state(GameState::Playing)
+            .add_event::<Explosion>()
+            .add_event::<FadeTo>()
+            .insert_resource(Score(12_345))
+            .insert_resource(SurvivalTime(0.0))
+            .insert_resource(Difficulty(1.0))
+            .insert_resource(PlayArea {
+                half_width: 400.0,
+                half_height: 300.0,
+            })
+            .insert_resource(RunMode::Levels)
+            .init_resource::<ButtonInput<KeyCode>>()
+            .insert_resource(LevelProgress {
+                level: LEVEL_COUNT - 1,
+                remaining: 0,
+            })
+            .add_systems(Update, advance_level.run_if(in_state(GameState::Playing)));
+        app.update();
+        app.update();
+
+        assert_eq!(
+            *app.world().resource::<State<GameState>>().get(),
+            GameState::Victory
+        );
+        let mut texts = app
+            .world_mut()
+            .query_filtered::<&Text2d, With<GameOverText>>();
+        assert!(
+            texts
+                .iter(app.world())
+                .any(|text| text.0.contains("Final Score: 12,345"))
+        );
+    }
+
+    #[test]
+    fn retrying_puts_the_run_back_to_its_checkpoint() {
+        let mut world = World::new();
+        world.insert_resource(LevelProgress {
+            level: 1,
+            remaining: LEVELS[1].budget,
+        });
+        world.insert_resource(Difficulty(LEVELS[1].difficulty));
+        world.insert_resource(Score(300));
+        world.insert_resource(MultiShot(2));
+        world.insert_resource(SpreadConfig::default());
+        world.insert_resource(CritConfig {
+            chance: 0.2,
+            multiplier: 2,
+            bonus_points: 5,
+        });
+        world.init_resource::<Heat>();
+        world.init_resource::<Overheated>();
+        let player = world
+            .spawn((
+                Health(3),
+                Transform::default(),
+                Velocity::default(),
+                IsPlayer,
+            ))
+            .id();
+        world.run_system_once(record_checkpoint).unwrap();
+
+        // Further into the level the duck picks up more and then dies.
+        world.resource_mut::<LevelProgress>().remaining = 4;
+        world.resource_mut::<Score>().0 = 750;
+        world.resource_mut::<MultiShot>().0 = 3;
+        world.resource_mut::<CritConfig>().chance = 0.5;
+        world.get_mut::<Health>(player).unwrap().0 = 0;
+        world.spawn((Transform::default(), IsEnemy));
+        world.insert_resource(RetryLevel);
+        world.run_system_once(restore_checkpoint).unwrap();
+
+        assert_eq!(world.resource::<Score>().0, 300);
+        assert_eq!(world.resource::<MultiShot>().0, 2);
+        assert_eq!(world.resource::<CritConfig>().chance, 0.2);
+        assert_eq!(world.get::<Health>(player).unwrap().0, 3);
+        assert_eq!(
+            world.resource::<LevelProgress>().remaining,
+            LEVELS[1].budget
+        );
+        assert_eq!(world.query::<&IsEnemy>().iter(&world).count(), 0);
+        assert!(!world.contains_resource::<RetryLevel>());
+    }
+
+    #[test]
+    fn a_level_with_enemies_left_carries_on() {
+        let mut world = World::new();
+        world.insert_resource(Difficulty(1.0));
+        world.insert_resource(NextState::<GameState>::default());
+        world.insert_resource(LevelProgress {
+            level: 0,
+            remaining: 0,
+        });
+        world.spawn(IsEnemy);
+        world.run_system_once(advance_level).unwrap();
+        assert!(matches!(
+            *world.resource::<NextState<GameState>>(),
+            NextState::Unchanged
+        ));
+        assert_eq!(world.resource::<LevelProgress>().level, 0);
+    }
+}
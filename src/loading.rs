@@ -0,0 +1,358 @@
+//! The loading screen shown at launch. Sprites and sounds are all requested up
+//! front, and the menu only comes up once every one of them has finished
+//! loading, so nothing pops in during the first frames of play. A bar shows
+//! how far along it is.
+//!
+//! An asset that fails to load doesn't hold the game up. It's logged and the
+//! game carries on without it, with a magenta square drawn in place of a
+//! missing sprite so the problem is plain to see.
+
+use bevy::{
+    asset::{LoadState, RenderAssetUsages},
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+    sprite::Anchor,
+};
+
+use crate::{
+    GameState,
+    transition::{FadeTo, in_transition},
+};
+
+const IMAGES: [&str; 3] = ["duck.png", "enemies.png", "explosion.png"];
+const SOUNDS: [&str; 7] = [
+    "audio/shot.ogg",
+    "audio/pop.ogg",
+    "audio/crit.ogg",
+    "audio/streak.ogg",
+    "audio/boss_sting.ogg",
+    "audio/music_calm.ogg",
+    "audio/music_intense.ogg",
+];
+/// Turns per second of the loading spinner.
+const SPINNER_SPEED: f32 = 1.5;
+const PROGRESS_BAR_SIZE: Vec2 = Vec2::new(300.0, 12.0);
+/// Width and height of the image standing in for a missing sprite, roughly
+/// the size of the real ones.
+const PLACEHOLDER_SIZE: u32 = 512;
+const PLACEHOLDER_COLOR: [u8; 4] = [255, 0, 255, 255];
+
+pub struct LoadingPlugin;
+
+impl Plugin for LoadingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LoadingProgress>()
+            .add_systems(Startup, start_loading)
+            .add_systems(OnEnter(GameState::Loading), spawn_loading_screen)
+            .add_systems(
+                Update,
+                (
+                    spin_spinner,
+                    // The bar is drawn full before the menu fades in.
+                    (
+                        track_progress,
+                        update_progress_bar,
+                        finish_loading.run_if(not(in_transition)),
+                    )
+                        .chain(),
+                )
+                    .run_if(in_state(GameState::Loading)),
+            )
+            .add_systems(
+                OnExit(GameState::Loading),
+                (despawn_loading_screen, report_failed_assets),
+            );
+    }
+}
+
+/// Everything loaded at launch. The handles are held for the whole game so the
+/// assets stay loaded between uses.
+#[derive(Resource)]
+struct PreloadedAssets(Vec<UntypedHandle>);
+
+impl PreloadedAssets {
+    /// Fraction of the handles that are done with, whether they loaded or
+    /// failed.
+    fn progress(&self, asset_server: &AssetServer) -> f32 {
+        if self.0.is_empty() {
+            return 1.0;
+        }
+        let settled = self
+            .0
+            .iter()
+            .filter(|handle| {
+                matches!(
+                    asset_server.get_load_state(handle.id()),
+                    Some(LoadState::Loaded | LoadState::Failed(_))
+                )
+            })
+            .count();
+        settled as f32 / self.0.len() as f32
+    }
+}
+
+/// How much of the loading is done, from 0.0 to 1.0.
+#[derive(Resource, Default)]
+struct LoadingProgress(f32);
+
+#[derive(Component)]
+struct LoadingScreen;
+
+#[derive(Component)]
+struct Spinner;
+
+/// The filled part of the progress bar, stretched to match `LoadingProgress`.
+#[derive(Component)]
+struct ProgressBarFill;
+
+fn start_loading(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let images = IMAGES
+        .into_iter()
+        .map(|path| asset_server.load::<Image>(path).untyped());
+    let sounds = SOUNDS
+        .into_iter()
+        .map(|path| asset_server.load::<AudioSource>(path).untyped());
+    commands.insert_resource(PreloadedAssets(images.chain(sounds).collect()));
+}
+
+fn spawn_loading_screen(mut commands: Commands) {
+    commands.spawn((
+        Text2d::new("Loading"),
+        TextFont {
+            font_size: 40.0,
+            ..default()
+        },
+        Transform::from_xyz(0.0, 40.0, 10.0),
+        LoadingScreen,
+    ));
+    commands.spawn((
+        Sprite::from_color(Color::WHITE, Vec2::splat(24.0)),
+        Transform::from_xyz(0.0, -20.0, 10.0),
+        Spinner,
+        LoadingScreen,
+    ));
+    commands.spawn((
+        Sprite::from_color(Color::srgb(0.2, 0.2, 0.2), PROGRESS_BAR_SIZE),
+        Transform::from_xyz(0.0, -60.0, 10.0),
+        LoadingScreen,
+    ));
+    commands.spawn((
+        Sprite {
+            color: Color::WHITE,
+            custom_size: Some(Vec2::new(0.0, PROGRESS_BAR_SIZE.y)),
+            anchor: Anchor::CenterLeft,
+            ..default()
+        },
+        Transform::from_xyz(-PROGRESS_BAR_SIZE.x / 2.0, -60.0, 11.0),
+        ProgressBarFill,
+        LoadingScreen,
+    ));
+}
+
+fn spin_spinner(mut query: Query<&mut Transform, With<Spinner>>, time: Res<Time>) {
+    for mut transform in query.iter_mut() {
+        transform.rotate_z(-SPINNER_SPEED * std::f32::consts::TAU * time.delta_secs());
+    }
+}
+
+fn track_progress(
+    assets: Res<PreloadedAssets>,
+    asset_server: Res<AssetServer>,
+    mut progress: ResMut<LoadingProgress>,
+) {
+    progress.0 = assets.progress(&asset_server);
+}
+
+fn update_progress_bar(
+    progress: Res<LoadingProgress>,
+    mut query: Query<&mut Sprite, With<ProgressBarFill>>,
+) {
+    for mut sprite in query.iter_mut() {
+        sprite.custom_size = Some(Vec2::new(
+            PROGRESS_BAR_SIZE.x * progress.0,
+            PROGRESS_BAR_SIZE.y,
+        ));
+    }
+}
+
+/// Moves on to the menu once nothing is left loading.
+fn finish_loading(progress: Res<LoadingProgress>, mut fades: EventWriter<FadeTo>) {
+    if progress.0 >= 1.0 {
+        fades.write(FadeTo(GameState::Menu));
+    }
+}
+
+/// Logs whatever failed to load, filling in missing images with a placeholder.
+/// Every sprite drawn from a missing image shows the placeholder instead.
+fn report_failed_assets(
+    assets: Res<PreloadedAssets>,
+    asset_server: Res<AssetServer>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    for handle in &assets.0 {
+        let Some(LoadState::Failed(err)) = asset_server.get_load_state(handle.id()) else {
+            continue;
+        };
+        let path = handle
+            .path()
+            .map_or_else(String::new, |path| path.to_string());
+        match handle.clone().try_typed::<Image>() {
+            Ok(image) => {
+                warn!("Couldn't load sprite {path}, drawing a placeholder instead: {err}");
+                images.insert(&image, placeholder_image());
+            }
+            Err(_) => warn!("Couldn't load {path}, carrying on without it: {err}"),
+        }
+    }
+}
+
+fn placeholder_image() -> Image {
+    Image::new_fill(
+        Extent3d {
+            width: PLACEHOLDER_SIZE,
+            height: PLACEHOLDER_SIZE,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &PLACEHOLDER_COLOR,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    )
+}
+
+fn despawn_loading_screen(mut commands: Commands, query: Query<Entity, With<LoadingScreen>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::state::app::StatesPlugin;
+
+    use super::*;
+    use crate::transition::{TransitionDuration, TransitionPlugin};
+
+    /// An app sitting on the loading screen, waiting on an image that never
+    /// loads instead of the real assets.
+    fn loading_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin::default(),
+            StatesPlugin,
+            TransitionPlugin,
+        ))
+        .init_asset::<Image>()
+        .init_resource::<LoadingProgress>()
+        .insert_resource(TransitionDuration(0.0))
+        .insert_state(GameState::Loading)
+        .add_systems(
+            Update,
+            (
+                track_progress,
+                update_progress_bar,
+                finish_loading.run_if(not(in_transition)),
+            )
+                .chain()
+                .run_if(in_state(GameState::Loading)),
+        )
+        .add_systems(OnExit(GameState::Loading), report_failed_assets);
+        let pending = never_settles(&mut app);
+        app.insert_resource(PreloadedAssets(vec![pending.untyped()]));
+        app
+    }
+
+    /// Loads a file that isn't there, waiting until the load has failed.
+    fn load_missing(app: &mut App) -> Handle<Image> {
+        let handle = app
+            .world()
+            .resource::<AssetServer>()
+            .load::<Image>("missing.png");
+        for _ in 0..100 {
+            app.update();
+            let state = app
+                .world()
+                .resource::<AssetServer>()
+                .get_load_state(&handle);
+            if matches!(state, Some(LoadState::Failed(_))) {
+                return handle;
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        panic!("missing.png never failed to load");
+    }
+
+    /// An image the asset server isn't loading, so it never settles.
+    fn never_settles(app: &mut App) -> Handle<Image> {
+        app.world_mut()
+            .resource_mut::<Assets<Image>>()
+            .add(placeholder_image())
+    }
+
+    fn state(app: &App) -> GameState {
+        app.world().resource::<State<GameState>>().get().clone()
+    }
+
+    #[test]
+    fn the_menu_waits_until_every_asset_has_settled() {
+        let mut app = loading_app();
+        let missing = load_missing(&mut app);
+        let pending = never_settles(&mut app);
+        app.insert_resource(PreloadedAssets(vec![
+            missing.clone().untyped(),
+            pending.untyped(),
+        ]));
+        for _ in 0..5 {
+            app.update();
+            assert_eq!(state(&app), GameState::Loading);
+        }
+
+        app.insert_resource(PreloadedAssets(vec![missing.untyped()]));
+        for _ in 0..5 {
+            app.update();
+        }
+        assert_eq!(state(&app), GameState::Menu);
+    }
+
+    #[test]
+    fn the_bar_fills_with_the_share_of_assets_settled() {
+        let mut app = loading_app();
+        let fill = app
+            .world_mut()
+            .spawn((Sprite::default(), ProgressBarFill))
+            .id();
+        let missing = load_missing(&mut app);
+        let pending = never_settles(&mut app);
+        app.insert_resource(PreloadedAssets(vec![missing.untyped(), pending.untyped()]));
+        app.update();
+
+        assert_eq!(app.world().resource::<LoadingProgress>().0, 0.5);
+        assert_eq!(
+            app.world().get::<Sprite>(fill).unwrap().custom_size,
+            Some(Vec2::new(PROGRESS_BAR_SIZE.x / 2.0, PROGRESS_BAR_SIZE.y))
+        );
+    }
+
+    #[test]
+    fn a_sprite_that_fails_to_load_is_drawn_as_a_placeholder() {
+        let mut app = loading_app();
+        let missing = load_missing(&mut app);
+        assert!(
+            app.world()
+                .resource::<Assets<Image>>()
+                .get(&missing)
+                .is_none()
+        );
+
+        app.insert_resource(PreloadedAssets(vec![missing.clone().untyped()]));
+        for _ in 0..5 {
+            app.update();
+        }
+        assert_eq!(state(&app), GameState::Menu);
+        let image = app.world().resource::<Assets<Image>>().get(&missing);
+        assert_eq!(image.map(Image::size), Some(UVec2::splat(PLACEHOLDER_SIZE)));
+    }
+}
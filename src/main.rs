@@ -1,208 +1,1860 @@
 //! Renders a 2D scene containing a single, moving sprite.
 
-use std::time::Duration;
+use std::{ops::Range, path::PathBuf};
 
-use bevy::{prelude::*, time::common_conditions::on_timer};
+use aim::{AimAssist, AimMode, AimPlugin, MouseFire};
+use animation::{AnimationPlugin, AnimationTimer};
+use bestiary::BestiaryPlugin;
+use bevy::{asset::io::file::FileAssetReader, audio::Volume, ecs::system::SystemParam, prelude::*};
+use boss::{Boss, BossIntro, BossPlugin, in_boss_death, in_boss_intro};
+use bounds::{BorderDamage, BoundsPlugin, apply_bounds};
+use camera::CameraPlugin;
+use captions::CaptionsPlugin;
+use cli::CliError;
+use coins::CoinsPlugin;
+use combo::{Combo, ComboPlugin};
+use custom_difficulty::{CustomDifficultyPlugin, RunDifficulty};
+use daily::Date;
+use debug::{DebugCheats, DebugOverlay, DebugPlugin, GodMode, god_mode};
+use display::{DisplayPlugin, FixedWindowSize};
+use effects::EffectsPlugin;
+use enemy_fire::{ENEMY_BULLET_RADIUS, EnemyBullet, EnemyFirePlugin, GRAZE_POINTS};
+use game_time::{GameTime, GameTimePlugin};
+use ghost::{GhostPlugin, GhostReplay};
+use grade::GradePlugin;
+use hud::{HudPlugin, SurvivalTime};
+use levels::{LevelProgress, LevelsPlugin, in_levels};
+use loading::LoadingPlugin;
+use menu::MenuPlugin;
+use menu_nav::MenuNavPlugin;
+use modifiers::{Modifier, ModifiersPlugin, RunModifiers};
+use particles::{Explosion, ParticlePlugin};
+use pause::PausePlugin;
+use play_area::{PlayArea, PlayAreaPlugin};
+use popups::{DamagePopup, PopupPlugin, PopupRendering, ScorePopup};
+use power_ups::{
+    ActivePowerUp, DropsPowerUp, POWER_UP_SPREAD_COUNT, Piercing, PowerUpPlugin, RAPID_FIRE_RATE,
+    WeaponPowerUp,
+};
+use practice::{PracticePlugin, in_practice};
+use profiler::{ProfiledSystem, Profiler, ProfilerPlugin};
+use radar::RadarPlugin;
+use replay::{ReplayPlayback, ReplayPlugin};
+use rumble::RumblePlugin;
+use run_stats::RunStatsPlugin;
+use save::SavePlugin;
+use scores::ScoresPlugin;
+use scrolling::{CenteredPlayer, ScrollingPlugin};
+use serde::{Deserialize, Serialize};
+use settings::{AutoFire, SettingsPlugin};
+use sound::{SoundAssets, SoundPlugin, sound_effect};
+use stress::{StressPlugin, StressTest};
+use time_attack::TimeAttackPlugin;
+use transition::{FadeTo, TransitionPlugin, in_transition};
+use tuning::{Tuning, TuningPlugin, enemy_spawn_due, mine_spawn_due};
+use tutorial::{TutorialPlugin, in_tutorial};
+use victory::VictoryPlugin;
+use waves::{ScriptedWaves, WaveData, WavesPlugin};
+
+mod aim;
+mod animation;
+mod bestiary;
+mod boss;
+mod bounds;
+mod camera;
+mod captions;
+mod cli;
+mod coins;
+mod combo;
+mod custom_difficulty;
+mod daily;
+mod debug;
+mod display;
+mod effects;
+mod enemy_fire;
+mod game_time;
+mod ghost;
+mod grade;
+mod hud;
+mod levels;
+mod loading;
+mod menu;
+mod menu_nav;
+mod modifiers;
+#[cfg(feature = "online")]
+mod online_scores;
+mod particles;
+mod pause;
+mod play_area;
+mod popups;
+mod power_ups;
+mod practice;
+mod profiler;
+mod radar;
+mod replay;
+mod rumble;
+mod run_stats;
+mod save;
+mod scores;
+mod scrolling;
+mod settings;
+mod sound;
+mod stress;
+mod time_attack;
+mod transition;
+mod tuning;
+mod tutorial;
+mod victory;
+mod waves;
 
 const WINDOW_WIDTH: f32 = 800.0;
 const WINDOW_HEIGHT: f32 = 600.0;
+const BACKGROUND_COLOR: Color = Color::srgb(0.0, 0.722, 0.961);
+const BULLET_COLOR: Color = Color::srgb(0.1, 0.1, 0.1);
+/// Size of each cell of `enemies.png`.
+const ENEMY_ATLAS_CELL_SIZE: UVec2 = UVec2::new(588, 425);
+const ENEMY_ATLAS_COLUMNS: u32 = 4;
 const PLAYER_SPEED: f32 = 50.0;
+const PLAYER_FRICTION: f32 = 0.8;
+/// Fraction of `Tuning::player_speed` the player moves at while focusing.
+const FOCUS_SPEED_FACTOR: f32 = 0.4;
+const PLAYER_MAX_HEALTH: u32 = 3;
+const PLAYER_HALF_SIZE: Vec2 = Vec2::new(80.0, 65.0);
+const PLAYER_HITBOX_RADIUS: f32 = 18.0;
+const DEFAULT_KNOCKBACK: f32 = 600.0;
+const DIFFICULTY_RAMP_RATE: f32 = 0.01;
+/// Seconds at the start of a run over which spawning eases up from nothing,
+/// giving the player a moment to get their bearings.
+const SPAWN_WARMUP: f32 = 5.0;
+const PLAYER_START: Vec2 = Vec2::ZERO;
+/// For this many seconds into a run nothing spawns within `SAFE_START_RADIUS`
+/// of the player.
+const SAFE_START_DURATION: f32 = 3.0;
+const SAFE_START_RADIUS: f32 = 250.0;
+/// Rolls at a spawn position clear of the start before the spawn is skipped.
+const SAFE_START_ATTEMPTS: u32 = 8;
+const DESPAWN_MARGIN: f32 = 100.0;
+const ENEMY_CONTACT_DAMAGE: u32 = 1;
+/// How far into a fish the player can get while darting past it and still
+/// have it count as a close call rather than a hit, so long as they aren't
+/// heading into it.
+const CLOSE_CALL_DEPTH: f32 = 8.0;
+const ENEMY_BULLET_DAMAGE: u32 = 1;
+const INVINCIBILITY_DURATION: f32 = 1.5;
+const INVINCIBILITY_BLINK_INTERVAL: f32 = 0.1;
+const BULLET_DAMAGE: u32 = 1;
+const FISH_HP: u32 = 1;
+const FISH_POINTS: u32 = 1;
+const FISH_RADIUS: f32 = 24.0;
+/// Vertical speed a seeker aims for per unit of height between it and the duck.
+const SEEKER_STRENGTH: f32 = 0.5;
+/// Fastest a seeker climbs or dives, kept slow enough to dodge.
+const MAX_SEEK_SPEED: f32 = 35.0;
+/// How quickly a seeker's vertical speed turns toward what it's aiming for.
+const SEEK_ACCELERATION: f32 = 40.0;
+const SEEKER_TINT: Color = Color::srgb(0.6, 1.0, 0.6);
+/// How much closer than an enemy's `BodyRadius` a bullet has to get to hit it.
+const BULLET_RADIUS: f32 = 6.0;
+/// Where shots are aimed from, relative to the player's center.
+const MUZZLE_OFFSET: Vec2 = Vec2::new(0.0, 14.0);
+/// How far along the aim from the muzzle bullets appear.
+const MUZZLE_DISTANCE: f32 = 70.0;
+const CRIT_CHANCE: f32 = 0.1;
+const CRIT_MULTIPLIER: u32 = 3;
+const CRIT_BONUS_POINTS: u32 = 2;
+const MINE_HP: u32 = 3;
+const MINE_RADIUS: f32 = 14.0;
+const MINE_COLOR: Color = Color::srgb(0.5, 0.05, 0.05);
+const MINE_CONTACT_DAMAGE: u32 = 2;
+const MINE_EXPLOSION_RADIUS: f32 = 80.0;
+const MAX_HEAT: f32 = 100.0;
+const HEAT_PER_SHOT: f32 = 12.0;
+const HEAT_DECAY_RATE: f32 = 25.0;
+const HEAT_RESUME_THRESHOLD: f32 = 40.0;
+const AUTO_FIRE_INTERVAL: f32 = 0.15;
+const MAX_MULTI_SHOT: u32 = 5;
+const MULTI_SHOT_SPACING: f32 = 12.0;
+const SCORE_DECAY_RATE: f32 = 0.5;
+const SCORE_DECAY_GRACE: f32 = 10.0;
+/// Fraction of the gap to the real score the displayed score closes per second.
+const SCORE_COUNT_RATE: f32 = 8.0;
+/// Slowest the displayed score counts up, in points per second, so small gaps
+/// still close quickly.
+const SCORE_COUNT_MIN_SPEED: f32 = 20.0;
 
 #[derive(States, Default, Debug, Clone, PartialEq, Eq, Hash)]
 enum GameState {
+    /// Waiting on assets at launch.
     #[default]
+    Loading,
+    Menu,
     Playing,
+    Paused,
     GameOver,
+    Victory,
+}
+
+/// The kind of run being played, chosen from the menu.
+#[derive(Resource, Default, Clone, Copy)]
+enum RunMode {
+    #[default]
+    Standard,
+    /// Seeded from the date. Only the first attempt of the day is `ranked`.
+    Daily { date: Date, ranked: bool },
+    /// Score as much as possible before a fixed time limit runs out.
+    TimeAttack,
+    /// A fixed sequence of stages with a limited number of enemies each.
+    Levels,
+    /// No spawning or damage; the tutorial drives the run instead.
+    Tutorial,
+    /// No spawning or damage, only target dummies to shoot at.
+    Practice,
 }
 
 #[derive(Resource)]
 struct Score(u32);
 
+/// The score as shown, counting up toward `Score` rather than jumping to it.
+#[derive(Resource, Default)]
+struct DisplayedScore(f32);
+
+impl DisplayedScore {
+    /// Moves toward `target` over `dt` seconds, landing on it exactly. Drops
+    /// straight down when the score falls.
+    fn approach(&mut self, target: u32, dt: f32) {
+        let target = target as f32;
+        let gap = target - self.0;
+        let step = (gap * SCORE_COUNT_RATE * dt).max(SCORE_COUNT_MIN_SPEED * dt);
+        self.0 = if gap <= step { target } else { self.0 + step };
+    }
+}
+
+/// Formats `value` with commas between each group of three digits.
+fn format_thousands(value: u32) -> String {
+    let digits = value.to_string();
+    let mut formatted = String::with_capacity(digits.len() + digits.len() / 3);
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            formatted.push(',');
+        }
+        formatted.push(digit);
+    }
+    formatted
+}
+
+/// How `Score` behaves over a run.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+enum ScoreMode {
+    #[default]
+    Normal,
+    /// Sudden death: the score drains over time and the run ends once it hits
+    /// zero, so only steady kills keep it going.
+    Decay,
+}
+
+/// Drain settings and progress for `ScoreMode::Decay`.
+#[derive(Resource)]
+struct ScoreDecay {
+    /// Points lost per second.
+    rate: f32,
+    /// Time at the start of a run before an empty score ends it.
+    grace: Timer,
+    /// Fraction of a point drained but not yet taken off the score.
+    pending: f32,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+enum DifficultyPreset {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+}
+
+impl DifficultyPreset {
+    fn starting_difficulty(self) -> f32 {
+        match self {
+            Self::Easy => 0.7,
+            Self::Normal => 1.0,
+            Self::Hard => 1.5,
+        }
+    }
+}
+
+/// Gun temperature, raised by each shot and cooling over time.
+#[derive(Resource, Default)]
+struct Heat(f32);
+
+/// Time between shots while auto-fire is on.
+#[derive(Resource)]
+struct FireCooldown(Timer);
+
+/// How many parallel bullets each shot fires, up to `MAX_MULTI_SHOT`.
+/// Upgrades raise it; it goes back to one at the start of every run.
+#[derive(Resource)]
+struct MultiShot(u32);
+
+impl Default for MultiShot {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+/// The fan of bullets each shot fires, evenly spaced across `angle_deg` in
+/// total and centered on the aim. A single bullet always flies straight.
+/// Upgrades widen the fan or add to it.
+#[derive(Resource)]
+struct SpreadConfig {
+    angle_deg: f32,
+    count: u32,
+}
+
+impl Default for SpreadConfig {
+    fn default() -> Self {
+        Self {
+            angle_deg: 30.0,
+            count: 1,
+        }
+    }
+}
+
+impl SpreadConfig {
+    fn directions(&self, aim: Vec2) -> impl Iterator<Item = Vec2> {
+        let count = self.count.max(1);
+        let step = if count > 1 {
+            self.angle_deg.to_radians() / (count - 1) as f32
+        } else {
+            0.0
+        };
+        let first = aim.to_angle() - step * (count - 1) as f32 / 2.0;
+        (0..count).map(move |i| Vec2::from_angle(first + step * i as f32))
+    }
+}
+
+/// Set once `Heat` reaches `MAX_HEAT` and cleared only after it cools below
+/// `HEAT_RESUME_THRESHOLD`.
+#[derive(Resource, Default)]
+struct Overheated(bool);
+
+/// Set when fire is pressed just before an overheat clears, so the shot goes
+/// off the moment it does instead of being dropped.
+#[derive(Resource, Default)]
+struct BufferedShot(bool);
+
+/// Scales enemy spawning. Starts at 1.0 and climbs steadily over a run.
+#[derive(Resource)]
+struct Difficulty(f32);
+
+/// How many enemies may be alive at once, growing with `Difficulty` up to `limit`.
+#[derive(Resource)]
+struct MaxEnemies {
+    base: usize,
+    per_difficulty: usize,
+    limit: usize,
+}
+
+impl MaxEnemies {
+    fn at(&self, difficulty: f32) -> usize {
+        let extra = (difficulty - 1.0).max(0.0) * self.per_difficulty as f32;
+        (self.base + extra as usize).min(self.limit)
+    }
+}
+
+/// Seed the run's `GameRng` started from.
+#[derive(Resource)]
+struct RunSeed(u64);
+
+/// Seed for standard runs, fixed by `--seed` or a replay. Random when unset.
+#[derive(Resource)]
+struct SeedOverride(Option<u64>);
+
+/// `Difficulty` at the start of a standard run.
+#[derive(Resource)]
+struct StartingDifficulty(f32);
+
+/// Source of randomness for gameplay, so a run can be saved and reproduced.
+/// Purely cosmetic effects draw from the global `fastrand` generator instead.
+#[derive(Resource)]
+struct GameRng(fastrand::Rng);
+
+/// Odds and payoff of a bullet landing a critical hit. Upgrades raise `chance`.
+#[derive(Resource)]
+struct CritConfig {
+    chance: f32,
+    /// Factor applied to a crit's `Damage`.
+    multiplier: u32,
+    /// Extra score for a kill landed with a crit.
+    bonus_points: u32,
+}
+
+/// Speed of the impulse pushing the player away from whatever hit them.
+#[derive(Resource)]
+struct Knockback(f32);
+
 #[derive(Resource)]
 struct BulletAssets {
     mesh: Handle<Mesh>,
     material: Handle<ColorMaterial>,
 }
 
+/// Sprite sheet shared by every kind of enemy drawn from art, with the frames
+/// of each one's animation side by side.
+#[derive(Resource)]
+struct EnemyAtlas {
+    image: Handle<Image>,
+    layout: Handle<TextureAtlasLayout>,
+}
+
+impl EnemyAtlas {
+    /// The kind's sprite, animated from its first frame.
+    fn sprite(&self, kind: EnemySprite) -> impl Bundle {
+        let (frames, frame_time) = kind.animation();
+        (
+            Sprite::from_atlas_image(
+                self.image.clone(),
+                TextureAtlas {
+                    layout: self.layout.clone(),
+                    index: frames.start,
+                },
+            ),
+            AnimationTimer::new(frames, frame_time),
+        )
+    }
+
+    /// The kind's first frame, for showing in menus.
+    fn image_node(&self, kind: EnemySprite) -> ImageNode {
+        ImageNode::from_atlas_image(
+            self.image.clone(),
+            TextureAtlas {
+                layout: self.layout.clone(),
+                index: kind.animation().0.start,
+            },
+        )
+    }
+}
+
+/// The kinds of enemy art in the `EnemyAtlas`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EnemySprite {
+    Fish,
+    Boss,
+}
+
+impl EnemySprite {
+    /// Cells of the atlas the kind's animation cycles through, and the
+    /// seconds each is shown for.
+    fn animation(self) -> (Range<usize>, f32) {
+        match self {
+            Self::Fish => (0..4, 0.12),
+            // A giant fish, swimming more lazily.
+            Self::Boss => (0..4, 0.25),
+        }
+    }
+}
+
+#[derive(Resource)]
+struct MineAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<ColorMaterial>,
+}
+
 #[derive(Component)]
 struct IsPlayer;
 
+/// Radius of the circle, centered on the player, that enemies and their
+/// bullets have to touch to hurt it. Much smaller than the sprite, so shots
+/// that only clip a wing or the tail miss.
+#[derive(Component)]
+struct PlayerHitbox(f32);
+
 #[derive(Component)]
 struct IsEnemy;
 
+/// Radius of an enemy's body for collisions with the player and bullets.
+#[derive(Component)]
+struct BodyRadius(f32);
+
+#[derive(Component)]
+struct IsBullet;
+
+/// How far a bullet can get before it falls away, whether or not it's still
+/// on screen. Counts down as the bullet flies.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+enum BulletLifetime {
+    /// Seconds of flight left.
+    Time(f32),
+    /// Distance left to travel.
+    Distance(f32),
+}
+
+/// How much health a bullet takes off whatever it hits.
+#[derive(Component)]
+struct Damage(u32);
+
+/// Remaining hits an enemy can take, in units of `Damage`.
+#[derive(Component)]
+struct EnemyHealth(u32);
+
+/// Score awarded for destroying an enemy, however it was destroyed.
+#[derive(Component)]
+struct PointValue(u32);
+
+#[derive(Component)]
+struct ScoreText;
+
+/// Anything shown on the game over screen, cleared when leaving it.
+#[derive(Component)]
+struct GameOverText;
+
+#[derive(Component)]
+struct Health(u32);
+
+/// The most `Health` the player can have.
+#[derive(Component)]
+struct MaxHealth(u32);
+
+/// Grace period after a hit during which the player can't be damaged.
+#[derive(Component)]
+struct Invincible(Timer);
+
+#[derive(Event)]
+struct PlayerHit;
+
+/// An enemy was destroyed by the player, by a bullet or a blast.
+#[derive(Event)]
+struct EnemyKilled {
+    position: Vec2,
+    /// The enemy's `BodyRadius`.
+    radius: f32,
+    /// The power-up it leaves behind, for a gunfish.
+    drop: Option<WeaponPowerUp>,
+    boss: bool,
+}
+
+/// One of the player's bullets struck an enemy or a mine.
+#[derive(Event)]
+struct BulletHit {
+    position: Vec2,
+    /// Health taken off what it struck, counting any critical hit.
+    damage: u32,
+    crit: bool,
+}
+
+/// A drifting mine that detonates once its `hp` is shot down to zero.
+#[derive(Component)]
+struct Mine {
+    hp: u32,
+}
+
 #[derive(Component, Default)]
 struct Velocity(Vec2);
 
+/// The kinds of fish the spawners pick between, weighted by the tuning. The
+/// wave schedule can also name them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FishKind {
+    /// Swims straight across.
+    Plain,
+    /// Steers toward the duck's height.
+    Seeker,
+    /// Drops a power-up when killed.
+    Gunfish,
+}
+
+/// Steers an enemy up or down toward the player's height as it swims.
+#[derive(Component)]
+struct SeekPlayerY {
+    /// Vertical speed aimed for per unit of height between the two.
+    strength: f32,
+}
+
+/// What the player is asking for this frame, read from the keyboard or a replay.
+#[derive(Resource, Clone, Copy)]
+struct PlayerInput {
+    left: bool,
+    right: bool,
+    up: bool,
+    down: bool,
+    fire: bool,
+    /// Slow, precise movement for dodging.
+    focus: bool,
+    /// Unit direction shots are fired in.
+    aim: Vec2,
+    /// Whether aim assist may turn shots toward nearby enemies.
+    assisted: bool,
+}
+
+impl Default for PlayerInput {
+    fn default() -> Self {
+        Self {
+            left: false,
+            right: false,
+            up: false,
+            down: false,
+            fire: false,
+            focus: false,
+            aim: Vec2::X,
+            assisted: false,
+        }
+    }
+}
+
+impl PlayerInput {
+    /// How hard the movement keys push the player this frame, moving at
+    /// `player_speed` when not focusing.
+    fn speed(&self, player_speed: f32) -> f32 {
+        if self.focus {
+            player_speed * FOCUS_SPEED_FACTOR
+        } else {
+            player_speed
+        }
+    }
+
+    /// Unnormalized direction the movement keys point in.
+    fn movement(&self) -> Vec2 {
+        let mut direction = Vec2::ZERO;
+        if self.left {
+            direction.x -= 1.0;
+        }
+        if self.right {
+            direction.x += 1.0;
+        }
+        if self.up {
+            direction.y += 1.0;
+        }
+        if self.down {
+            direction.y -= 1.0;
+        }
+        direction
+    }
+}
+
+/// `path` within the assets folder, found where the `AssetServer` looks for it
+/// rather than relative to wherever the game was launched from.
+fn asset_file(path: &str) -> PathBuf {
+    FileAssetReader::get_base_path().join("assets").join(path)
+}
+
 fn main() {
-    App::new()
-        .add_plugins(DefaultPlugins.set(WindowPlugin {
-            primary_window: Some(Window {
-                title: "The Duck Game".to_string(),
-                resolution: (WINDOW_WIDTH, WINDOW_HEIGHT).into(),
-                resizable: false,
-                ..default()
-            }),
+    let config = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(config) => config,
+        Err(CliError::Help) => {
+            println!("{}", cli::USAGE);
+            return;
+        }
+        Err(CliError::Invalid(err)) => {
+            eprintln!("error: {err}\n\n{}", cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    let replay = config.replay.as_deref().map(|path| {
+        replay::load_replay(path).unwrap_or_else(|err| {
+            eprintln!("error: couldn't load replay {}: {err}", path.display());
+            std::process::exit(1);
+        })
+    });
+
+    let seed = replay.as_ref().map(|replay| replay.seed).or(config.seed);
+    let difficulty = replay.as_ref().map_or_else(
+        || config.difficulty.starting_difficulty(),
+        |replay| replay.difficulty,
+    );
+    // Spawns follow the window's size, so a replay is played back at the size
+    // it was recorded at.
+    let fixed_size = match &replay {
+        Some(replay) => Some(replay.window_size.unwrap_or((WINDOW_WIDTH, WINDOW_HEIGHT))),
+        None => config.window_size.map(|(w, h)| (w as f32, h as f32)),
+    };
+    let (width, height) = fixed_size.unwrap_or((WINDOW_WIDTH, WINDOW_HEIGHT));
+
+    let mut app = App::new();
+    app.add_plugins(DefaultPlugins.set(WindowPlugin {
+        primary_window: Some(Window {
+            title: "The Duck Game".to_string(),
+            resolution: (width, height).into(),
+            resizable: false,
             ..default()
-        }))
-        .insert_resource(ClearColor(Color::srgb(0.0, 0.722, 0.961)))
+        }),
+        ..default()
+    }));
+    if fixed_size.is_some() {
+        app.insert_resource(FixedWindowSize);
+    }
+    if config.mute {
+        app.insert_resource(GlobalVolume::new(Volume::Linear(0.0)));
+    }
+    if config.batched_popups {
+        app.insert_resource(PopupRendering::Batched);
+    }
+    if config.debug || cfg!(debug_assertions) {
+        app.insert_resource(DebugCheats);
+    }
+    if let Some(count) = config.stress {
+        app.insert_resource(StressTest::new(count))
+            .insert_resource(GodMode(true))
+            .add_plugins(StressPlugin);
+    }
+    if let Some(grid_spacing) = config.grid_spacing {
+        app.insert_resource(DebugOverlay {
+            visible: false,
+            grid_spacing,
+        });
+    }
+    if replay
+        .as_ref()
+        .map_or(config.border_damage, |replay| replay.border_damage)
+    {
+        app.init_resource::<BorderDamage>();
+    }
+    if replay
+        .as_ref()
+        .map_or(config.centered, |replay| replay.centered)
+    {
+        app.insert_resource(CenteredPlayer);
+    }
+    if replay.as_ref().map_or(config.waves, |replay| replay.waves) {
+        app.insert_resource(ScriptedWaves);
+    }
+    if let Some(replay) = replay {
+        app.insert_resource(replay.tuning.clone())
+            .insert_resource(ReplayPlayback::new(replay));
+    }
+    if let Some(path) = &config.ghost {
+        if app.world().contains_resource::<CenteredPlayer>() {
+            // The ghost would drift off as the field scrolled under it.
+            eprintln!("warning: the ghost isn't shown with --centered");
+        } else {
+            match replay::load_replay(path) {
+                Ok(ghost) => {
+                    app.insert_resource(GhostReplay(ghost));
+                }
+                Err(err) => {
+                    eprintln!("error: couldn't load ghost {}: {err}", path.display());
+                    std::process::exit(1);
+                }
+            }
+        }
+    }
+    #[cfg(feature = "online")]
+    app.add_plugins(online_scores::OnlineScoresPlugin);
+
+    app.insert_resource(ClearColor(BACKGROUND_COLOR))
         .insert_resource(Score(0))
+        .init_resource::<DisplayedScore>()
+        .init_resource::<Heat>()
+        .init_resource::<Overheated>()
+        .init_resource::<BufferedShot>()
+        .init_resource::<MultiShot>()
+        .init_resource::<SpreadConfig>()
+        .insert_resource(FireCooldown(Timer::from_seconds(
+            AUTO_FIRE_INTERVAL,
+            TimerMode::Repeating,
+        )))
+        .init_resource::<PlayerInput>()
+        .init_resource::<RunMode>()
+        .init_resource::<ScoreMode>()
+        .insert_resource(ScoreDecay {
+            rate: SCORE_DECAY_RATE,
+            grace: Timer::from_seconds(SCORE_DECAY_GRACE, TimerMode::Once),
+            pending: 0.0,
+        })
+        .insert_resource(Knockback(DEFAULT_KNOCKBACK))
+        .insert_resource(CritConfig {
+            chance: CRIT_CHANCE,
+            multiplier: CRIT_MULTIPLIER,
+            bonus_points: CRIT_BONUS_POINTS,
+        })
+        .insert_resource(Difficulty(difficulty))
+        .insert_resource(StartingDifficulty(difficulty))
+        .insert_resource(SeedOverride(seed))
+        .insert_resource(RunSeed(0))
+        .insert_resource(GameRng(fastrand::Rng::new()))
+        .insert_resource(MaxEnemies {
+            base: 20,
+            per_difficulty: 10,
+            limit: 60,
+        })
+        .add_event::<PlayerHit>()
+        .add_event::<EnemyKilled>()
+        .add_event::<BulletHit>()
+        .add_plugins((
+            AimPlugin,
+            BossPlugin,
+            BoundsPlugin,
+            CaptionsPlugin,
+            ComboPlugin,
+            DisplayPlugin,
+            EffectsPlugin,
+            EnemyFirePlugin,
+            GhostPlugin,
+            HudPlugin,
+            LevelsPlugin,
+            MenuPlugin,
+            MenuNavPlugin,
+            ParticlePlugin,
+            PausePlugin,
+        ))
+        .add_plugins((
+            CustomDifficultyPlugin,
+            PlayAreaPlugin,
+            PopupPlugin,
+            ProfilerPlugin,
+            ReplayPlugin,
+            RumblePlugin,
+            SavePlugin,
+            ScoresPlugin,
+            SettingsPlugin,
+            SoundPlugin,
+            TimeAttackPlugin,
+            TransitionPlugin,
+            TutorialPlugin,
+            VictoryPlugin,
+            WavesPlugin,
+        ))
+        .add_plugins((
+            AnimationPlugin,
+            BestiaryPlugin,
+            CameraPlugin,
+            CoinsPlugin,
+            DebugPlugin,
+            GameTimePlugin,
+            GradePlugin,
+            LoadingPlugin,
+            ModifiersPlugin,
+            RunStatsPlugin,
+            PowerUpPlugin,
+            PracticePlugin,
+            RadarPlugin,
+            ScrollingPlugin,
+            TuningPlugin,
+        ))
         .init_state::<GameState>()
         .add_systems(Startup, setup)
+        .add_systems(OnEnter(GameState::Menu), despawn_run)
+        .add_systems(OnExit(GameState::Menu), start_run)
+        .add_systems(
+            Update,
+            read_player_input
+                .in_set(InputSet)
+                .run_if(in_state(GameState::Playing)),
+        )
         .add_systems(
             Update,
             (
-                handle_input,
-                update,
-                spawn_enemies.run_if(on_timer(Duration::from_secs_f32(0.25))),
-                spawn_bullets,
-                check_for_collisions,
-                check_for_player_collisions,
-                update_score_text,
+                handle_input.after(InputSet).run_if(not(in_boss_death)),
+                (seek_player_y, update, expire_bullets).chain(),
+                tint_seekers,
+                draw_focus_hitbox.after(apply_bounds),
+                ramp_difficulty.run_if(not(in_tutorial).and(not(in_levels)).and(not(in_practice))),
+                // Scripted waves take over unless the levels mode is counting
+                // spawns itself.
+                spawn_enemies
+                    .after(spawn_bullets)
+                    .run_if(enemy_spawn_due)
+                    .run_if(
+                        not(in_tutorial)
+                            .and(not(in_practice))
+                            .and(not(in_boss_intro))
+                            .and(not(in_boss_death)),
+                    )
+                    .run_if(not(resource_exists::<WaveData>).or(in_levels)),
+                spawn_mines
+                    .after(spawn_enemies)
+                    .run_if(mine_spawn_due)
+                    .run_if(
+                        not(in_tutorial)
+                            .and(not(in_practice))
+                            .and(not(in_boss_intro))
+                            .and(not(in_boss_death)),
+                    )
+                    .run_if(not(resource_exists::<WaveData>).or(in_levels)),
+                spawn_bullets.after(InputSet).run_if(not(in_boss_death)),
+                ensure_bullet_material.before(spawn_bullets),
+                cool_weapon,
+                decay_score.run_if(resource_equals(ScoreMode::Decay)),
+                // Everything drawing from `GameRng` runs in a fixed order so
+                // replays stay in sync: the spawners, the scripted waves
+                // included, after `spawn_bullets`, then this chain, then kill
+                // sounds after `apply_explosions`.
+                (
+                    check_for_collisions.after(spawn_mines),
+                    detonate_mines,
+                    apply_explosions,
+                )
+                    .chain(),
+                check_for_player_collisions
+                    .run_if(not(in_tutorial).and(not(in_practice)).and(not(god_mode))),
+                tick_invincibility,
             )
                 .run_if(in_state(GameState::Playing)),
         )
+        // Kept running after a run ends so the count finishes on the final score.
+        .add_systems(Update, update_score_text.after(apply_explosions))
         .add_systems(
             OnEnter(GameState::GameOver),
             (darken_screen, display_game_over_text),
         )
+        .add_systems(
+            Update,
+            return_to_menu.run_if(
+                (in_state(GameState::GameOver).or(in_state(GameState::Victory)))
+                    .and(not(in_transition)),
+            ),
+        )
+        .add_systems(
+            OnExit(GameState::GameOver),
+            (restore_screen, despawn_game_over_text),
+        )
+        .add_systems(OnExit(GameState::Victory), despawn_game_over_text)
         .run();
 }
 
+/// Produces `PlayerInput` for the frame. Anything reading it runs after this set.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+struct InputSet;
+
 fn setup(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
+    mut atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    asset_server: Res<AssetServer>,
+    tuning: Res<Tuning>,
 ) {
     commands.spawn(Camera2d);
 
-    commands.spawn((
-        Sprite::from_image(asset_server.load("duck.png")),
-        Transform::from_xyz(0., 0., 0.).with_scale(Vec3::splat(0.3)),
-        IsPlayer,
-        Velocity::default(),
-    ));
+    commands.insert_resource(EnemyAtlas {
+        image: asset_server.load("enemies.png"),
+        layout: atlas_layouts.add(TextureAtlasLayout::from_grid(
+            ENEMY_ATLAS_CELL_SIZE,
+            ENEMY_ATLAS_COLUMNS,
+            1,
+            None,
+            None,
+        )),
+    });
 
     let bullet_mesh = meshes.add(Rectangle::new(17.0, 6.0));
-    let bullet_material = materials.add(ColorMaterial::from(Color::srgb(0.1, 0.1, 0.1)));
+    let bullet_material = materials.add(ColorMaterial::from(BULLET_COLOR));
     commands.insert_resource(BulletAssets {
         mesh: bullet_mesh,
         material: bullet_material,
     });
 
+    commands.insert_resource(MineAssets {
+        mesh: meshes.add(Circle::new(tuning.mine_radius)),
+        material: materials.add(ColorMaterial::from(MINE_COLOR)),
+    });
+
+    // On screen rather than in the world, so zooming the camera can't hide it.
     commands.spawn((
-        Text2d::new("Score: 0"),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(12.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        Text::new("Score: 0"),
         TextFont {
             font_size: 30.0,
             ..default()
         },
-        Transform::from_xyz(0.0, WINDOW_HEIGHT / 2.0 - 30.0, 0.0),
+        TextLayout::new_with_justify(JustifyText::Center),
+        ScoreText,
     ));
 }
 
+#[allow(clippy::too_many_arguments)]
+fn start_run(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mode: Res<RunMode>,
+    seed_override: Res<SeedOverride>,
+    starting_difficulty: Res<StartingDifficulty>,
+    mut run_seed: ResMut<RunSeed>,
+    mut rng: ResMut<GameRng>,
+    mut score: ResMut<Score>,
+    mut score_decay: ResMut<ScoreDecay>,
+    mut difficulty: ResMut<Difficulty>,
+    mut heat: ResMut<Heat>,
+    mut overheated: ResMut<Overheated>,
+    mut buffered_shot: ResMut<BufferedShot>,
+    run_difficulty: Res<RunDifficulty>,
+    tuning: Res<Tuning>,
+) {
+    // Daily challenges always start from the same seed and difficulty so every
+    // player on a given day gets an identical run.
+    let (seed, starting_level) = match *mode {
+        RunMode::Standard
+        | RunMode::TimeAttack
+        | RunMode::Levels
+        | RunMode::Tutorial
+        | RunMode::Practice => (
+            seed_override.0.unwrap_or_else(|| fastrand::u64(..)),
+            starting_difficulty.0,
+        ),
+        RunMode::Daily { date, .. } => (
+            date.daily_seed(),
+            DifficultyPreset::Normal.starting_difficulty(),
+        ),
+    };
+    run_seed.0 = seed;
+    rng.0.seed(seed);
+    score.0 = 0;
+    score_decay.grace.reset();
+    score_decay.pending = 0.0;
+    difficulty.0 = starting_level;
+    heat.0 = 0.0;
+    overheated.0 = false;
+    buffered_shot.0 = false;
+
+    commands.spawn((
+        Sprite::from_image(asset_server.load("duck.png")),
+        Transform::from_translation(PLAYER_START.extend(0.0)).with_scale(Vec3::splat(0.3)),
+        IsPlayer,
+        PlayerHitbox(tuning.player_hitbox_radius),
+        Health(run_difficulty.0.player_health),
+        MaxHealth(run_difficulty.0.player_health),
+        Velocity::default(),
+    ));
+}
+
+#[allow(clippy::type_complexity)]
+fn despawn_run(
+    mut commands: Commands,
+    mut score: ResMut<Score>,
+    mut multi_shot: ResMut<MultiShot>,
+    mut spread: ResMut<SpreadConfig>,
+    query: Query<
+        Entity,
+        Or<(
+            With<IsPlayer>,
+            With<IsEnemy>,
+            With<IsBullet>,
+            With<EnemyBullet>,
+            With<Mine>,
+        )>,
+    >,
+) {
+    score.0 = 0;
+    *multi_shot = MultiShot::default();
+    *spread = SpreadConfig::default();
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn read_player_input(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    mut input: ResMut<PlayerInput>,
+    auto_fire: Res<AutoFire>,
+    aim_mode: Res<AimMode>,
+    mouse_fire: Res<MouseFire>,
+    power_up: Res<ActivePowerUp>,
+    mut cooldown: ResMut<FireCooldown>,
+    time: Res<GameTime>,
+) {
+    // The mouse only fires while it's doing the aiming.
+    let fire_button = (*aim_mode == AimMode::Mouse).then(|| MouseButton::from(mouse_fire.button));
+    let mouse_held = fire_button.is_some_and(|button| mouse_input.pressed(button));
+    let pressed = keyboard_input.just_pressed(KeyCode::Space)
+        || fire_button.is_some_and(|button| mouse_input.just_pressed(button));
+    // Rapid fire lets any fire button be held, and speeds up the interval.
+    let rapid_fire = power_up.is(WeaponPowerUp::RapidFire);
+    let held = (mouse_fire.hold && mouse_held)
+        || (rapid_fire && (mouse_held || keyboard_input.pressed(KeyCode::Space)));
+    let delta = if rapid_fire {
+        time.delta() * RAPID_FIRE_RATE
+    } else {
+        time.delta()
+    };
+    // Auto-fire is decided here rather than in `spawn_bullets` so replays
+    // record every shot it takes. Turning it off falls straight back to
+    // per-press firing, and turning it on again starts a fresh interval.
+    // Holding the mouse button fires at the same rate, starting on the click,
+    // and auto-fire already covers it when on.
+    let fire = if auto_fire.0 {
+        cooldown.0.tick(delta).just_finished()
+    } else if pressed {
+        cooldown.0.reset();
+        true
+    } else if held {
+        cooldown.0.tick(delta).just_finished()
+    } else {
+        cooldown.0.reset();
+        false
+    };
+    *input = PlayerInput {
+        left: keyboard_input.pressed(KeyCode::KeyA),
+        right: keyboard_input.pressed(KeyCode::KeyD),
+        up: keyboard_input.pressed(KeyCode::KeyW),
+        down: keyboard_input.pressed(KeyCode::KeyS),
+        fire,
+        focus: keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]),
+        // Aim carries over until `read_aim` has a new direction, and it
+        // decides on assist.
+        aim: input.aim,
+        assisted: input.assisted,
+    };
+}
+
 fn handle_input(
     mut query: Query<&mut Velocity, With<IsPlayer>>,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    input: Res<PlayerInput>,
+    tuning: Res<Tuning>,
 ) {
     if let Ok(mut vel) = query.single_mut() {
-        if keyboard_input.pressed(KeyCode::KeyA) {
-            vel.0.x -= PLAYER_SPEED;
-        }
-        if keyboard_input.pressed(KeyCode::KeyD) {
-            vel.0.x += PLAYER_SPEED;
-        }
-        if keyboard_input.pressed(KeyCode::KeyW) {
-            vel.0.y += PLAYER_SPEED;
-        }
-        if keyboard_input.pressed(KeyCode::KeyS) {
-            vel.0.y -= PLAYER_SPEED;
-        }
+        vel.0 += input.movement() * input.speed(tuning.player_speed);
     }
 }
 
-fn update(mut query: Query<(&mut Transform, &mut Velocity, Option<&IsPlayer>)>, time: Res<Time>) {
+fn update(
+    mut query: Query<(&mut Transform, &mut Velocity, Option<&IsPlayer>)>,
+    time: Res<GameTime>,
+    profiler: Res<Profiler>,
+) {
+    let _timing = profiler.measure(ProfiledSystem::Update);
     for (mut transform, mut vel, is_player) in query.iter_mut() {
         transform.translation.x += vel.0.x * time.delta_secs();
         transform.translation.y += vel.0.y * time.delta_secs();
 
         if is_player.is_some() {
-            vel.0 *= 0.8; // Slow down the player over time
+            vel.0 *= PLAYER_FRICTION; // Slow down the player over time
+        }
+    }
+}
+
+fn expire_bullets(
+    mut commands: Commands,
+    mut query: Query<(Entity, &Velocity, &mut BulletLifetime)>,
+    time: Res<GameTime>,
+) {
+    for (entity, velocity, mut lifetime) in query.iter_mut() {
+        let left = match &mut *lifetime {
+            BulletLifetime::Time(left) => {
+                *left -= time.delta_secs();
+                *left
+            }
+            BulletLifetime::Distance(left) => {
+                *left -= velocity.0.length() * time.delta_secs();
+                *left
+            }
+        };
+        if left <= 0.0 {
+            commands.entity(entity).despawn();
         }
     }
 }
 
-fn spawn_enemies(mut commands: Commands, asset_server: Res<AssetServer>) {
-    if fastrand::u8(0..3) == 0 {
-        commands.spawn((
-            Sprite::from_image(asset_server.load("fish.png")),
-            Transform::from_xyz(
-                (WINDOW_WIDTH * 0.9 + fastrand::f32() * (WINDOW_WIDTH - WINDOW_WIDTH * 0.9)) / 2.0,
-                -WINDOW_HEIGHT / 2.0 + fastrand::f32() * WINDOW_HEIGHT,
-                0.0,
+fn seek_player_y(
+    mut query: Query<(&Transform, &mut Velocity, &SeekPlayerY)>,
+    player_query: Query<&Transform, (With<IsPlayer>, Without<SeekPlayerY>)>,
+    time: Res<GameTime>,
+) {
+    let Ok(player_transform) = player_query.single() else {
+        return;
+    };
+    let step = SEEK_ACCELERATION * time.delta_secs();
+    for (transform, mut vel, seek) in query.iter_mut() {
+        let wanted = ((player_transform.translation.y - transform.translation.y) * seek.strength)
+            .clamp(-MAX_SEEK_SPEED, MAX_SEEK_SPEED);
+        vel.0.y += (wanted - vel.0.y).clamp(-step, step);
+    }
+}
+
+fn tint_seekers(mut query: Query<&mut Sprite, Added<SeekPlayerY>>) {
+    for mut sprite in query.iter_mut() {
+        sprite.color = SEEKER_TINT;
+    }
+}
+
+/// Outlines the player's hitbox while focusing.
+fn draw_focus_hitbox(
+    mut gizmos: Gizmos,
+    input: Res<PlayerInput>,
+    query: Query<(&Transform, &PlayerHitbox)>,
+) {
+    if !input.focus {
+        return;
+    }
+    for (transform, hitbox) in query.iter() {
+        gizmos.circle_2d(transform.translation.truncate(), hitbox.0, Color::WHITE);
+    }
+}
+
+fn ramp_difficulty(mut difficulty: ResMut<Difficulty>, time: Res<GameTime>) {
+    difficulty.0 += DIFFICULTY_RAMP_RATE * time.delta_secs();
+}
+
+/// How far the player's center may stray from the origin on each axis.
+fn player_bounds(play_area: &PlayArea) -> Vec2 {
+    Vec2::new(play_area.half_width, play_area.half_height) - PLAYER_HALF_SIZE
+}
+
+/// Share of the usual spawn chance, or of the wave schedule's pace, in effect
+/// `elapsed` seconds into a run.
+fn spawn_warmup(elapsed: f32) -> f32 {
+    let progress = (elapsed / SPAWN_WARMUP).clamp(0.0, 1.0);
+    progress * progress * (3.0 - 2.0 * progress)
+}
+
+/// Whether `position` is too close to the `player` to spawn at, `elapsed`
+/// seconds into a run.
+fn crowds_safe_start(position: Vec2, player: Vec2, elapsed: f32) -> bool {
+    elapsed < SAFE_START_DURATION && position.distance(player) < SAFE_START_RADIUS
+}
+
+/// Where the player is, for keeping spawns clear of them.
+fn player_position(player_query: &Query<&Transform, With<IsPlayer>>) -> Vec2 {
+    player_query
+        .single()
+        .map_or(PLAYER_START, |transform| transform.translation.truncate())
+}
+
+/// Rolls a spawn position, re-rolling any too close to the player while the
+/// run is young. `None` if every roll was.
+fn spawn_position(
+    rng: &mut fastrand::Rng,
+    player: Vec2,
+    elapsed: f32,
+    mut roll: impl FnMut(&mut fastrand::Rng) -> Vec2,
+) -> Option<Vec2> {
+    if elapsed >= SAFE_START_DURATION {
+        return Some(roll(rng));
+    }
+    (0..SAFE_START_ATTEMPTS)
+        .map(|_| roll(rng))
+        .find(|&position| !crowds_safe_start(position, player, elapsed))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_enemies(
+    mut commands: Commands,
+    enemy_atlas: Res<EnemyAtlas>,
+    difficulty: Res<Difficulty>,
+    max_enemies: Res<MaxEnemies>,
+    enemy_query: Query<(), With<IsEnemy>>,
+    mut rng: ResMut<GameRng>,
+    mut level: Option<ResMut<LevelProgress>>,
+    profiler: Res<Profiler>,
+    play_area: Res<PlayArea>,
+    run_difficulty: Res<RunDifficulty>,
+    tuning: Res<Tuning>,
+    survival_time: Res<SurvivalTime>,
+    player_query: Query<&Transform, With<IsPlayer>>,
+) {
+    let _timing = profiler.measure(ProfiledSystem::SpawnEnemies);
+    if enemy_query.iter().count() >= max_enemies.at(difficulty.0)
+        || level.as_ref().is_some_and(|level| level.remaining == 0)
+    {
+        return;
+    }
+    let custom = &run_difficulty.0;
+    let chance = custom.spawn_chance(difficulty.0, tuning.full_spawn_difficulty)
+        * spawn_warmup(survival_time.0);
+    if rng.0.f32() < chance {
+        let size = play_area.size();
+        let player = player_position(&player_query);
+        let Some(position) = spawn_position(&mut rng.0, player, survival_time.0, |rng| {
+            Vec2::new(
+                (size.x * 0.9 + rng.f32() * (size.x - size.x * 0.9)) / 2.0,
+                -size.y / 2.0 + rng.f32() * size.y,
             )
-            .with_scale(Vec3::splat(0.1)),
-            Velocity(Vec2::new(-10.0 - fastrand::f32() * 30.0, 0.0)),
-            IsEnemy,
+        }) else {
+            return;
+        };
+        let (slowest, fastest) = tuning.fish_speed;
+        let velocity =
+            Vec2::new(-slowest - rng.0.f32() * (fastest - slowest), 0.0) * custom.enemy_speed;
+        let mut entity = commands.spawn(fish(
+            &enemy_atlas,
+            &tuning,
+            position,
+            velocity,
+            FISH_HP * custom.enemy_health,
         ));
+        let kind = tuning.fish_weights.pick(difficulty.0, rng.0.f32());
+        insert_fish_kind(&mut entity, kind, &mut rng.0);
+        if let Some(level) = &mut level {
+            level.remaining -= 1;
+        }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
+fn spawn_mines(
+    mut commands: Commands,
+    mine_assets: Res<MineAssets>,
+    mut rng: ResMut<GameRng>,
+    play_area: Res<PlayArea>,
+    run_difficulty: Res<RunDifficulty>,
+    tuning: Res<Tuning>,
+    survival_time: Res<SurvivalTime>,
+    player_query: Query<&Transform, With<IsPlayer>>,
+) {
+    if rng.0.u8(0..3) == 0 {
+        let player = player_position(&player_query);
+        let Some(position) = spawn_position(&mut rng.0, player, survival_time.0, |rng| {
+            Vec2::new(
+                play_area.half_width,
+                -play_area.half_height + rng.f32() * play_area.half_height * 2.0,
+            )
+        }) else {
+            return;
+        };
+        let (slowest, fastest) = tuning.mine_speed;
+        let velocity = Vec2::new(
+            -slowest - rng.0.f32() * (fastest - slowest),
+            -10.0 + rng.0.f32() * 20.0,
+        ) * run_difficulty.0.enemy_speed;
+        let hp = MINE_HP * run_difficulty.0.enemy_health;
+        commands.spawn(mine(&mine_assets, position, velocity, hp));
+    }
+}
+
+fn fish(
+    enemy_atlas: &EnemyAtlas,
+    tuning: &Tuning,
+    position: Vec2,
+    velocity: Vec2,
+    hp: u32,
+) -> impl Bundle {
+    (
+        enemy_atlas.sprite(EnemySprite::Fish),
+        Transform::from_translation(position.extend(0.0)).with_scale(Vec3::splat(0.1)),
+        Velocity(velocity),
+        IsEnemy,
+        EnemyHealth(hp),
+        PointValue(FISH_POINTS),
+        BodyRadius(tuning.fish_radius),
+    )
+}
+
+/// Makes a fish just spawned with `fish` into a `kind`, rolling what a gunfish
+/// drops.
+fn insert_fish_kind(entity: &mut EntityCommands, kind: FishKind, rng: &mut fastrand::Rng) {
+    match kind {
+        FishKind::Plain => {}
+        FishKind::Seeker => {
+            entity.insert(SeekPlayerY {
+                strength: SEEKER_STRENGTH,
+            });
+        }
+        FishKind::Gunfish => {
+            let drop = WeaponPowerUp::ALL[rng.usize(..WeaponPowerUp::ALL.len())];
+            entity.insert(DropsPowerUp(drop));
+        }
+    }
+}
+
+/// One of the player's bullets, flying along `direction`.
+fn bullet(
+    bullet_assets: &BulletAssets,
+    tuning: &Tuning,
+    position: Vec2,
+    direction: Vec2,
+) -> impl Bundle {
+    (
+        Mesh2d(bullet_assets.mesh.clone()),
+        MeshMaterial2d(bullet_assets.material.clone()),
+        Transform::from_translation(position.extend(0.0))
+            .with_rotation(Quat::from_rotation_z(direction.to_angle())),
+        Velocity(direction * tuning.bullet_speed),
+        IsBullet,
+        Damage(BULLET_DAMAGE),
+    )
+}
+
+fn mine(mine_assets: &MineAssets, position: Vec2, velocity: Vec2, hp: u32) -> impl Bundle {
+    (
+        Mesh2d(mine_assets.mesh.clone()),
+        MeshMaterial2d(mine_assets.material.clone()),
+        Transform::from_translation(position.extend(0.0)),
+        Velocity(velocity),
+        Mine { hp },
+    )
+}
+
+/// The state of the duck's gun that firing reads and updates.
+#[derive(SystemParam)]
+struct Weapon<'w> {
+    heat: ResMut<'w, Heat>,
+    overheated: Res<'w, Overheated>,
+    buffered_shot: ResMut<'w, BufferedShot>,
+    multi_shot: Res<'w, MultiShot>,
+    spread: Res<'w, SpreadConfig>,
+    power_up: Res<'w, ActivePowerUp>,
+}
+
+#[allow(clippy::too_many_arguments)]
 fn spawn_bullets(
     mut commands: Commands,
-    keyboard_input: Res<ButtonInput<KeyCode>>,
+    input: Res<PlayerInput>,
     player_query: Query<&Transform, With<IsPlayer>>,
     bullet_assets: Res<BulletAssets>,
+    sounds: Res<SoundAssets>,
+    mut rng: ResMut<GameRng>,
+    weapon: Weapon,
+    aim_assist: Res<AimAssist>,
+    modifiers: Res<RunModifiers>,
+    enemy_query: Query<&Transform, With<IsEnemy>>,
+    tuning: Res<Tuning>,
 ) {
-    if keyboard_input.just_pressed(KeyCode::Space) {
+    let Weapon {
+        mut heat,
+        overheated,
+        mut buffered_shot,
+        multi_shot,
+        spread,
+        power_up,
+    } = weapon;
+    if input.fire && overheated.0 {
+        // Heat only falls while the gun is locked, so this is exactly how long
+        // is left.
+        let cooling_left = (heat.0 - HEAT_RESUME_THRESHOLD) / HEAT_DECAY_RATE;
+        if cooling_left <= tuning.fire_buffer {
+            buffered_shot.0 = true;
+        }
+    }
+    if (input.fire || buffered_shot.0) && !overheated.0 {
+        buffered_shot.0 = false;
         if let Ok(player_transform) = player_query.single() {
-            commands.spawn((
-                Mesh2d(bullet_assets.mesh.clone()),
-                MeshMaterial2d(bullet_assets.material.clone()),
-                Transform::from_xyz(
-                    player_transform.translation.x + 70.0,
-                    player_transform.translation.y + 14.0,
-                    0.0,
-                ),
-                Velocity(Vec2::new(500.0, 0.0)),
-            ));
+            let center = player_transform.translation.truncate() + MUZZLE_OFFSET;
+            let mut aim = input.aim;
+            if input.assisted {
+                aim = aim_assist.adjust(
+                    aim,
+                    center,
+                    enemy_query
+                        .iter()
+                        .map(|transform| transform.translation.truncate()),
+                );
+            }
+            let spread = SpreadConfig {
+                count: if power_up.is(WeaponPowerUp::Spread) {
+                    spread.count.max(POWER_UP_SPREAD_COUNT)
+                } else {
+                    spread.count
+                },
+                ..*spread
+            };
+            let piercing = power_up.is(WeaponPowerUp::Pierce);
+            // Each direction of the fan gets its own row of parallel bullets.
+            let count = multi_shot.0.clamp(1, MAX_MULTI_SHOT);
+            for direction in spread.directions(aim) {
+                for i in 0..count {
+                    let offset = (i as f32 - (count - 1) as f32 / 2.0) * MULTI_SHOT_SPACING;
+                    let position = center + direction * MUZZLE_DISTANCE + direction.perp() * offset;
+                    let mut shot =
+                        commands.spawn(bullet(&bullet_assets, &tuning, position, direction));
+                    if piercing {
+                        shot.insert(Piercing);
+                    }
+                    if let Some(lifetime) = tuning.bullet_lifetime {
+                        shot.insert(lifetime);
+                    }
+                }
+            }
+            commands.spawn(sound_effect(&sounds.shot, &mut rng));
+            let heat_per_shot = if modifiers.has(Modifier::NoCooldown) {
+                0.0
+            } else if power_up.is(WeaponPowerUp::RapidFire) {
+                HEAT_PER_SHOT / 2.0
+            } else {
+                HEAT_PER_SHOT
+            };
+            heat.0 = (heat.0 + heat_per_shot).min(MAX_HEAT);
         }
     }
 }
 
+/// Puts back a plain bullet material if the one set up at launch has gone
+/// missing or turned invisible, so shots can always be seen.
+fn ensure_bullet_material(
+    bullet_assets: Res<BulletAssets>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let visible = materials
+        .get(&bullet_assets.material)
+        .is_some_and(|material| material.color.alpha() > 0.0);
+    if !visible {
+        warn!("Bullet material is missing or transparent, falling back to a solid color");
+        materials.insert(&bullet_assets.material, ColorMaterial::from(BULLET_COLOR));
+    }
+}
+
+fn cool_weapon(mut heat: ResMut<Heat>, mut overheated: ResMut<Overheated>, time: Res<GameTime>) {
+    if heat.0 >= MAX_HEAT {
+        overheated.0 = true;
+    }
+    heat.0 = (heat.0 - HEAT_DECAY_RATE * time.delta_secs()).max(0.0);
+    if heat.0 <= HEAT_RESUME_THRESHOLD {
+        overheated.0 = false;
+    }
+}
+
+fn decay_score(
+    mut score: ResMut<Score>,
+    mut decay: ResMut<ScoreDecay>,
+    mut game_state: ResMut<NextState<GameState>>,
+    time: Res<GameTime>,
+) {
+    decay.grace.tick(time.delta());
+    decay.pending += decay.rate * time.delta_secs();
+    let drained = decay.pending.floor();
+    decay.pending -= drained;
+    score.0 = score.0.saturating_sub(drained as u32);
+
+    if score.0 == 0 && decay.grace.finished() {
+        game_state.set(GameState::GameOver);
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn check_for_collisions(
     mut commands: Commands,
     mut score: ResMut<Score>,
-    mut bullet_query: Query<(Entity, &Transform), With<Mesh2d>>,
-    enemy_query: Query<(Entity, &Transform), With<IsEnemy>>,
+    mut rng: ResMut<GameRng>,
+    crit: Res<CritConfig>,
+    combo: Res<Combo>,
+    modifiers: Res<RunModifiers>,
+    mut explosions: EventWriter<Explosion>,
+    mut popups: EventWriter<ScorePopup>,
+    mut damage_popups: EventWriter<DamagePopup>,
+    mut kills: EventWriter<EnemyKilled>,
+    mut hits: EventWriter<BulletHit>,
+    bullet_query: Query<(Entity, &Transform, &Damage, Has<Piercing>), With<IsBullet>>,
+    mut enemy_query: Query<
+        (
+            Entity,
+            &Transform,
+            &BodyRadius,
+            &mut EnemyHealth,
+            &PointValue,
+            Option<&DropsPowerUp>,
+            Has<Boss>,
+        ),
+        With<IsEnemy>,
+    >,
+    mut mine_query: Query<(&Transform, &mut Mine)>,
+    profiler: Res<Profiler>,
+    tuning: Res<Tuning>,
 ) {
-    for (bullet_entity, bullet_transform) in bullet_query.iter_mut() {
-        for (enemy_entity, enemy_transform) in enemy_query.iter() {
-            if bullet_transform
-                .translation
-                .distance(enemy_transform.translation)
-                < 30.0
+    let _timing = profiler.measure(ProfiledSystem::CheckForCollisions);
+    'bullets: for (bullet_entity, bullet_transform, damage, piercing) in bullet_query.iter() {
+        for (enemy_entity, enemy_transform, radius, mut health, points, drops, is_boss) in
+            enemy_query.iter_mut()
+        {
+            // Skip anything already killed this frame but not yet despawned.
+            if health.0 > 0
+                && bullet_transform
+                    .translation
+                    .distance(enemy_transform.translation)
+                    < radius.0 + tuning.bullet_radius
+            {
+                let is_crit = rng.0.f32() < crit.chance;
+                let damage = if is_crit {
+                    damage.0 * crit.multiplier
+                } else {
+                    damage.0
+                };
+                hits.write(BulletHit {
+                    position: bullet_transform.translation.truncate(),
+                    damage,
+                    crit: is_crit,
+                });
+                health.0 = health.0.saturating_sub(damage);
+                // A piercing bullet carries on through what it kills, and
+                // reaches the next enemy by the following frame.
+                if health.0 > 0 || !piercing {
+                    commands.entity(bullet_entity).despawn();
+                }
+                if health.0 > 0 {
+                    damage_popups.write(DamagePopup {
+                        position: bullet_transform.translation.truncate(),
+                        damage,
+                    });
+                } else {
+                    let position = enemy_transform.translation.truncate();
+                    let points = if is_crit {
+                        points.0 + crit.bonus_points
+                    } else {
+                        points.0
+                    } * combo.multiplier
+                        * modifiers.score_multiplier();
+                    commands.entity(enemy_entity).despawn();
+                    score.0 += points;
+                    explosions.write(Explosion {
+                        position,
+                        radius: 0.0,
+                    });
+                    popups.write(ScorePopup {
+                        position,
+                        points,
+                        crit: is_crit,
+                    });
+                    kills.write(EnemyKilled {
+                        position,
+                        radius: radius.0,
+                        drop: drops.map(|drops| drops.0),
+                        boss: is_boss,
+                    });
+                }
+                continue 'bullets;
+            }
+        }
+        for (mine_transform, mut mine) in mine_query.iter_mut() {
+            if mine.hp > 0
+                && bullet_transform
+                    .translation
+                    .distance(mine_transform.translation)
+                    < tuning.mine_radius + tuning.bullet_radius
             {
                 commands.entity(bullet_entity).despawn();
-                commands.entity(enemy_entity).despawn();
-                score.0 += 1;
+                hits.write(BulletHit {
+                    position: bullet_transform.translation.truncate(),
+                    damage: damage.0,
+                    crit: false,
+                });
+                mine.hp = mine.hp.saturating_sub(damage.0);
+                continue 'bullets;
             }
         }
     }
 }
 
+/// Whether touching a fish `offset` from the player's center counts as a close
+/// call: the player is darting at close to full speed, only just touching it
+/// within `reach`, and moving away from or past it rather than into it.
+fn is_close_call(offset: Vec2, velocity: Vec2, reach: f32, close_call_speed: f32) -> bool {
+    velocity.length() >= close_call_speed
+        && offset.length() > reach - CLOSE_CALL_DEPTH
+        && velocity.dot(offset) <= 0.0
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
 fn check_for_player_collisions(
-    player_query: Query<&Transform, With<IsPlayer>>,
-    enemy_query: Query<&Transform, With<IsEnemy>>,
+    mut commands: Commands,
+    mut score: ResMut<Score>,
+    mut explosions: EventWriter<Explosion>,
+    mut popups: EventWriter<ScorePopup>,
+    mut player_query: Query<
+        (
+            Entity,
+            &Transform,
+            &PlayerHitbox,
+            &mut Health,
+            &mut Velocity,
+        ),
+        (With<IsPlayer>, Without<Invincible>),
+    >,
+    // A boss is harmless until its intro is over.
+    enemy_query: Query<
+        (Entity, &Transform, &BodyRadius, Has<Boss>),
+        (With<IsEnemy>, Without<BossIntro>),
+    >,
+    mine_query: Query<(Entity, &Transform), With<Mine>>,
+    enemy_bullet_query: Query<(Entity, &Transform), With<EnemyBullet>>,
+    mut player_hits: EventWriter<PlayerHit>,
     mut game_state: ResMut<NextState<GameState>>,
+    knockback: Res<Knockback>,
+    tuning: Res<Tuning>,
 ) {
-    if let Ok(player_transform) = player_query.single() {
-        for enemy_transform in enemy_query.iter() {
+    if let Ok((player_entity, player_transform, hitbox, mut health, mut vel)) =
+        player_query.single_mut()
+    {
+        let mut damage = 0;
+        let mut push = Vec2::ZERO;
+        for (enemy_entity, enemy_transform, radius, is_boss) in enemy_query.iter() {
+            let distance = player_transform
+                .translation
+                .distance(enemy_transform.translation);
+            if distance < hitbox.0 + radius.0 {
+                // Clipping the edge of a fish while darting past grazes it
+                // instead. Bosses, mines and bullets always hurt.
+                let offset =
+                    (enemy_transform.translation - player_transform.translation).truncate();
+                if !is_boss
+                    && is_close_call(offset, vel.0, hitbox.0 + radius.0, tuning.close_call_speed)
+                {
+                    commands.entity(enemy_entity).despawn();
+                    let position = enemy_transform.translation.truncate();
+                    score.0 += GRAZE_POINTS;
+                    explosions.write(Explosion {
+                        position,
+                        radius: 0.0,
+                    });
+                    popups.write(ScorePopup {
+                        position,
+                        points: GRAZE_POINTS,
+                        crit: false,
+                    });
+                    continue;
+                }
+                // Bosses shrug off ramming the player; anything else is spent.
+                if !is_boss {
+                    commands.entity(enemy_entity).despawn();
+                }
+                damage += ENEMY_CONTACT_DAMAGE;
+                push += (player_transform.translation - enemy_transform.translation)
+                    .truncate()
+                    .normalize_or_zero();
+            }
+        }
+        for (mine_entity, mine_transform) in mine_query.iter() {
             if player_transform
                 .translation
-                .distance(enemy_transform.translation)
-                < 50.0
+                .distance(mine_transform.translation)
+                < hitbox.0 + tuning.mine_radius
+            {
+                commands.entity(mine_entity).despawn();
+                explosions.write(Explosion {
+                    position: mine_transform.translation.truncate(),
+                    radius: MINE_EXPLOSION_RADIUS,
+                });
+                damage += MINE_CONTACT_DAMAGE;
+                push += (player_transform.translation - mine_transform.translation)
+                    .truncate()
+                    .normalize_or_zero();
+            }
+        }
+        for (bullet_entity, bullet_transform) in enemy_bullet_query.iter() {
+            if player_transform
+                .translation
+                .distance(bullet_transform.translation)
+                < hitbox.0 + ENEMY_BULLET_RADIUS
+            {
+                commands.entity(bullet_entity).despawn();
+                damage += ENEMY_BULLET_DAMAGE;
+                push += (player_transform.translation - bullet_transform.translation)
+                    .truncate()
+                    .normalize_or_zero();
+            }
+        }
+
+        if damage == 0 {
+            return;
+        }
+        player_hits.write(PlayerHit);
+        health.0 = health.0.saturating_sub(damage);
+        if health.0 == 0 {
+            game_state.set(GameState::GameOver);
+        } else {
+            vel.0 += push.normalize_or_zero() * knockback.0;
+            commands
+                .entity(player_entity)
+                .insert(Invincible(Timer::from_seconds(
+                    INVINCIBILITY_DURATION,
+                    TimerMode::Once,
+                )));
+        }
+    }
+}
+
+fn tick_invincibility(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Invincible, &mut Visibility)>,
+    time: Res<GameTime>,
+) {
+    for (entity, mut invincible, mut visibility) in query.iter_mut() {
+        invincible.0.tick(time.delta());
+        if invincible.0.finished() {
+            commands.entity(entity).remove::<Invincible>();
+            *visibility = Visibility::Inherited;
+        } else if ((invincible.0.elapsed_secs() / INVINCIBILITY_BLINK_INTERVAL) as u32)
+            .is_multiple_of(2)
+        {
+            *visibility = Visibility::Hidden;
+        } else {
+            *visibility = Visibility::Inherited;
+        }
+    }
+}
+
+fn detonate_mines(
+    mut commands: Commands,
+    mut explosions: EventWriter<Explosion>,
+    query: Query<(Entity, &Transform, &Mine)>,
+) {
+    for (entity, transform, mine) in query.iter() {
+        if mine.hp == 0 {
+            commands.entity(entity).despawn();
+            explosions.write(Explosion {
+                position: transform.translation.truncate(),
+                radius: MINE_EXPLOSION_RADIUS,
+            });
+        }
+    }
+}
+
+/// Destroys enemies caught in a blast and sets off any mines in range, letting
+/// explosions chain.
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn apply_explosions(
+    mut commands: Commands,
+    mut score: ResMut<Score>,
+    combo: Res<Combo>,
+    modifiers: Res<RunModifiers>,
+    mut explosions: EventReader<Explosion>,
+    mut popups: EventWriter<ScorePopup>,
+    mut kills: EventWriter<EnemyKilled>,
+    mut enemy_query: Query<
+        (
+            Entity,
+            &Transform,
+            &BodyRadius,
+            &mut EnemyHealth,
+            &PointValue,
+            Option<&DropsPowerUp>,
+            Has<Boss>,
+        ),
+        With<IsEnemy>,
+    >,
+    mut mine_query: Query<(&Transform, &mut Mine)>,
+) {
+    for explosion in explosions.read() {
+        if explosion.radius <= 0.0 {
+            continue;
+        }
+        for (enemy_entity, enemy_transform, radius, mut health, points, drops, is_boss) in
+            enemy_query.iter_mut()
+        {
+            if health.0 > 0
+                && enemy_transform
+                    .translation
+                    .truncate()
+                    .distance(explosion.position)
+                    < explosion.radius
+            {
+                health.0 = 0;
+                commands.entity(enemy_entity).despawn();
+                let points = points.0 * combo.multiplier * modifiers.score_multiplier();
+                score.0 += points;
+                let position = enemy_transform.translation.truncate();
+                popups.write(ScorePopup {
+                    position,
+                    points,
+                    crit: false,
+                });
+                kills.write(EnemyKilled {
+                    position,
+                    radius: radius.0,
+                    drop: drops.map(|drops| drops.0),
+                    boss: is_boss,
+                });
+            }
+        }
+        for (mine_transform, mut mine) in mine_query.iter_mut() {
+            if mine_transform
+                .translation
+                .truncate()
+                .distance(explosion.position)
+                < explosion.radius
             {
-                game_state.set(GameState::GameOver);
+                mine.hp = 0;
             }
         }
     }
@@ -212,6 +1864,10 @@ fn darken_screen(mut color: ResMut<ClearColor>) {
     color.0 = Color::srgb(0.1, 0.1, 0.1);
 }
 
+fn restore_screen(mut color: ResMut<ClearColor>) {
+    color.0 = BACKGROUND_COLOR;
+}
+
 fn display_game_over_text(mut commands: Commands) {
     commands.spawn((
         Text2d::new("Game Over!"),
@@ -220,11 +1876,439 @@ fn display_game_over_text(mut commands: Commands) {
             ..default()
         },
         Transform::from_xyz(0.0, 0.0, 0.0),
+        GameOverText,
     ));
 }
 
-fn update_score_text(score: Res<Score>, mut query: Query<&mut Text2d>) {
+fn return_to_menu(keyboard_input: Res<ButtonInput<KeyCode>>, mut fades: EventWriter<FadeTo>) {
+    if keyboard_input.just_pressed(KeyCode::Enter) {
+        fades.write(FadeTo(GameState::Menu));
+    }
+}
+
+fn despawn_game_over_text(mut commands: Commands, query: Query<Entity, With<GameOverText>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn update_score_text(
+    score: Res<Score>,
+    mut displayed: ResMut<DisplayedScore>,
+    mut query: Query<&mut Text, With<ScoreText>>,
+    time: Res<Time>,
+) {
+    displayed.approach(score.0, time.delta_secs());
     if let Ok(mut text) = query.single_mut() {
-        text.0 = format!("Score: {}", score.0);
+        text.0 = format!("Score: {}", format_thousands(displayed.0 as u32));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{ecs::system::RunSystemOnce, state::app::StatesPlugin, time::TimeUpdateStrategy};
+
+    use super::*;
+    use crate::aim::FireButton;
+
+    /// A bullet at the origin, about to hit whatever is put there.
+    fn collision_world(seed: u64, crit_chance: f32) -> World {
+        let mut world = World::new();
+        world.insert_resource(Score(0));
+        world.insert_resource(GameRng(fastrand::Rng::with_seed(seed)));
+        world.insert_resource(CritConfig {
+            chance: crit_chance,
+            multiplier: CRIT_MULTIPLIER,
+            bonus_points: CRIT_BONUS_POINTS,
+        });
+        world.init_resource::<Combo>();
+        world.init_resource::<RunModifiers>();
+        world.init_resource::<Profiler>();
+        world.init_resource::<Tuning>();
+        world.init_resource::<Events<Explosion>>();
+        world.init_resource::<Events<ScorePopup>>();
+        world.init_resource::<Events<DamagePopup>>();
+        world.init_resource::<Events<EnemyKilled>>();
+        world.init_resource::<Events<BulletHit>>();
+        world.spawn((Transform::default(), Damage(1), IsBullet));
+        world
+    }
+
+    /// The duck at the origin with a cool gun, holding fire straight ahead.
+    fn firing_app() -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, GameTimePlugin))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                100,
+            )))
+            .insert_state(GameState::Playing)
+            .insert_resource(PlayerInput {
+                fire: true,
+                ..default()
+            })
+            .insert_resource(BulletAssets {
+                mesh: Handle::default(),
+                material: Handle::default(),
+            })
+            .insert_resource(SoundAssets {
+                shot: Handle::default(),
+                pop: Handle::default(),
+                crit: Handle::default(),
+                streak: Handle::default(),
+                boss_sting: Handle::default(),
+            })
+            .insert_resource(GameRng(fastrand::Rng::with_seed(1)))
+            .init_resource::<Heat>()
+            .init_resource::<Overheated>()
+            .init_resource::<BufferedShot>()
+            .init_resource::<MultiShot>()
+            .init_resource::<SpreadConfig>()
+            .init_resource::<ActivePowerUp>()
+            .init_resource::<AimMode>()
+            .init_resource::<AimAssist>()
+            .init_resource::<RunModifiers>()
+            .init_resource::<Tuning>()
+            .add_systems(Update, (spawn_bullets, cool_weapon).chain());
+        app.world_mut().spawn((Transform::default(), IsPlayer));
+        app
+    }
+
+    /// Where each of the duck's bullets is, despawning them all.
+    fn take_bullets(app: &mut App) -> Vec<Vec2> {
+        let world = app.world_mut();
+        let mut query = world.query_filtered::<(Entity, &Transform), With<IsBullet>>();
+        let bullets: Vec<_> = query
+            .iter(world)
+            .map(|(entity, transform)| (entity, transform.translation.truncate()))
+            .collect();
+        for (entity, _) in &bullets {
+            world.despawn(*entity);
+        }
+        bullets.into_iter().map(|(_, position)| position).collect()
+    }
+
+    #[test]
+    fn multi_shot_fires_a_row_of_parallel_bullets() {
+        let mut app = firing_app();
+        app.insert_resource(MultiShot(3));
+        app.update();
+
+        let mut ys: Vec<f32> = take_bullets(&mut app)
+            .iter()
+            .map(|position| {
+                assert_eq!(position.x, MUZZLE_DISTANCE);
+                position.y - MUZZLE_OFFSET.y
+            })
+            .collect();
+        ys.sort_by(f32::total_cmp);
+        assert_eq!(ys, [-MULTI_SHOT_SPACING, 0.0, MULTI_SHOT_SPACING]);
+    }
+
+    /// Holds fire until the gun overheats, returning the shots it took.
+    fn fire_until_overheated(app: &mut App) -> usize {
+        let mut shots = 0;
+        while !app.world().resource::<Overheated>().0 {
+            app.update();
+            shots += take_bullets(app).len();
+            assert!(shots < 20, "the gun never overheated");
+        }
+        shots
+    }
+
+    #[test]
+    fn an_overheated_gun_waits_to_cool_before_firing_again() {
+        let mut app = firing_app();
+        assert!(fire_until_overheated(&mut app) >= (MAX_HEAT / HEAT_PER_SHOT) as usize);
+
+        // Still holding fire, nothing comes out until it has cooled.
+        let mut locked = 0.0;
+        while app.world().resource::<Overheated>().0 {
+            app.update();
+            assert!(take_bullets(&mut app).is_empty());
+            locked += 0.1;
+        }
+        let cooling = (MAX_HEAT - HEAT_RESUME_THRESHOLD) / HEAT_DECAY_RATE;
+        assert!(locked >= cooling - 0.2, "locked for {locked}s");
+        app.update();
+        assert_eq!(take_bullets(&mut app).len(), 1);
+    }
+
+    /// Lets an overheated gun cool to just short of firing again, with fire
+    /// tapped on the last frame if `tap`, and returns the shots fired on the
+    /// frame after it's free.
+    fn shots_once_cooled(tap: bool) -> usize {
+        let mut app = firing_app();
+        fire_until_overheated(&mut app);
+        app.world_mut().resource_mut::<PlayerInput>().fire = false;
+        let window = Tuning::default().fire_buffer * HEAT_DECAY_RATE;
+        while app.world().resource::<Heat>().0 - HEAT_RESUME_THRESHOLD > window {
+            app.update();
+        }
+        app.world_mut().resource_mut::<PlayerInput>().fire = tap;
+        app.update();
+        app.world_mut().resource_mut::<PlayerInput>().fire = false;
+        assert!(take_bullets(&mut app).is_empty());
+        assert!(!app.world().resource::<Overheated>().0);
+        app.update();
+        take_bullets(&mut app).len()
+    }
+
+    #[test]
+    fn a_press_just_before_the_gun_is_free_fires_when_it_is() {
+        assert_eq!(shots_once_cooled(true), 1);
+        assert_eq!(shots_once_cooled(false), 0);
+    }
+
+    #[test]
+    fn a_short_range_bullet_falls_away_on_screen() {
+        let range = 150.0;
+        let mut app = firing_app();
+        app.world_mut().resource_mut::<Tuning>().bullet_lifetime =
+            Some(BulletLifetime::Distance(range));
+        app.init_resource::<Profiler>()
+            .add_systems(Update, (update, expire_bullets).chain().after(cool_weapon));
+        app.update();
+        app.world_mut().resource_mut::<PlayerInput>().fire = false;
+
+        let mut query = app
+            .world_mut()
+            .query_filtered::<&Transform, With<IsBullet>>();
+        let mut furthest = 0.0;
+        for _ in 0..10 {
+            let Some(transform) = query.iter(app.world()).next() else {
+                break;
+            };
+            furthest = transform.translation.x - MUZZLE_DISTANCE;
+            app.update();
+        }
+        assert_eq!(query.iter(app.world()).count(), 0);
+        // Last seen within a frame's flight of its range, well on screen.
+        let step = Tuning::default().bullet_speed * 0.1;
+        assert!(
+            furthest >= range - step && furthest < range,
+            "went {furthest}"
+        );
+    }
+
+    /// Whether clicking `button` fires, with fire set to the right button.
+    fn right_button_fires(button: MouseButton) -> bool {
+        let mut world = World::new();
+        let mut mouse_input = ButtonInput::<MouseButton>::default();
+        mouse_input.press(button);
+        world.insert_resource(mouse_input);
+        world.init_resource::<ButtonInput<KeyCode>>();
+        world.init_resource::<PlayerInput>();
+        world.insert_resource(AutoFire(false));
+        world.insert_resource(AimMode::Mouse);
+        world.insert_resource(MouseFire {
+            button: FireButton::Right,
+            hold: false,
+        });
+        world.init_resource::<ActivePowerUp>();
+        world.insert_resource(FireCooldown(Timer::from_seconds(0.2, TimerMode::Repeating)));
+        world.init_resource::<GameTime>();
+        world.run_system_once(read_player_input).unwrap();
+        world.resource::<PlayerInput>().fire
+    }
+
+    #[test]
+    fn fire_follows_the_chosen_mouse_button() {
+        assert!(right_button_fires(MouseButton::Right));
+        assert!(!right_button_fires(MouseButton::Left));
+    }
+
+    /// Blasts written so far, as where they went off and how big they were.
+    fn blasts(world: &World) -> Vec<(Vec2, f32)> {
+        world
+            .resource::<Events<Explosion>>()
+            .iter_current_update_events()
+            .map(|explosion| (explosion.position, explosion.radius))
+            .collect()
+    }
+
+    #[test]
+    fn shot_down_mines_blow_up() {
+        let mut world = collision_world(0, 0.0);
+        let mine = world.spawn((Transform::default(), Mine { hp: 1 })).id();
+        world.run_system_once(check_for_collisions).unwrap();
+        world.run_system_once(detonate_mines).unwrap();
+
+        assert!(world.get_entity(mine).is_err());
+        assert_eq!(blasts(&world), [(Vec2::ZERO, MINE_EXPLOSION_RADIUS)]);
+    }
+
+    #[test]
+    fn touching_a_mine_hurts_the_duck() {
+        let mut world = World::new();
+        world.insert_resource(Score(0));
+        world.insert_resource(Knockback(100.0));
+        world.init_resource::<Tuning>();
+        world.init_resource::<NextState<GameState>>();
+        world.init_resource::<Events<Explosion>>();
+        world.init_resource::<Events<ScorePopup>>();
+        world.init_resource::<Events<PlayerHit>>();
+        let player = world
+            .spawn((
+                Transform::default(),
+                PlayerHitbox(PLAYER_HITBOX_RADIUS),
+                Health(3),
+                Velocity::default(),
+                IsPlayer,
+            ))
+            .id();
+        let mine = world
+            .spawn((Transform::from_xyz(10.0, 0.0, 0.0), Mine { hp: MINE_HP }))
+            .id();
+        world.run_system_once(check_for_player_collisions).unwrap();
+
+        assert_eq!(
+            world.get::<Health>(player).unwrap().0,
+            3 - MINE_CONTACT_DAMAGE
+        );
+        assert!(world.get::<Invincible>(player).is_some());
+        assert!(world.get_entity(mine).is_err());
+        assert_eq!(
+            blasts(&world),
+            [(Vec2::new(10.0, 0.0), MINE_EXPLOSION_RADIUS)]
+        );
+    }
+
+    #[test]
+    fn the_seeded_rng_decides_which_hits_crit() {
+        let mut outcomes = Vec::new();
+        for seed in 0..8 {
+            let crits = fastrand::Rng::with_seed(seed).f32() < 0.5;
+            let mut world = collision_world(seed, 0.5);
+            world.spawn((
+                Transform::default(),
+                BodyRadius(FISH_RADIUS),
+                EnemyHealth(1),
+                PointValue(FISH_POINTS),
+                IsEnemy,
+            ));
+            world.run_system_once(check_for_collisions).unwrap();
+
+            let expected = if crits {
+                FISH_POINTS + CRIT_BONUS_POINTS
+            } else {
+                FISH_POINTS
+            };
+            assert_eq!(world.resource::<Score>().0, expected);
+            let hits = world.resource::<Events<BulletHit>>();
+            let hit = hits.iter_current_update_events().next().unwrap();
+            assert_eq!(hit.crit, crits);
+            outcomes.push(crits);
+        }
+        assert!(outcomes.contains(&true) && outcomes.contains(&false));
+    }
+
+    #[test]
+    fn spawning_eases_in_over_the_warmup() {
+        assert_eq!(spawn_warmup(0.0), 0.0);
+        assert_eq!(spawn_warmup(SPAWN_WARMUP / 2.0), 0.5);
+        assert_eq!(spawn_warmup(SPAWN_WARMUP), 1.0);
+        assert_eq!(spawn_warmup(SPAWN_WARMUP * 4.0), 1.0);
+        assert!(spawn_warmup(1.0) < spawn_warmup(2.0));
+    }
+
+    #[test]
+    fn only_darting_away_from_a_fish_is_a_close_call() {
+        let reach = 40.0;
+        let speed = 200.0;
+        let edge = Vec2::new(0.0, reach - CLOSE_CALL_DEPTH / 2.0);
+        // Flying past it, and away from it.
+        assert!(is_close_call(edge, Vec2::new(speed, 0.0), reach, speed));
+        assert!(is_close_call(edge, Vec2::new(0.0, -speed), reach, speed));
+        // Flying into it, too slowly, or too deep.
+        assert!(!is_close_call(edge, Vec2::new(0.0, speed), reach, speed));
+        assert!(!is_close_call(
+            edge,
+            Vec2::new(speed / 2.0, 0.0),
+            reach,
+            speed
+        ));
+        assert!(!is_close_call(
+            Vec2::new(0.0, reach / 2.0),
+            Vec2::new(speed, 0.0),
+            reach,
+            speed
+        ));
+    }
+
+    #[test]
+    fn early_spawns_keep_clear_of_the_player() {
+        let player = Vec2::new(300.0, 100.0);
+        let near = player + Vec2::new(SAFE_START_RADIUS / 2.0, 0.0);
+        let far = player + Vec2::new(SAFE_START_RADIUS * 2.0, 0.0);
+        let mut rng = fastrand::Rng::with_seed(1);
+
+        assert_eq!(spawn_position(&mut rng, player, 0.0, |_| near), None);
+        assert_eq!(spawn_position(&mut rng, player, 0.0, |_| far), Some(far));
+        assert_eq!(
+            spawn_position(&mut rng, player, SAFE_START_DURATION, |_| near),
+            Some(near)
+        );
+
+        let mut rolls = [near, near, far].into_iter();
+        assert_eq!(
+            spawn_position(&mut rng, player, 1.0, |_| rolls.next().unwrap()),
+            Some(far)
+        );
+    }
+
+    #[test]
+    fn the_shown_score_counts_up_and_lands_on_the_score() {
+        let mut displayed = DisplayedScore::default();
+        let mut shown = Vec::new();
+        for _ in 0..120 {
+            displayed.approach(1000, 1.0 / 60.0);
+            shown.push(displayed.0);
+        }
+        assert!(shown[0] > 0.0 && shown[0] < 1000.0);
+        assert!(shown.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(*shown.last().unwrap(), 1000.0);
+        assert_eq!(format_thousands(displayed.0 as u32), "1,000");
+
+        assert_eq!(format_thousands(0), "0");
+        assert_eq!(format_thousands(999), "999");
+        assert_eq!(format_thousands(1_234_567), "1,234,567");
+    }
+
+    #[test]
+    fn a_missing_or_clear_bullet_material_is_replaced() {
+        let mut world = World::new();
+        world.init_resource::<Assets<ColorMaterial>>();
+        let material = world
+            .resource_mut::<Assets<ColorMaterial>>()
+            .add(ColorMaterial::from(Color::NONE));
+        world.insert_resource(BulletAssets {
+            mesh: Handle::default(),
+            material: material.clone(),
+        });
+        let color = |world: &World| {
+            world
+                .resource::<Assets<ColorMaterial>>()
+                .get(&material)
+                .map(|material| material.color)
+        };
+
+        world.run_system_once(ensure_bullet_material).unwrap();
+        assert_eq!(color(&world), Some(BULLET_COLOR));
+
+        world
+            .resource_mut::<Assets<ColorMaterial>>()
+            .remove(&material);
+        world.run_system_once(ensure_bullet_material).unwrap();
+        assert_eq!(color(&world), Some(BULLET_COLOR));
+
+        // A visible material is left alone.
+        let red = Color::srgb(1.0, 0.0, 0.0);
+        world
+            .resource_mut::<Assets<ColorMaterial>>()
+            .insert(&material, ColorMaterial::from(red));
+        world.run_system_once(ensure_bullet_material).unwrap();
+        assert_eq!(color(&world), Some(red));
     }
 }
@@ -0,0 +1,214 @@
+//! The main menu, shown at launch and between runs.
+
+use bevy::prelude::*;
+
+use crate::{
+    GameState, RunMode, ScoreMode,
+    bestiary::BestiaryOpen,
+    daily::Date,
+    menu_nav::{MenuAction, MenuItem, MenuNav, MenuNavSet},
+    modifiers::{Modifiers, ModifiersOpen},
+    pause::RestartRun,
+    replay::ReplayPlayback,
+    scores::Leaderboards,
+    transition::{FadeTo, in_transition},
+};
+
+const MENU_OPTIONS: [MenuOption; 9] = [
+    MenuOption::Play,
+    MenuOption::SuddenDeath,
+    MenuOption::TimeAttack,
+    MenuOption::Levels,
+    MenuOption::Daily,
+    MenuOption::Tutorial,
+    MenuOption::Practice,
+    MenuOption::Modifiers,
+    MenuOption::Bestiary,
+];
+
+pub struct MenuPlugin;
+
+impl Plugin for MenuPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::Menu),
+            spawn_menu.run_if(
+                not(resource_exists::<RestartRun>).and(not(resource_exists::<ReplayPlayback>)),
+            ),
+        )
+        .add_systems(OnExit(GameState::Menu), despawn_menu)
+        .add_systems(
+            Update,
+            (
+                menu_input.after(MenuNavSet).run_if(
+                    not(in_transition)
+                        .and(not(resource_exists::<BestiaryOpen>))
+                        .and(not(resource_exists::<ModifiersOpen>)),
+                ),
+                // The bestiary and modifiers screens take the menu's place
+                // while they're open.
+                despawn_menu
+                    .run_if(resource_added::<BestiaryOpen>.or(resource_added::<ModifiersOpen>)),
+                spawn_menu
+                    .run_if(resource_removed::<BestiaryOpen>.or(resource_removed::<ModifiersOpen>)),
+            )
+                .run_if(in_state(GameState::Menu)),
+        );
+    }
+}
+
+#[derive(Component)]
+struct MenuUi;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MenuOption {
+    Play,
+    SuddenDeath,
+    TimeAttack,
+    Levels,
+    Daily,
+    Tutorial,
+    Practice,
+    Modifiers,
+    Bestiary,
+}
+
+impl MenuOption {
+    /// Key that picks this option without moving the highlight to it.
+    fn shortcut(self) -> Option<KeyCode> {
+        match self {
+            Self::Play => None,
+            Self::SuddenDeath => Some(KeyCode::KeyS),
+            Self::TimeAttack => Some(KeyCode::KeyA),
+            Self::Levels => Some(KeyCode::KeyL),
+            Self::Daily => Some(KeyCode::KeyD),
+            Self::Tutorial => Some(KeyCode::KeyT),
+            Self::Practice => Some(KeyCode::KeyP),
+            Self::Modifiers => Some(KeyCode::KeyM),
+            Self::Bestiary => Some(KeyCode::KeyB),
+        }
+    }
+}
+
+fn spawn_menu(mut commands: Commands, leaderboards: Res<Leaderboards>, modifiers: Res<Modifiers>) {
+    let today = Date::today_utc();
+    let daily_note = if leaderboards.last_daily_attempt == Some(today) {
+        " (played, unranked)"
+    } else {
+        ""
+    };
+
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(16.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+            MenuNav::new(MENU_OPTIONS.len()),
+            MenuUi,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("The Duck Game"),
+                TextFont {
+                    font_size: 60.0,
+                    ..default()
+                },
+            ));
+            for (index, option) in MENU_OPTIONS.into_iter().enumerate() {
+                let label = match option {
+                    MenuOption::Play => "Play".to_string(),
+                    MenuOption::SuddenDeath => "S - Sudden Death".to_string(),
+                    MenuOption::TimeAttack => "A - Time Attack".to_string(),
+                    MenuOption::Levels => "L - Levels".to_string(),
+                    MenuOption::Daily => format!("D - Daily Challenge {today}{daily_note}"),
+                    MenuOption::Tutorial => "T - Tutorial (Esc to skip)".to_string(),
+                    MenuOption::Practice => "P - Practice (Esc to leave)".to_string(),
+                    MenuOption::Modifiers => match modifiers.0.len() {
+                        0 => "M - Modifiers".to_string(),
+                        on => format!("M - Modifiers ({on} on)"),
+                    },
+                    MenuOption::Bestiary => "B - Bestiary".to_string(),
+                };
+                parent.spawn((
+                    Text::new(label),
+                    TextFont {
+                        font_size: 30.0,
+                        ..default()
+                    },
+                    TextColor(Color::WHITE),
+                    MenuItem(index),
+                ));
+            }
+        });
+}
+
+fn despawn_menu(mut commands: Commands, query: Query<Entity, With<MenuUi>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn menu_input(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut actions: EventReader<MenuAction>,
+    mut mode: ResMut<RunMode>,
+    mut score_mode: ResMut<ScoreMode>,
+    mut leaderboards: ResMut<Leaderboards>,
+    mut fades: EventWriter<FadeTo>,
+) {
+    let selected = actions.read().find_map(|action| match action {
+        MenuAction::Select(index) => MENU_OPTIONS.get(*index).copied(),
+        MenuAction::Back | MenuAction::Adjust { .. } => None,
+    });
+    let Some(option) = selected.or_else(|| {
+        MENU_OPTIONS.into_iter().find(|option| {
+            option
+                .shortcut()
+                .is_some_and(|key| keyboard_input.just_pressed(key))
+        })
+    }) else {
+        return;
+    };
+
+    (*mode, *score_mode) = match option {
+        MenuOption::Play => (RunMode::Standard, ScoreMode::Normal),
+        MenuOption::SuddenDeath => (RunMode::Standard, ScoreMode::Decay),
+        MenuOption::TimeAttack => (RunMode::TimeAttack, ScoreMode::Normal),
+        MenuOption::Levels => (RunMode::Levels, ScoreMode::Normal),
+        MenuOption::Daily => {
+            let today = Date::today_utc();
+            let ranked = leaderboards.last_daily_attempt != Some(today);
+            if ranked {
+                // Record the attempt up front so quitting mid-run still uses it up.
+                leaderboards.last_daily_attempt = Some(today);
+                leaderboards.save();
+            }
+            (
+                RunMode::Daily {
+                    date: today,
+                    ranked,
+                },
+                ScoreMode::Normal,
+            )
+        }
+        MenuOption::Tutorial => (RunMode::Tutorial, ScoreMode::Normal),
+        MenuOption::Practice => (RunMode::Practice, ScoreMode::Normal),
+        MenuOption::Modifiers => {
+            commands.insert_resource(ModifiersOpen);
+            return;
+        }
+        MenuOption::Bestiary => {
+            commands.insert_resource(BestiaryOpen);
+            return;
+        }
+    };
+    fades.write(FadeTo(GameState::Playing));
+}
@@ -0,0 +1,253 @@
+//! Shared keyboard and gamepad navigation for menus.
+//!
+//! A menu puts `MenuNav` on its root and `MenuItem`s on the children that can
+//! be picked. Up and down move the highlight, wrapping at either end, and
+//! Enter, Escape, left and right come back as `MenuAction`s for the menu to
+//! act on. Menus without letter shortcuts add `WasdNav` so W and S move the
+//! highlight too.
+//!
+//! On a gamepad the D-pad or left stick moves the highlight, D-pad left and
+//! right adjust, South selects and East backs out. Holding up or down steps
+//! once, then repeats after a delay.
+
+use std::time::Duration;
+
+use bevy::prelude::*;
+
+use crate::transition::in_transition;
+
+const HIGHLIGHT_COLOR: Color = Color::srgb(1.0, 0.85, 0.2);
+/// How far the left stick has to be pushed to count as a direction.
+const STICK_THRESHOLD: f32 = 0.5;
+/// Time a direction is held before it starts repeating.
+const REPEAT_DELAY: f32 = 0.4;
+/// Time between repeated steps once repeating.
+const REPEAT_INTERVAL: f32 = 0.12;
+
+pub struct MenuNavPlugin;
+
+impl Plugin for MenuNavPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<MenuAction>()
+            .init_resource::<GamepadRepeat>()
+            .add_systems(
+                Update,
+                (
+                    navigate_menus.run_if(not(in_transition)),
+                    highlight_menu_items,
+                )
+                    .chain()
+                    .in_set(MenuNavSet),
+            );
+    }
+}
+
+/// Menus reading `MenuAction`s run after this.
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct MenuNavSet;
+
+/// The highlighted item of a menu with `len` items.
+#[derive(Component)]
+pub struct MenuNav {
+    pub selected: usize,
+    pub len: usize,
+}
+
+impl MenuNav {
+    pub fn new(len: usize) -> Self {
+        Self { selected: 0, len }
+    }
+
+    /// Moves the highlight by `steps`, wrapping around either end.
+    pub fn step(&mut self, steps: isize) {
+        if self.len > 0 {
+            self.selected = (self.selected as isize + steps).rem_euclid(self.len as isize) as usize;
+        }
+    }
+}
+
+/// Lets W and S move a `MenuNav` menu's highlight, like up and down. Left off
+/// menus whose items have letter shortcuts.
+#[derive(Component)]
+pub struct WasdNav;
+
+/// A child of a `MenuNav` menu, highlighted while its index is selected.
+#[derive(Component)]
+pub struct MenuItem(pub usize);
+
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuAction {
+    /// The item at this index was chosen.
+    Select(usize),
+    /// Leave the menu or go up a level.
+    Back,
+    /// Left or right was pressed on the item at this index, for items holding
+    /// a value that can be nudged either way.
+    Adjust { index: usize, steps: i32 },
+}
+
+/// The direction held on any gamepad and when it next repeats.
+#[derive(Resource)]
+struct GamepadRepeat {
+    /// -1 for up, 1 for down, 0 when nothing is held.
+    direction: isize,
+    timer: Timer,
+}
+
+impl Default for GamepadRepeat {
+    fn default() -> Self {
+        Self {
+            direction: 0,
+            timer: Timer::from_seconds(REPEAT_DELAY, TimerMode::Once),
+        }
+    }
+}
+
+impl GamepadRepeat {
+    /// Steps to move for this frame with `direction` held.
+    fn update(&mut self, direction: isize, delta: Duration) -> isize {
+        if direction != self.direction {
+            self.direction = direction;
+            self.timer = Timer::from_seconds(REPEAT_DELAY, TimerMode::Once);
+            return direction;
+        }
+        if direction == 0 || !self.timer.tick(delta).finished() {
+            return 0;
+        }
+        self.timer = Timer::from_seconds(REPEAT_INTERVAL, TimerMode::Once);
+        direction
+    }
+}
+
+fn held_direction(gamepad: &Gamepad) -> isize {
+    let stick = gamepad.get(GamepadAxis::LeftStickY).unwrap_or(0.0);
+    if gamepad.pressed(GamepadButton::DPadUp) || stick > STICK_THRESHOLD {
+        -1
+    } else if gamepad.pressed(GamepadButton::DPadDown) || stick < -STICK_THRESHOLD {
+        1
+    } else {
+        0
+    }
+}
+
+fn navigate_menus(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut repeat: ResMut<GamepadRepeat>,
+    mut nav_query: Query<(&mut MenuNav, Has<WasdNav>)>,
+    mut actions: EventWriter<MenuAction>,
+    time: Res<Time>,
+) {
+    let direction = gamepads
+        .iter()
+        .map(held_direction)
+        .find(|direction| *direction != 0)
+        .unwrap_or(0);
+    let gamepad_steps = repeat.update(direction, time.delta());
+    let gamepad_pressed =
+        |button: GamepadButton| gamepads.iter().any(|gamepad| gamepad.just_pressed(button));
+
+    for (mut nav, wasd) in nav_query.iter_mut() {
+        let up = keyboard_input.just_pressed(KeyCode::ArrowUp)
+            || wasd && keyboard_input.just_pressed(KeyCode::KeyW);
+        let down = keyboard_input.just_pressed(KeyCode::ArrowDown)
+            || wasd && keyboard_input.just_pressed(KeyCode::KeyS);
+        if up {
+            nav.step(-1);
+        } else if down {
+            nav.step(1);
+        } else if gamepad_steps != 0 {
+            nav.step(gamepad_steps);
+        } else if (keyboard_input.any_just_pressed([KeyCode::Enter, KeyCode::Space])
+            || gamepad_pressed(GamepadButton::South))
+            && nav.len > 0
+        {
+            actions.write(MenuAction::Select(nav.selected));
+        } else if keyboard_input.just_pressed(KeyCode::Escape)
+            || gamepad_pressed(GamepadButton::East)
+        {
+            actions.write(MenuAction::Back);
+        } else if nav.len > 0 {
+            let steps = if keyboard_input.just_pressed(KeyCode::ArrowLeft)
+                || gamepad_pressed(GamepadButton::DPadLeft)
+            {
+                -1
+            } else if keyboard_input.just_pressed(KeyCode::ArrowRight)
+                || gamepad_pressed(GamepadButton::DPadRight)
+            {
+                1
+            } else {
+                0
+            };
+            if steps != 0 {
+                actions.write(MenuAction::Adjust {
+                    index: nav.selected,
+                    steps,
+                });
+            }
+        }
+    }
+}
+
+fn highlight_menu_items(
+    nav_query: Query<(&MenuNav, &Children)>,
+    mut item_query: Query<(&MenuItem, &mut TextColor)>,
+) {
+    for (nav, children) in nav_query.iter() {
+        for child in children.iter() {
+            let Ok((item, mut color)) = item_query.get_mut(child) else {
+                continue;
+            };
+            let wanted = if item.0 == nav.selected {
+                HIGHLIGHT_COLOR
+            } else {
+                Color::WHITE
+            };
+            if color.0 != wanted {
+                color.0 = wanted;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_highlight_wraps_at_both_ends() {
+        let mut nav = MenuNav::new(4);
+        nav.step(-1);
+        assert_eq!(nav.selected, 3);
+        nav.step(1);
+        assert_eq!(nav.selected, 0);
+        nav.step(6);
+        assert_eq!(nav.selected, 2);
+
+        let mut empty = MenuNav::new(0);
+        empty.step(1);
+        assert_eq!(empty.selected, 0);
+    }
+
+    #[test]
+    fn a_held_direction_repeats_after_a_delay() {
+        let frame = Duration::from_millis(60);
+        let mut repeat = GamepadRepeat::default();
+        // One step straight away, then one once the delay is up and one every
+        // interval after that.
+        let steps: Vec<_> = (0..20).map(|_| repeat.update(1, frame)).collect();
+        let moved: Vec<_> = steps
+            .iter()
+            .enumerate()
+            .filter(|(_, steps)| **steps != 0)
+            .map(|(frame, _)| frame)
+            .collect();
+        assert_eq!(moved, [0, 7, 9, 11, 13, 15, 17, 19]);
+        assert!(steps.iter().all(|&steps| steps == 0 || steps == 1));
+
+        // Letting go and pressing the other way steps at once.
+        assert_eq!(repeat.update(0, frame), 0);
+        assert_eq!(repeat.update(-1, frame), -1);
+        assert_eq!(repeat.update(-1, frame), 0);
+    }
+}
@@ -0,0 +1,346 @@
+//! Optional rules that change a run, each trading a handicap for a reward.
+//!
+//! Modifiers are switched on and off from a screen opened from the main menu
+//! and stay on for every run until switched off again. Daily challenges, the
+//! tutorial and practice ignore them. The modifiers in play are listed on the
+//! HUD and count toward the grade a run earns, but a run with any on is kept
+//! off the leaderboards, local and online, since its score isn't comparable.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    GameState, RunMode,
+    custom_difficulty::{RunDifficulty, select_run_difficulty},
+    menu_nav::{MenuAction, MenuItem, MenuNav, MenuNavSet},
+    replay::ReplayPlayback,
+    start_run,
+};
+
+pub struct ModifiersPlugin;
+
+impl Plugin for ModifiersPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Modifiers>()
+            .init_resource::<RunModifiers>()
+            .add_systems(
+                OnExit(GameState::Menu),
+                (
+                    select_run_modifiers,
+                    // A replay's difficulty was recorded with its modifiers
+                    // already applied.
+                    apply_modifiers.run_if(not(resource_exists::<ReplayPlayback>)),
+                    spawn_modifier_list,
+                )
+                    .chain()
+                    .after(select_run_difficulty)
+                    .before(start_run),
+            )
+            .add_systems(OnEnter(GameState::Menu), despawn_modifier_list)
+            .add_systems(
+                Update,
+                (
+                    spawn_modifiers_screen.run_if(resource_added::<ModifiersOpen>),
+                    (modifiers_input.after(MenuNavSet), update_modifier_labels)
+                        .chain()
+                        .run_if(resource_exists::<ModifiersOpen>),
+                    despawn_modifiers_screen.run_if(resource_removed::<ModifiersOpen>),
+                )
+                    .run_if(in_state(GameState::Menu)),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Modifier {
+    /// Enemies move twice as fast, for double score.
+    FastFish,
+    /// The gun never overheats, but enemies have double health.
+    NoCooldown,
+    /// One hit ends the run, for triple coins.
+    GlassDuck,
+}
+
+impl Modifier {
+    pub const ALL: [Self; 3] = [Self::FastFish, Self::NoCooldown, Self::GlassDuck];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::FastFish => "Fast Fish",
+            Self::NoCooldown => "No Cooldown",
+            Self::GlassDuck => "Glass Duck",
+        }
+    }
+
+    fn description(self) -> &'static str {
+        match self {
+            Self::FastFish => "Enemies move twice as fast, for double score",
+            Self::NoCooldown => "The gun never overheats, but enemies have double health",
+            Self::GlassDuck => "One hit ends the run, for triple coins",
+        }
+    }
+}
+
+/// The modifiers switched on from the menu.
+#[derive(Resource, Default)]
+pub struct Modifiers(pub Vec<Modifier>);
+
+impl Modifiers {
+    fn toggle(&mut self, modifier: Modifier) {
+        if let Some(index) = self.0.iter().position(|on| *on == modifier) {
+            self.0.remove(index);
+        } else {
+            self.0.push(modifier);
+        }
+    }
+}
+
+/// The modifiers in play for the current run.
+#[derive(Resource, Default)]
+pub struct RunModifiers(pub Vec<Modifier>);
+
+impl RunModifiers {
+    pub fn has(&self, modifier: Modifier) -> bool {
+        self.0.contains(&modifier)
+    }
+
+    /// What points are multiplied by, on top of the combo.
+    pub fn score_multiplier(&self) -> u32 {
+        if self.has(Modifier::FastFish) { 2 } else { 1 }
+    }
+
+    pub fn coin_multiplier(&self) -> u32 {
+        if self.has(Modifier::GlassDuck) { 3 } else { 1 }
+    }
+}
+
+/// Whether the current run has any modifiers in play.
+pub fn modifiers_in_play(run_modifiers: Res<RunModifiers>) -> bool {
+    !run_modifiers.0.is_empty()
+}
+
+/// Present while the modifiers screen is showing in place of the main menu.
+#[derive(Resource)]
+pub struct ModifiersOpen;
+
+#[derive(Component)]
+struct ModifiersUi;
+
+#[derive(Component)]
+struct ModifierLabel(Modifier);
+
+/// Lists the modifiers in play during a run.
+#[derive(Component)]
+struct ModifierList;
+
+fn select_run_modifiers(
+    mut run_modifiers: ResMut<RunModifiers>,
+    modifiers: Res<Modifiers>,
+    mode: Res<RunMode>,
+    playback: Option<Res<ReplayPlayback>>,
+) {
+    run_modifiers.0 = match (&playback, &*mode) {
+        (Some(playback), _) => playback.replay().modifiers.clone(),
+        (None, RunMode::Daily { .. } | RunMode::Tutorial | RunMode::Practice) => Vec::new(),
+        (None, _) => modifiers.0.clone(),
+    };
+}
+
+/// Folds the modifiers' handicaps into the run's difficulty profile.
+fn apply_modifiers(run_modifiers: Res<RunModifiers>, mut run_difficulty: ResMut<RunDifficulty>) {
+    let difficulty = &mut run_difficulty.0;
+    for modifier in &run_modifiers.0 {
+        match modifier {
+            Modifier::FastFish => difficulty.enemy_speed *= 2.0,
+            Modifier::NoCooldown => difficulty.enemy_health *= 2,
+            Modifier::GlassDuck => difficulty.player_health = 1,
+        }
+    }
+}
+
+fn spawn_modifier_list(mut commands: Commands, run_modifiers: Res<RunModifiers>) {
+    if run_modifiers.0.is_empty() {
+        return;
+    }
+    let names: Vec<_> = run_modifiers
+        .0
+        .iter()
+        .map(|modifier| modifier.name())
+        .collect();
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            right: Val::Px(10.0),
+            bottom: Val::Px(10.0),
+            ..default()
+        },
+        Text::new(names.join("  ")),
+        TextFont {
+            font_size: 16.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 0.6, 0.1)),
+        ModifierList,
+    ));
+}
+
+fn despawn_modifier_list(mut commands: Commands, query: Query<Entity, With<ModifierList>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn spawn_modifiers_screen(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(16.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+            MenuNav::new(Modifier::ALL.len()),
+            ModifiersUi,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Modifiers"),
+                TextFont {
+                    font_size: 50.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+            ));
+            for (index, modifier) in Modifier::ALL.into_iter().enumerate() {
+                parent.spawn((
+                    Text::default(),
+                    TextFont {
+                        font_size: 24.0,
+                        ..default()
+                    },
+                    TextColor(Color::WHITE),
+                    MenuItem(index),
+                    ModifierLabel(modifier),
+                ));
+            }
+            parent.spawn((
+                Text::new("Enter - Toggle    Esc - Back"),
+                TextFont {
+                    font_size: 24.0,
+                    ..default()
+                },
+                TextColor(Color::srgb(0.7, 0.7, 0.7)),
+            ));
+        });
+}
+
+fn modifiers_input(
+    mut commands: Commands,
+    mut actions: EventReader<MenuAction>,
+    mut modifiers: ResMut<Modifiers>,
+) {
+    for action in actions.read() {
+        match action {
+            MenuAction::Select(index) => {
+                if let Some(modifier) = Modifier::ALL.get(*index) {
+                    modifiers.toggle(*modifier);
+                }
+            }
+            MenuAction::Back => commands.remove_resource::<ModifiersOpen>(),
+            MenuAction::Adjust { .. } => {}
+        }
+    }
+}
+
+fn update_modifier_labels(
+    modifiers: Res<Modifiers>,
+    mut query: Query<(&mut Text, Ref<ModifierLabel>)>,
+) {
+    for (mut text, label) in query.iter_mut() {
+        if !(modifiers.is_changed() || label.is_added()) {
+            continue;
+        }
+        let mark = if modifiers.0.contains(&label.0) {
+            "x"
+        } else {
+            " "
+        };
+        text.0 = format!("[{mark}] {} - {}", label.0.name(), label.0.description());
+    }
+}
+
+fn despawn_modifiers_screen(mut commands: Commands, query: Query<Entity, With<ModifiersUi>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::{
+        GameRng, MineAssets, Velocity, custom_difficulty::CustomDifficulty, hud::SurvivalTime,
+        play_area::PlayArea, spawn_mines, tuning::Tuning,
+    };
+
+    /// Velocity of the mine spawned under `modifiers` by a seed known to
+    /// bring one in.
+    fn mine_velocity(modifiers: Vec<Modifier>) -> Vec2 {
+        let mut world = World::new();
+        world.insert_resource(RunModifiers(modifiers));
+        world.insert_resource(RunDifficulty(CustomDifficulty::default()));
+        world.run_system_once(apply_modifiers).unwrap();
+
+        world.insert_resource(MineAssets {
+            mesh: Handle::default(),
+            material: Handle::default(),
+        });
+        world.insert_resource(PlayArea {
+            half_width: 400.0,
+            half_height: 300.0,
+        });
+        world.insert_resource(SurvivalTime(60.0));
+        world.init_resource::<Tuning>();
+        let seed = (0..)
+            .find(|seed| fastrand::Rng::with_seed(*seed).u8(0..3) == 0)
+            .unwrap();
+        world.insert_resource(GameRng(fastrand::Rng::with_seed(seed)));
+        world.run_system_once(spawn_mines).unwrap();
+
+        let mut query = world.query::<&Velocity>();
+        query.single(&world).unwrap().0
+    }
+
+    #[test]
+    fn fast_fish_doubles_enemy_speed() {
+        let base = mine_velocity(Vec::new());
+        assert!(base.length() > 0.0);
+        assert_eq!(mine_velocity(vec![Modifier::FastFish]), base * 2.0);
+    }
+
+    #[test]
+    fn modifier_runs_are_told_apart() {
+        let mut world = World::new();
+        world.init_resource::<RunModifiers>();
+        assert!(!world.run_system_once(modifiers_in_play).unwrap());
+        world
+            .resource_mut::<RunModifiers>()
+            .0
+            .push(Modifier::FastFish);
+        assert!(world.run_system_once(modifiers_in_play).unwrap());
+    }
+
+    #[test]
+    fn rewards_follow_the_modifiers_in_play() {
+        let run_modifiers = RunModifiers(vec![Modifier::FastFish, Modifier::GlassDuck]);
+        assert_eq!(run_modifiers.score_multiplier(), 2);
+        assert_eq!(run_modifiers.coin_multiplier(), 3);
+        assert_eq!(RunModifiers::default().score_multiplier(), 1);
+    }
+}
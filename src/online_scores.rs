@@ -0,0 +1,157 @@
+//! Posts final scores to an online leaderboard.
+//!
+//! Built only with the `online` feature, and even then off unless
+//! `DUCKGAME_LEADERBOARD_URL` names an endpoint. Each score is sent as JSON
+//! from a thread of its own, so a slow or unreachable server never stalls the
+//! game or the task pools, and every request gives up after a timeout. Failed
+//! attempts are retried a few times and then dropped with a warning.
+
+use std::{env, thread, time::Duration};
+
+use bevy::prelude::*;
+use serde::Serialize;
+use ureq::Agent;
+
+use crate::{
+    GameState, RunMode, RunSeed, Score, ScoreMode, debug::cheats_used,
+    modifiers::modifiers_in_play, replay::ReplayPlayback,
+};
+
+const URL_VAR: &str = "DUCKGAME_LEADERBOARD_URL";
+const NAME_VAR: &str = "DUCKGAME_PLAYER_NAME";
+const DEFAULT_NAME: &str = "Anonymous";
+const SUBMIT_ATTEMPTS: u32 = 3;
+/// Wait before retrying a failed attempt, doubled after each one.
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+/// Time allowed for the server to answer once connected.
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(10);
+
+pub struct OnlineScoresPlugin;
+
+impl Plugin for OnlineScoresPlugin {
+    fn build(&self, app: &mut App) {
+        let Some(url) = env::var(URL_VAR).ok().filter(|url| !url.is_empty()) else {
+            return;
+        };
+        let name = env::var(NAME_VAR).unwrap_or_else(|_| DEFAULT_NAME.to_string());
+        app.insert_resource(LeaderboardEndpoint { url, name });
+        for state in [GameState::GameOver, GameState::Victory] {
+            app.add_systems(
+                OnEnter(state),
+                submit_score.run_if(
+                    not(resource_exists::<ReplayPlayback>)
+                        .and(not(cheats_used))
+                        .and(not(modifiers_in_play)),
+                ),
+            );
+        }
+    }
+}
+
+#[derive(Resource)]
+struct LeaderboardEndpoint {
+    url: String,
+    /// Name the scores are submitted under.
+    name: String,
+}
+
+#[derive(Serialize)]
+struct ScoreSubmission<'a> {
+    name: &'a str,
+    score: u32,
+    seed: u64,
+    mode: String,
+}
+
+impl<'a> ScoreSubmission<'a> {
+    /// Returns `None` for runs that don't belong on a leaderboard.
+    fn new(
+        name: &'a str,
+        score: u32,
+        seed: u64,
+        mode: RunMode,
+        score_mode: ScoreMode,
+    ) -> Option<Self> {
+        let mode = match (mode, score_mode) {
+            (RunMode::Tutorial | RunMode::Practice, _)
+            | (RunMode::Daily { ranked: false, .. }, _) => return None,
+            (RunMode::Standard, ScoreMode::Decay) => "sudden_death".to_string(),
+            (RunMode::Standard, _) => "standard".to_string(),
+            (RunMode::TimeAttack, _) => "time_attack".to_string(),
+            (RunMode::Levels, _) => "levels".to_string(),
+            (RunMode::Daily { date, .. }, _) => format!("daily_{date}"),
+        };
+        Some(Self {
+            name,
+            score,
+            seed,
+            mode,
+        })
+    }
+
+    fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("a score submission always serializes")
+    }
+}
+
+fn submit_score(
+    endpoint: Res<LeaderboardEndpoint>,
+    score: Res<Score>,
+    run_seed: Res<RunSeed>,
+    mode: Res<RunMode>,
+    score_mode: Res<ScoreMode>,
+) {
+    let Some(submission) =
+        ScoreSubmission::new(&endpoint.name, score.0, run_seed.0, *mode, *score_mode)
+    else {
+        return;
+    };
+    let body = submission.to_json();
+    let url = endpoint.url.clone();
+    let spawned = thread::Builder::new()
+        .name("score submission".to_string())
+        .spawn(move || post_score(&url, &body));
+    if let Err(err) = spawned {
+        warn!("Couldn't start submitting score to {}: {err}", endpoint.url);
+    }
+}
+
+fn post_score(url: &str, body: &str) {
+    let agent: Agent = Agent::config_builder()
+        .timeout_connect(Some(CONNECT_TIMEOUT))
+        .timeout_recv_response(Some(RESPONSE_TIMEOUT))
+        .build()
+        .into();
+    let mut delay = RETRY_DELAY;
+    for attempt in 1..=SUBMIT_ATTEMPTS {
+        let result = agent
+            .post(url)
+            .header("Content-Type", "application/json")
+            .send(body);
+        match result {
+            Ok(_) => return,
+            Err(err) if attempt < SUBMIT_ATTEMPTS => {
+                warn!("Failed to submit score to {url} ({err}), retrying");
+                thread::sleep(delay);
+                delay *= 2;
+            }
+            Err(err) => warn!("Giving up on submitting score to {url}: {err}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn submissions_are_tagged_with_their_mode() {
+        let submission =
+            ScoreSubmission::new("duck", 10, 7, RunMode::Standard, ScoreMode::Decay).unwrap();
+        assert_eq!(submission.mode, "sudden_death");
+        assert!(
+            ScoreSubmission::new("duck", 10, 7, RunMode::Tutorial, ScoreMode::Normal).is_none()
+        );
+    }
+}
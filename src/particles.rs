@@ -0,0 +1,435 @@
+//! Short-lived particle bursts used for explosions, and the flipbook blast
+//! played over every enemy destroyed.
+//!
+//! Both are recycled through a `ParticlePool` rather than spawned and despawned
+//! every time: finished ones are hidden and handed out again, and each kind of
+//! effect has its own cap on how many ever exist.
+
+use bevy::{platform::collections::HashMap, prelude::*};
+
+use crate::{
+    EnemyKilled, GameState, Velocity, animation::AnimationTimer, apply_explosions,
+    settings::ReduceMotion,
+};
+
+const PARTICLE_LIFETIME: f32 = 0.5;
+const FLIPBOOK_FRAMES: u32 = 8;
+const FLIPBOOK_FRAME_SIZE: u32 = 64;
+const FLIPBOOK_FRAME_TIME: f32 = 0.05;
+/// Size of the blast relative to the enemy's body.
+const FLIPBOOK_SCALE: f32 = 1.5;
+/// How fast particles fly when reducing motion, relative to normal.
+const REDUCED_MOTION_SPEED: f32 = 0.25;
+const SPARK_CAP: usize = 400;
+const BLAST_CAP: usize = 32;
+
+pub struct ParticlePlugin;
+
+impl Plugin for ParticlePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<Explosion>()
+            .insert_resource(
+                ParticlePool::default()
+                    .with_cap(ParticleEffect::Spark, SPARK_CAP)
+                    .with_cap(ParticleEffect::Blast, BLAST_CAP),
+            )
+            .add_systems(Startup, setup_particle_assets)
+            .add_systems(OnEnter(GameState::Menu), release_particles)
+            .add_systems(
+                Update,
+                spawn_kill_flipbooks
+                    .after(apply_explosions)
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                Update,
+                // Also kept running for the fireworks on the victory screen.
+                (spawn_explosion_particles, update_particles)
+                    .run_if(in_state(GameState::Playing).or(in_state(GameState::Victory))),
+            );
+    }
+}
+
+/// An explosion at `position`. Anything within `radius` is caught in the blast;
+/// a radius of zero is purely cosmetic.
+#[derive(Event)]
+pub struct Explosion {
+    pub position: Vec2,
+    pub radius: f32,
+}
+
+#[derive(Resource)]
+struct ParticleAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<ColorMaterial>,
+    flipbook_image: Handle<Image>,
+    flipbook_layout: Handle<TextureAtlasLayout>,
+}
+
+/// The kinds of pooled effect, each capped separately.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ParticleEffect {
+    /// One of the sparks flung out by an `Explosion`.
+    Spark,
+    /// A blast from `explosion.png` over a destroyed enemy, playing through
+    /// once.
+    Blast,
+}
+
+/// Particle entities waiting to be reused. Effects that would take their kind
+/// past its cap are cut short or skipped instead.
+#[derive(Resource, Default)]
+pub struct ParticlePool {
+    pools: HashMap<ParticleEffect, EffectPool>,
+}
+
+#[derive(Default)]
+struct EffectPool {
+    free: Vec<Entity>,
+    /// Particles created so far, in use or not.
+    len: usize,
+    cap: usize,
+}
+
+/// Where the next particle of an effect comes from.
+enum PoolSlot {
+    Reuse(Entity),
+    Spawn,
+    Full,
+}
+
+impl ParticlePool {
+    /// Allows at most `cap` particles of `effect`.
+    pub fn with_cap(mut self, effect: ParticleEffect, cap: usize) -> Self {
+        self.pools.entry(effect).or_default().cap = cap;
+        self
+    }
+
+    fn claim(&mut self, effect: ParticleEffect) -> PoolSlot {
+        let pool = self.pools.entry(effect).or_default();
+        if let Some(entity) = pool.free.pop() {
+            PoolSlot::Reuse(entity)
+        } else if pool.len < pool.cap {
+            pool.len += 1;
+            PoolSlot::Spawn
+        } else {
+            PoolSlot::Full
+        }
+    }
+
+    fn release(&mut self, effect: ParticleEffect, entity: Entity) {
+        self.pools.entry(effect).or_default().free.push(entity);
+    }
+}
+
+/// A particle's remaining life. Idle particles in the pool have none.
+#[derive(Component)]
+struct Particle(Option<Timer>);
+
+fn setup_particle_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    asset_server: Res<AssetServer>,
+) {
+    commands.insert_resource(ParticleAssets {
+        mesh: meshes.add(Circle::new(3.0)),
+        material: materials.add(ColorMaterial::from(Color::srgb(1.0, 0.6, 0.1))),
+        flipbook_image: asset_server.load("explosion.png"),
+        flipbook_layout: atlas_layouts.add(TextureAtlasLayout::from_grid(
+            UVec2::splat(FLIPBOOK_FRAME_SIZE),
+            FLIPBOOK_FRAMES,
+            1,
+            None,
+            None,
+        )),
+    });
+}
+
+fn spawn_kill_flipbooks(
+    mut commands: Commands,
+    mut kills: EventReader<EnemyKilled>,
+    particle_assets: Res<ParticleAssets>,
+    mut pool: ResMut<ParticlePool>,
+    mut query: Query<(
+        &mut Particle,
+        &mut Transform,
+        &mut Sprite,
+        &mut AnimationTimer,
+        &mut Visibility,
+    )>,
+) {
+    for kill in kills.read() {
+        let size = Vec2::splat(kill.radius * 2.0 * FLIPBOOK_SCALE);
+        let transform = Transform::from_translation(kill.position.extend(2.0));
+        // Lives exactly as long as one play through the frames.
+        let particle = Particle(Some(Timer::from_seconds(
+            FLIPBOOK_FRAMES as f32 * FLIPBOOK_FRAME_TIME,
+            TimerMode::Once,
+        )));
+
+        match pool.claim(ParticleEffect::Blast) {
+            PoolSlot::Reuse(entity) => {
+                if let Ok((
+                    mut old_particle,
+                    mut old_transform,
+                    mut sprite,
+                    mut animation,
+                    mut visibility,
+                )) = query.get_mut(entity)
+                {
+                    *old_particle = particle;
+                    *old_transform = transform;
+                    sprite.custom_size = Some(size);
+                    if let Some(atlas) = &mut sprite.texture_atlas {
+                        atlas.index = 0;
+                    }
+                    animation.timer.reset();
+                    *visibility = Visibility::Inherited;
+                }
+            }
+            PoolSlot::Spawn => {
+                commands.spawn((
+                    Sprite {
+                        custom_size: Some(size),
+                        ..Sprite::from_atlas_image(
+                            particle_assets.flipbook_image.clone(),
+                            TextureAtlas {
+                                layout: particle_assets.flipbook_layout.clone(),
+                                index: 0,
+                            },
+                        )
+                    },
+                    transform,
+                    AnimationTimer::new(0..FLIPBOOK_FRAMES as usize, FLIPBOOK_FRAME_TIME),
+                    particle,
+                    ParticleEffect::Blast,
+                ));
+            }
+            PoolSlot::Full => {}
+        }
+    }
+}
+
+fn spawn_explosion_particles(
+    mut commands: Commands,
+    mut explosions: EventReader<Explosion>,
+    particle_assets: Res<ParticleAssets>,
+    mut pool: ResMut<ParticlePool>,
+    mut query: Query<(
+        &mut Particle,
+        &mut Transform,
+        &mut Velocity,
+        &mut Visibility,
+    )>,
+    reduce_motion: Res<ReduceMotion>,
+) {
+    for explosion in explosions.read() {
+        let count = 8 + (explosion.radius / 5.0) as u32;
+        let mut speed = 100.0 + explosion.radius * 2.0;
+        if reduce_motion.0 {
+            speed *= REDUCED_MOTION_SPEED;
+        }
+        for _ in 0..count {
+            let direction = Vec2::from_angle(fastrand::f32() * std::f32::consts::TAU);
+            let transform = Transform::from_translation(explosion.position.extend(1.0));
+            let velocity = Velocity(direction * speed * (0.5 + fastrand::f32() * 0.5));
+            let particle = Particle(Some(Timer::from_seconds(
+                PARTICLE_LIFETIME,
+                TimerMode::Once,
+            )));
+
+            match pool.claim(ParticleEffect::Spark) {
+                PoolSlot::Reuse(entity) => {
+                    if let Ok((
+                        mut old_particle,
+                        mut old_transform,
+                        mut old_velocity,
+                        mut visibility,
+                    )) = query.get_mut(entity)
+                    {
+                        *old_particle = particle;
+                        *old_transform = transform;
+                        *old_velocity = velocity;
+                        *visibility = Visibility::Inherited;
+                    }
+                }
+                PoolSlot::Spawn => {
+                    commands.spawn((
+                        Mesh2d(particle_assets.mesh.clone()),
+                        MeshMaterial2d(particle_assets.material.clone()),
+                        transform,
+                        velocity,
+                        particle,
+                        ParticleEffect::Spark,
+                    ));
+                }
+                // The rest of the burst would go past the cap.
+                PoolSlot::Full => break,
+            }
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn update_particles(
+    mut pool: ResMut<ParticlePool>,
+    mut query: Query<(
+        Entity,
+        &ParticleEffect,
+        &mut Particle,
+        &mut Transform,
+        Option<&mut Velocity>,
+        &mut Visibility,
+    )>,
+    time: Res<Time>,
+) {
+    for (entity, &effect, mut particle, mut transform, velocity, mut visibility) in query.iter_mut()
+    {
+        let Some(timer) = &mut particle.0 else {
+            continue;
+        };
+        timer.tick(time.delta());
+        if timer.finished() {
+            particle.0 = None;
+            if let Some(mut velocity) = velocity {
+                velocity.0 = Vec2::ZERO;
+            }
+            *visibility = Visibility::Hidden;
+            pool.release(effect, entity);
+        } else if effect == ParticleEffect::Spark {
+            transform.scale = Vec3::splat(timer.fraction_remaining());
+        }
+    }
+}
+
+/// Returns every particle still in flight to the pool.
+fn release_particles(
+    mut pool: ResMut<ParticlePool>,
+    mut query: Query<(
+        Entity,
+        &ParticleEffect,
+        &mut Particle,
+        Option<&mut Velocity>,
+        &mut Visibility,
+    )>,
+) {
+    for (entity, &effect, mut particle, velocity, mut visibility) in query.iter_mut() {
+        if particle.0.take().is_some() {
+            if let Some(mut velocity) = velocity {
+                velocity.0 = Vec2::ZERO;
+            }
+            *visibility = Visibility::Hidden;
+            pool.release(effect, entity);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::time::TimeUpdateStrategy;
+
+    use super::*;
+
+    fn particle_app(spark_cap: usize, blast_cap: usize) -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f32(
+                0.1,
+            )))
+            .add_event::<Explosion>()
+            .add_event::<EnemyKilled>()
+            .insert_resource(ReduceMotion(false))
+            .insert_resource(ParticleAssets {
+                mesh: default(),
+                material: default(),
+                flipbook_image: default(),
+                flipbook_layout: default(),
+            })
+            .insert_resource(
+                ParticlePool::default()
+                    .with_cap(ParticleEffect::Spark, spark_cap)
+                    .with_cap(ParticleEffect::Blast, blast_cap),
+            )
+            .add_systems(
+                Update,
+                (
+                    spawn_explosion_particles,
+                    spawn_kill_flipbooks,
+                    update_particles,
+                )
+                    .chain(),
+            );
+        app
+    }
+
+    fn count(app: &mut App, effect: ParticleEffect) -> usize {
+        app.world_mut()
+            .query::<&ParticleEffect>()
+            .iter(app.world())
+            .filter(|&&kind| kind == effect)
+            .count()
+    }
+
+    #[test]
+    fn heavy_explosions_stay_within_the_spark_cap() {
+        let mut app = particle_app(100, 10);
+        for _ in 0..30 {
+            for _ in 0..50 {
+                app.world_mut().send_event(Explosion {
+                    position: Vec2::ZERO,
+                    radius: 40.0,
+                });
+            }
+            app.update();
+            assert!(count(&mut app, ParticleEffect::Spark) <= 100);
+        }
+        assert_eq!(count(&mut app, ParticleEffect::Spark), 100);
+    }
+
+    #[test]
+    fn each_effect_has_its_own_cap() {
+        let mut app = particle_app(20, 3);
+        for _ in 0..10 {
+            app.world_mut().send_event(Explosion {
+                position: Vec2::ZERO,
+                radius: 0.0,
+            });
+            app.world_mut().send_event(EnemyKilled {
+                position: Vec2::ZERO,
+                radius: 10.0,
+                drop: None,
+                boss: false,
+            });
+        }
+        app.update();
+        assert_eq!(count(&mut app, ParticleEffect::Spark), 20);
+        assert_eq!(count(&mut app, ParticleEffect::Blast), 3);
+    }
+
+    #[test]
+    fn finished_particles_are_reused() {
+        let mut app = particle_app(100, 10);
+        app.world_mut().send_event(Explosion {
+            position: Vec2::ZERO,
+            radius: 0.0,
+        });
+        app.update();
+        let spawned = count(&mut app, ParticleEffect::Spark);
+        assert_eq!(spawned, 8);
+
+        // Long enough for the first burst to finish and go back to the pool.
+        for _ in 0..10 {
+            app.update();
+        }
+        app.world_mut().send_event(Explosion {
+            position: Vec2::ZERO,
+            radius: 0.0,
+        });
+        app.update();
+        assert_eq!(count(&mut app, ParticleEffect::Spark), spawned);
+    }
+}
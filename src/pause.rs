@@ -0,0 +1,485 @@
+//! The pause menu, opened with P or a gamepad's Start button during a run.
+
+use bevy::{audio::Volume, ecs::system::SystemParam, prelude::*};
+
+use crate::{
+    GameState, RunMode,
+    aim::{AimAssist, AimLine, AimMode, MouseFire},
+    custom_difficulty::{CustomDifficulty, DifficultySlider},
+    display::{DisplayMode, Resolution},
+    menu_nav::{MenuAction, MenuItem, MenuNav, MenuNavSet, WasdNav},
+    radar::Radar,
+    replay::ReplayRecorder,
+    rumble::RumbleSettings,
+    settings::{AutoFire, Captions, ReduceMotion, Settings},
+    transition::{FadeTo, in_transition},
+};
+
+pub struct PausePlugin;
+
+impl Plugin for PausePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            open_pause_menu.run_if(in_state(GameState::Playing).and(not(in_transition))),
+        )
+        .add_systems(OnEnter(GameState::Paused), spawn_pause_menu)
+        .add_systems(OnExit(GameState::Paused), despawn_pause_menu)
+        .add_systems(
+            OnEnter(GameState::Menu),
+            restart_run.run_if(resource_exists::<RestartRun>),
+        )
+        .add_systems(
+            Update,
+            (pause_menu_input, update_pause_items)
+                .chain()
+                .after(MenuNavSet)
+                .run_if(in_state(GameState::Paused).and(not(in_transition))),
+        );
+    }
+}
+
+/// Set when leaving a run through "Restart", so the menu immediately starts
+/// another run of the same mode instead of showing itself.
+#[derive(Resource)]
+pub struct RestartRun;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PausePage {
+    Main,
+    Settings,
+    Video,
+    Audio,
+    Controls,
+    Gameplay,
+    Difficulty,
+}
+
+impl PausePage {
+    fn options(self) -> &'static [PauseOption] {
+        match self {
+            Self::Main => &[
+                PauseOption::Resume,
+                PauseOption::Restart,
+                PauseOption::Page(Self::Settings),
+                PauseOption::QuitToMenu,
+            ],
+            Self::Settings => &[
+                PauseOption::Page(Self::Video),
+                PauseOption::Page(Self::Audio),
+                PauseOption::Page(Self::Controls),
+                PauseOption::Page(Self::Gameplay),
+                PauseOption::Back,
+            ],
+            Self::Video => &[
+                PauseOption::Resolution,
+                PauseOption::DisplayMode,
+                PauseOption::ReduceMotion,
+                PauseOption::Back,
+            ],
+            Self::Audio => &[PauseOption::Mute, PauseOption::Captions, PauseOption::Back],
+            Self::Controls => &[
+                PauseOption::Aim,
+                PauseOption::AimAssist,
+                PauseOption::AimLine,
+                PauseOption::MouseFireButton,
+                PauseOption::HoldToFire,
+                PauseOption::AutoFire,
+                PauseOption::Rumble,
+                PauseOption::Back,
+            ],
+            Self::Gameplay => &[
+                PauseOption::Radar,
+                PauseOption::Page(Self::Difficulty),
+                PauseOption::Back,
+            ],
+            Self::Difficulty => &[
+                PauseOption::Slider(DifficultySlider::SpawnRate),
+                PauseOption::Slider(DifficultySlider::EnemySpeed),
+                PauseOption::Slider(DifficultySlider::EnemyHealth),
+                PauseOption::Slider(DifficultySlider::PlayerHealth),
+                PauseOption::Back,
+            ],
+        }
+    }
+
+    /// The page that backing out of this one returns to.
+    fn parent(self) -> Option<Self> {
+        match self {
+            Self::Main => None,
+            Self::Settings => Some(Self::Main),
+            Self::Video | Self::Audio | Self::Controls | Self::Gameplay => Some(Self::Settings),
+            Self::Difficulty => Some(Self::Gameplay),
+        }
+    }
+
+    fn title(self) -> &'static str {
+        match self {
+            Self::Main => "Paused",
+            Self::Settings => "Settings",
+            Self::Video => "Video",
+            Self::Audio => "Audio",
+            Self::Controls => "Controls",
+            Self::Gameplay => "Gameplay",
+            Self::Difficulty => "Custom Difficulty",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PauseOption {
+    Resume,
+    Restart,
+    QuitToMenu,
+    /// Opens a page of the menu.
+    Page(PausePage),
+    Mute,
+    Rumble,
+    Aim,
+    AimAssist,
+    /// Only drawn while aiming with the mouse or stick.
+    AimLine,
+    /// Only used while aiming with the mouse.
+    MouseFireButton,
+    HoldToFire,
+    AutoFire,
+    Resolution,
+    DisplayMode,
+    ReduceMotion,
+    Captions,
+    Radar,
+    /// Changes take effect from the next run.
+    Slider(DifficultySlider),
+    Back,
+}
+
+impl PauseOption {
+    fn label(self, settings: &PauseSettings) -> String {
+        let on_off = |on: bool| if on { "On" } else { "Off" };
+        match self {
+            Self::Resume => "Resume".to_string(),
+            Self::Restart => "Restart".to_string(),
+            Self::QuitToMenu => "Quit to Menu".to_string(),
+            Self::Mute => format!("Mute: {}", on_off(settings.muted())),
+            Self::Rumble => format!("Rumble: {}", on_off(settings.rumble.enabled)),
+            Self::Aim => format!("Aim: {:?}", *settings.aim_mode),
+            Self::AimAssist => format!("Aim Assist: {}", on_off(settings.aim_assist.enabled)),
+            Self::AimLine => format!("Aim Line: {}", on_off(settings.aim_line.0)),
+            Self::MouseFireButton => format!("Mouse Fire: {:?}", settings.mouse_fire.button),
+            Self::HoldToFire => format!("Hold to Fire: {}", on_off(settings.mouse_fire.hold)),
+            Self::AutoFire => format!("Auto-Fire: {}", on_off(settings.auto_fire.0)),
+            Self::Resolution => format!(
+                "Resolution: {}{}",
+                settings.resolution.label(),
+                settings.display_note()
+            ),
+            Self::DisplayMode => format!(
+                "Display: {:?}{}",
+                *settings.display_mode,
+                settings.display_note()
+            ),
+            Self::ReduceMotion => format!("Reduce Motion: {}", on_off(settings.reduce_motion.0)),
+            Self::Captions => format!("Captions: {}", on_off(settings.captions.0)),
+            Self::Radar => format!("Radar: {}", on_off(settings.radar.0)),
+            Self::Page(page) => page.title().to_string(),
+            Self::Slider(slider) => settings.custom_difficulty.label(slider),
+            Self::Back => "Back".to_string(),
+        }
+    }
+}
+
+/// The settings that can be changed from the pause menu.
+#[derive(SystemParam)]
+struct PauseSettings<'w> {
+    global_volume: ResMut<'w, GlobalVolume>,
+    rumble: ResMut<'w, RumbleSettings>,
+    aim_mode: ResMut<'w, AimMode>,
+    aim_assist: ResMut<'w, AimAssist>,
+    mouse_fire: ResMut<'w, MouseFire>,
+    aim_line: ResMut<'w, AimLine>,
+    auto_fire: ResMut<'w, AutoFire>,
+    resolution: ResMut<'w, Resolution>,
+    display_mode: ResMut<'w, DisplayMode>,
+    reduce_motion: ResMut<'w, ReduceMotion>,
+    captions: ResMut<'w, Captions>,
+    radar: ResMut<'w, Radar>,
+    custom_difficulty: ResMut<'w, CustomDifficulty>,
+    saved: Res<'w, Settings>,
+    recorder: Option<Res<'w, ReplayRecorder>>,
+}
+
+impl PauseSettings<'_> {
+    fn muted(&self) -> bool {
+        self.global_volume.volume.to_linear() == 0.0
+    }
+
+    /// Display changes made during a recorded run only apply once it's over.
+    fn display_note(&self) -> &'static str {
+        if self.recorder.is_some() {
+            " (after this run)"
+        } else {
+            ""
+        }
+    }
+
+    fn is_changed(&self) -> bool {
+        self.global_volume.is_changed()
+            || self.rumble.is_changed()
+            || self.aim_mode.is_changed()
+            || self.aim_assist.is_changed()
+            || self.mouse_fire.is_changed()
+            || self.aim_line.is_changed()
+            || self.auto_fire.is_changed()
+            || self.resolution.is_changed()
+            || self.display_mode.is_changed()
+            || self.reduce_motion.is_changed()
+            || self.captions.is_changed()
+            || self.radar.is_changed()
+            || self.custom_difficulty.is_changed()
+    }
+}
+
+#[derive(Resource)]
+struct PauseMenu {
+    page: PausePage,
+}
+
+#[derive(Component)]
+struct PauseMenuUi;
+
+/// Rows needed for the longest page. Rows past the end of the current page
+/// are hidden. Settings are split over pages to keep this within an 800x600
+/// window.
+const PAUSE_ITEM_SLOTS: usize = 8;
+
+fn open_pause_menu(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyP)
+        || gamepads
+            .iter()
+            .any(|gamepad| gamepad.just_pressed(GamepadButton::Start))
+    {
+        game_state.set(GameState::Paused);
+    }
+}
+
+fn spawn_pause_menu(mut commands: Commands) {
+    commands.insert_resource(PauseMenu {
+        page: PausePage::Main,
+    });
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.0),
+                height: Val::Percent(100.0),
+                flex_direction: FlexDirection::Column,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(16.0),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+            MenuNav::new(PausePage::Main.options().len()),
+            WasdNav,
+            PauseMenuUi,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("Paused"),
+                TextFont {
+                    font_size: 60.0,
+                    ..default()
+                },
+            ));
+            for index in 0..PAUSE_ITEM_SLOTS {
+                parent.spawn((
+                    Text::default(),
+                    TextFont {
+                        font_size: 30.0,
+                        ..default()
+                    },
+                    TextColor(Color::WHITE),
+                    MenuItem(index),
+                ));
+            }
+        });
+}
+
+fn despawn_pause_menu(mut commands: Commands, query: Query<Entity, With<PauseMenuUi>>) {
+    commands.remove_resource::<PauseMenu>();
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn pause_menu_input(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    gamepads: Query<&Gamepad>,
+    mut actions: EventReader<MenuAction>,
+    mut menu: ResMut<PauseMenu>,
+    mut nav_query: Query<&mut MenuNav, With<PauseMenuUi>>,
+    mut mode: ResMut<RunMode>,
+    mut settings: PauseSettings,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut fades: EventWriter<FadeTo>,
+) {
+    let Ok(mut nav) = nav_query.single_mut() else {
+        return;
+    };
+    if keyboard_input.just_pressed(KeyCode::KeyP)
+        || gamepads
+            .iter()
+            .any(|gamepad| gamepad.just_pressed(GamepadButton::Start))
+    {
+        game_state.set(GameState::Playing);
+        return;
+    }
+
+    match actions.read().last().copied() {
+        Some(MenuAction::Back) => match menu.page.parent() {
+            Some(parent) => open_page(&mut menu, &mut nav, parent),
+            None => game_state.set(GameState::Playing),
+        },
+        Some(MenuAction::Adjust { index, steps }) => {
+            if let PauseOption::Slider(slider) = menu.page.options()[index] {
+                settings.custom_difficulty.adjust(slider, steps);
+            }
+        }
+        Some(MenuAction::Select(index)) => match menu.page.options()[index] {
+            PauseOption::Resume => game_state.set(GameState::Playing),
+            PauseOption::Restart => {
+                // A restarted daily challenge is a retry, so it no longer counts.
+                if let RunMode::Daily { ranked, .. } = &mut *mode {
+                    *ranked = false;
+                }
+                commands.insert_resource(RestartRun);
+                fades.write(FadeTo(GameState::Menu));
+            }
+            PauseOption::Page(page) => open_page(&mut menu, &mut nav, page),
+            PauseOption::QuitToMenu => {
+                fades.write(FadeTo(GameState::Menu));
+            }
+            PauseOption::Mute => {
+                let volume = if settings.muted() {
+                    settings.saved.volume
+                } else {
+                    0.0
+                };
+                settings.global_volume.volume = Volume::Linear(volume);
+            }
+            PauseOption::Rumble => settings.rumble.enabled = !settings.rumble.enabled,
+            PauseOption::Aim => *settings.aim_mode = settings.aim_mode.next(),
+            PauseOption::AimAssist => settings.aim_assist.enabled = !settings.aim_assist.enabled,
+            PauseOption::AimLine => settings.aim_line.0 = !settings.aim_line.0,
+            PauseOption::MouseFireButton => {
+                settings.mouse_fire.button = settings.mouse_fire.button.next();
+            }
+            PauseOption::HoldToFire => settings.mouse_fire.hold = !settings.mouse_fire.hold,
+            PauseOption::AutoFire => settings.auto_fire.0 = !settings.auto_fire.0,
+            PauseOption::Resolution => *settings.resolution = settings.resolution.next(),
+            PauseOption::DisplayMode => *settings.display_mode = settings.display_mode.next(),
+            PauseOption::ReduceMotion => settings.reduce_motion.0 = !settings.reduce_motion.0,
+            PauseOption::Captions => settings.captions.0 = !settings.captions.0,
+            PauseOption::Radar => settings.radar.0 = !settings.radar.0,
+            PauseOption::Slider(slider) => settings.custom_difficulty.cycle(slider),
+            PauseOption::Back => {
+                let parent = menu.page.parent().unwrap_or(PausePage::Main);
+                open_page(&mut menu, &mut nav, parent);
+            }
+        },
+        None => {}
+    }
+}
+
+fn open_page(menu: &mut PauseMenu, nav: &mut MenuNav, page: PausePage) {
+    menu.page = page;
+    *nav = MenuNav::new(page.options().len());
+}
+
+fn update_pause_items(
+    menu: Res<PauseMenu>,
+    settings: PauseSettings,
+    mut query: Query<(&MenuItem, &mut Text, &mut Node)>,
+) {
+    if !menu.is_changed() && !settings.is_changed() {
+        return;
+    }
+    let options = menu.page.options();
+    for (item, mut text, mut node) in query.iter_mut() {
+        let Some(option) = options.get(item.0) else {
+            node.display = Display::None;
+            continue;
+        };
+        node.display = Display::Flex;
+        text.0 = option.label(&settings);
+    }
+}
+
+/// Starts the next run straight away after a restart; the teardown already
+/// happened on entering the menu.
+fn restart_run(mut commands: Commands, mut game_state: ResMut<NextState<GameState>>) {
+    commands.remove_resource::<RestartRun>();
+    game_state.set(GameState::Playing);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every page reachable from the main one, in the order they're found.
+    fn reachable_pages() -> Vec<PausePage> {
+        let mut pages = vec![PausePage::Main];
+        let mut next = 0;
+        while let Some(&page) = pages.get(next) {
+            for option in page.options() {
+                if let PauseOption::Page(child) = *option {
+                    assert_eq!(child.parent(), Some(page));
+                    pages.push(child);
+                }
+            }
+            next += 1;
+        }
+        pages
+    }
+
+    #[test]
+    fn every_page_fits_in_a_small_window() {
+        // The title plus each row at Bevy's default line height, with the
+        // gaps between them.
+        let height = 60.0 * 1.2 + PAUSE_ITEM_SLOTS as f32 * (30.0 * 1.2 + 16.0);
+        assert!(height <= 600.0, "the menu is {height}px tall");
+        for page in reachable_pages() {
+            assert!(
+                page.options().len() <= PAUSE_ITEM_SLOTS,
+                "{page:?} overflows"
+            );
+            assert_eq!(
+                page.options().last() == Some(&PauseOption::Back),
+                page.parent().is_some()
+            );
+        }
+    }
+
+    #[test]
+    fn every_setting_can_be_reached() {
+        let options: Vec<_> = reachable_pages()
+            .into_iter()
+            .flat_map(|page| page.options().iter().copied())
+            .collect();
+        for setting in [
+            PauseOption::Radar,
+            PauseOption::Mute,
+            PauseOption::Rumble,
+            PauseOption::AimAssist,
+            PauseOption::Resolution,
+            PauseOption::Slider(DifficultySlider::PlayerHealth),
+        ] {
+            assert!(options.contains(&setting), "{setting:?} can't be reached");
+        }
+    }
+}
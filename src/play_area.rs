@@ -0,0 +1,144 @@
+//! Tracks the playable region of the world and optionally outlines its edges.
+
+use bevy::{prelude::*, window::PrimaryWindow};
+
+use crate::{WINDOW_HEIGHT, WINDOW_WIDTH};
+
+pub struct PlayAreaPlugin;
+
+impl Plugin for PlayAreaPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(PlayArea {
+            half_width: WINDOW_WIDTH / 2.0,
+            half_height: WINDOW_HEIGHT / 2.0,
+        })
+        .init_resource::<BoundaryStyle>()
+        .add_systems(
+            Update,
+            (
+                sync_play_area,
+                cycle_boundary_style,
+                draw_boundary.run_if(|style: Res<BoundaryStyle>| *style != BoundaryStyle::Hidden),
+            ),
+        );
+    }
+}
+
+/// The visible world region, centered on the origin.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct PlayArea {
+    pub half_width: f32,
+    pub half_height: f32,
+}
+
+impl PlayArea {
+    pub fn size(&self) -> Vec2 {
+        Vec2::new(self.half_width * 2.0, self.half_height * 2.0)
+    }
+}
+
+/// How the play area's border is drawn. Cycled with F3.
+#[derive(Resource, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoundaryStyle {
+    #[default]
+    Hidden,
+    Subtle,
+    Debug,
+}
+
+fn sync_play_area(
+    window_query: Query<&Window, With<PrimaryWindow>>,
+    mut play_area: ResMut<PlayArea>,
+) {
+    if let Ok(window) = window_query.single() {
+        let area = PlayArea {
+            half_width: window.width() / 2.0,
+            half_height: window.height() / 2.0,
+        };
+        play_area.set_if_neq(area);
+    }
+}
+
+fn cycle_boundary_style(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut style: ResMut<BoundaryStyle>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F3) {
+        *style = match *style {
+            BoundaryStyle::Hidden => BoundaryStyle::Subtle,
+            BoundaryStyle::Subtle => BoundaryStyle::Debug,
+            BoundaryStyle::Debug => BoundaryStyle::Hidden,
+        };
+    }
+}
+
+fn draw_boundary(mut gizmos: Gizmos, play_area: Res<PlayArea>, style: Res<BoundaryStyle>) {
+    let color = match *style {
+        BoundaryStyle::Debug => Color::srgb(1.0, 0.0, 1.0),
+        _ => Color::srgba(1.0, 1.0, 1.0, 0.25),
+    };
+    for (from, to) in boundary_walls(&play_area) {
+        gizmos.line_2d(from, to, color);
+    }
+}
+
+/// The four sides of the outline, each from one corner to the next.
+fn boundary_walls(play_area: &PlayArea) -> [(Vec2, Vec2); 4] {
+    // Inset by a pixel so the outline isn't clipped by the window edge.
+    let corner = Vec2::new(play_area.half_width, play_area.half_height) - Vec2::ONE;
+    let corners = [
+        Vec2::new(-corner.x, corner.y),
+        corner,
+        Vec2::new(corner.x, -corner.y),
+        -corner,
+    ];
+    std::array::from_fn(|i| (corners[i], corners[(i + 1) % 4]))
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{ecs::system::RunSystemOnce, window::WindowResolution};
+
+    use super::*;
+
+    #[test]
+    fn the_walls_outline_the_play_area() {
+        let walls = boundary_walls(&PlayArea {
+            half_width: 400.0,
+            half_height: 300.0,
+        });
+        assert_eq!(
+            walls,
+            [
+                (Vec2::new(-399.0, 299.0), Vec2::new(399.0, 299.0)),
+                (Vec2::new(399.0, 299.0), Vec2::new(399.0, -299.0)),
+                (Vec2::new(399.0, -299.0), Vec2::new(-399.0, -299.0)),
+                (Vec2::new(-399.0, -299.0), Vec2::new(-399.0, 299.0)),
+            ]
+        );
+    }
+
+    #[test]
+    fn the_play_area_follows_the_window() {
+        let mut world = World::new();
+        world.insert_resource(PlayArea {
+            half_width: 400.0,
+            half_height: 300.0,
+        });
+        world.spawn((
+            Window {
+                resolution: WindowResolution::new(1000.0, 500.0),
+                ..default()
+            },
+            PrimaryWindow,
+        ));
+        world.run_system_once(sync_play_area).unwrap();
+        assert_eq!(
+            *world.resource::<PlayArea>(),
+            PlayArea {
+                half_width: 500.0,
+                half_height: 250.0,
+            }
+        );
+    }
+}
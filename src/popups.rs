@@ -0,0 +1,314 @@
+//! Floating "+N" score popups shown where points were earned, and damage
+//! numbers where a bullet hurt an enemy without killing it.
+//!
+//! Each popup is normally its own `Text2d`. With `--batched-popups` they're
+//! instead drawn together as one mesh, built every frame from a small bitmap
+//! font, which keeps heavy combos down to a single draw.
+
+use bevy::{
+    asset::RenderAssetUsages,
+    image::ImageSampler,
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology},
+        render_resource::{Extent3d, TextureDimension, TextureFormat},
+        view::NoFrustumCulling,
+    },
+};
+
+use crate::GameState;
+
+const POPUP_LIFETIME: f32 = 0.8;
+const POPUP_RISE_SPEED: f32 = 40.0;
+const CRIT_COLOR: Color = Color::srgb(1.0, 0.85, 0.1);
+const DAMAGE_COLOR: Color = Color::srgb(1.0, 0.45, 0.4);
+/// Popups closer than this horizontally are treated as stacked.
+const POPUP_STACK_WIDTH: f32 = 40.0;
+/// Vertical gap kept between stacked popups.
+const POPUP_SPACING: f32 = 24.0;
+/// Characters in the bitmap font, in atlas order.
+const GLYPHS: &str = "0123456789+!";
+const GLYPH_WIDTH: usize = 3;
+const GLYPH_HEIGHT: usize = 5;
+/// Each glyph's pixels, top row first, one bit per column.
+const GLYPH_BITMAPS: [[u8; GLYPH_HEIGHT]; 12] = [
+    [0b111, 0b101, 0b101, 0b101, 0b111],
+    [0b010, 0b110, 0b010, 0b010, 0b111],
+    [0b111, 0b001, 0b111, 0b100, 0b111],
+    [0b111, 0b001, 0b111, 0b001, 0b111],
+    [0b101, 0b101, 0b111, 0b001, 0b001],
+    [0b111, 0b100, 0b111, 0b001, 0b111],
+    [0b111, 0b100, 0b111, 0b101, 0b111],
+    [0b111, 0b001, 0b001, 0b001, 0b001],
+    [0b111, 0b101, 0b111, 0b101, 0b111],
+    [0b111, 0b101, 0b111, 0b001, 0b111],
+    [0b000, 0b010, 0b111, 0b010, 0b000],
+    [0b010, 0b010, 0b010, 0b000, 0b010],
+];
+
+pub struct PopupPlugin;
+
+impl Plugin for PopupPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ScorePopup>()
+            .add_event::<DamagePopup>()
+            .init_resource::<PopupRendering>()
+            .add_systems(
+                Startup,
+                setup_popup_batch.run_if(resource_equals(PopupRendering::Batched)),
+            )
+            .add_systems(OnEnter(GameState::Menu), despawn_popups)
+            .add_systems(
+                Update,
+                (
+                    spawn_popups,
+                    update_popups,
+                    draw_popup_batch.run_if(resource_equals(PopupRendering::Batched)),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Points earned at `position`. Crits get a bigger, yellow popup.
+#[derive(Event)]
+pub struct ScorePopup {
+    pub position: Vec2,
+    pub points: u32,
+    pub crit: bool,
+}
+
+/// Damage dealt at `position` to an enemy that survived it.
+#[derive(Event)]
+pub struct DamagePopup {
+    pub position: Vec2,
+    pub damage: u32,
+}
+
+/// How popups are drawn.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum PopupRendering {
+    /// A `Text2d` per popup.
+    #[default]
+    Text,
+    /// All popups in one mesh.
+    Batched,
+}
+
+#[derive(Component)]
+struct Popup(Timer);
+
+/// What a batched popup says, since it has no `Text2d` of its own.
+#[derive(Component)]
+struct PopupGlyphs {
+    text: String,
+    font_size: f32,
+    color: Color,
+}
+
+/// The single mesh holding every batched popup.
+#[derive(Component)]
+struct PopupBatch(Handle<Mesh>);
+
+fn setup_popup_batch(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut images: ResMut<Assets<Image>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let atlas_width = GLYPH_WIDTH * GLYPH_BITMAPS.len();
+    let mut pixels = vec![0; atlas_width * GLYPH_HEIGHT * 4];
+    for (glyph, rows) in GLYPH_BITMAPS.iter().enumerate() {
+        for (y, row) in rows.iter().enumerate() {
+            for x in 0..GLYPH_WIDTH {
+                if row & (1 << (GLYPH_WIDTH - 1 - x)) != 0 {
+                    let offset = (y * atlas_width + glyph * GLYPH_WIDTH + x) * 4;
+                    pixels[offset..offset + 4].fill(255);
+                }
+            }
+        }
+    }
+    let mut atlas = Image::new(
+        Extent3d {
+            width: atlas_width as u32,
+            height: GLYPH_HEIGHT as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        pixels,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    atlas.sampler = ImageSampler::nearest();
+
+    let mesh = meshes.add(popup_mesh(&[]));
+    commands.spawn((
+        Mesh2d(mesh.clone()),
+        MeshMaterial2d(materials.add(ColorMaterial::from(images.add(atlas)))),
+        Transform::from_xyz(0.0, 0.0, 8.0),
+        // The mesh changes every frame, so its bounds can't be trusted.
+        NoFrustumCulling,
+        PopupBatch(mesh),
+    ));
+}
+
+fn spawn_popups(
+    mut commands: Commands,
+    mut popups: EventReader<ScorePopup>,
+    mut damage_popups: EventReader<DamagePopup>,
+    rendering: Res<PopupRendering>,
+    active_query: Query<&Transform, With<Popup>>,
+) {
+    let mut placed: Vec<Vec2> = active_query
+        .iter()
+        .map(|transform| transform.translation.truncate())
+        .collect();
+    let score_popups = popups.read().map(|popup| {
+        let (text, font_size, color) = if popup.crit {
+            (format!("+{}!", popup.points), 32.0, CRIT_COLOR)
+        } else {
+            (format!("+{}", popup.points), 20.0, Color::WHITE)
+        };
+        (popup.position, text, font_size, color)
+    });
+    // Kept small and without a "+" so they can't be mistaken for points.
+    let damage_popups = damage_popups
+        .read()
+        .map(|popup| (popup.position, popup.damage.to_string(), 16.0, DAMAGE_COLOR));
+    for (origin, text, font_size, color) in score_popups.chain(damage_popups) {
+        let position = stacked_position(origin, &placed);
+        placed.push(position);
+
+        let mut entity = commands.spawn((
+            Transform::from_translation(position.extend(8.0)),
+            Popup(Timer::from_seconds(POPUP_LIFETIME, TimerMode::Once)),
+        ));
+        match *rendering {
+            PopupRendering::Text => {
+                entity.insert((
+                    Text2d::new(text),
+                    TextFont {
+                        font_size,
+                        ..default()
+                    },
+                    TextColor(color),
+                ));
+            }
+            PopupRendering::Batched => {
+                entity.insert(PopupGlyphs {
+                    text,
+                    font_size,
+                    color,
+                });
+            }
+        }
+    }
+}
+
+/// Moves `position` up past any popups it would overlap so each stays readable.
+fn stacked_position(mut position: Vec2, placed: &[Vec2]) -> Vec2 {
+    // Each pass clears one blocker, so it settles within `placed.len()` passes.
+    for _ in 0..placed.len() {
+        let Some(blocker) = placed.iter().find(|other| {
+            (other.x - position.x).abs() < POPUP_STACK_WIDTH
+                && (other.y - position.y).abs() < POPUP_SPACING
+        }) else {
+            break;
+        };
+        position.y = blocker.y + POPUP_SPACING;
+    }
+    position
+}
+
+fn update_popups(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut Popup, &mut Transform, Option<&mut TextColor>)>,
+    time: Res<Time>,
+) {
+    for (entity, mut popup, mut transform, color) in query.iter_mut() {
+        popup.0.tick(time.delta());
+        if popup.0.finished() {
+            commands.entity(entity).despawn();
+        } else {
+            transform.translation.y += POPUP_RISE_SPEED * time.delta_secs();
+            if let Some(mut color) = color {
+                color.0.set_alpha(popup.0.fraction_remaining());
+            }
+        }
+    }
+}
+
+/// Rebuilds the batch mesh from every live batched popup.
+fn draw_popup_batch(
+    mut meshes: ResMut<Assets<Mesh>>,
+    batch_query: Query<&PopupBatch>,
+    popup_query: Query<(&Popup, &Transform, &PopupGlyphs)>,
+) {
+    let Ok(batch) = batch_query.single() else {
+        return;
+    };
+    let Some(mesh) = meshes.get_mut(&batch.0) else {
+        return;
+    };
+    let popups: Vec<_> = popup_query
+        .iter()
+        .filter(|(popup, ..)| !popup.0.finished())
+        .map(|(popup, transform, glyphs)| {
+            let color = glyphs.color.with_alpha(popup.0.fraction_remaining());
+            (transform.translation.truncate(), glyphs, color)
+        })
+        .collect();
+    *mesh = popup_mesh(&popups);
+}
+
+/// One textured quad per character, centered on each popup's position.
+fn popup_mesh(popups: &[(Vec2, &PopupGlyphs, Color)]) -> Mesh {
+    let mut positions = Vec::new();
+    let mut uvs = Vec::new();
+    let mut colors = Vec::new();
+    let mut indices = Vec::new();
+    let glyph_u = 1.0 / GLYPH_BITMAPS.len() as f32;
+
+    for (center, glyphs, color) in popups {
+        // Sized so the glyphs stand about as tall as the same text would.
+        let pixel = glyphs.font_size / 7.0;
+        let size = Vec2::new(GLYPH_WIDTH as f32, GLYPH_HEIGHT as f32) * pixel;
+        let advance = (GLYPH_WIDTH + 1) as f32 * pixel;
+        let chars: Vec<usize> = glyphs.text.chars().filter_map(|c| GLYPHS.find(c)).collect();
+        let width = chars.len() as f32 * advance - pixel;
+        let color = color.to_linear().to_f32_array();
+
+        for (i, glyph) in chars.into_iter().enumerate() {
+            let min = *center + Vec2::new(i as f32 * advance - width / 2.0, -size.y / 2.0);
+            let max = min + size;
+            let u = glyph as f32 * glyph_u;
+            let first = positions.len() as u32;
+            positions.extend([
+                [min.x, min.y, 0.0],
+                [max.x, min.y, 0.0],
+                [max.x, max.y, 0.0],
+                [min.x, max.y, 0.0],
+            ]);
+            uvs.extend([[u, 1.0], [u + glyph_u, 1.0], [u + glyph_u, 0.0], [u, 0.0]]);
+            colors.extend([color; 4]);
+            indices.extend([first, first + 1, first + 2, first, first + 2, first + 3]);
+        }
+    }
+
+    // Kept in the main world too so it can be rebuilt in place next frame.
+    Mesh::new(
+        PrimitiveTopology::TriangleList,
+        RenderAssetUsages::default(),
+    )
+    .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_UV_0, uvs)
+    .with_inserted_attribute(Mesh::ATTRIBUTE_COLOR, colors)
+    .with_inserted_indices(Indices::U32(indices))
+}
+
+fn despawn_popups(mut commands: Commands, query: Query<Entity, With<Popup>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
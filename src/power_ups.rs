@@ -0,0 +1,235 @@
+//! Temporary weapon power-ups, dropped by gunfish.
+//!
+//! A gunfish is a rare, glowing fish that always leaves a power-up behind when
+//! it's killed. Flying into the power-up gives the duck spread shots, rapid
+//! fire or piercing bullets for a while. Picking up another replaces the one
+//! in effect and starts its time afresh.
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    EnemyKilled, GameState, IsPlayer, PlayerHitbox, Velocity, apply_explosions,
+    bounds::BoundsBehavior, game_time::GameTime, settings::ReduceMotion,
+};
+
+const POWER_UP_DURATION: f32 = 8.0;
+const POWER_UP_RADIUS: f32 = 12.0;
+/// Speed the dropped power-up drifts left at, so it can't be left for later.
+const POWER_UP_DRIFT_SPEED: f32 = 40.0;
+/// Bullets in the fan fired while the spread power-up is in effect.
+pub const POWER_UP_SPREAD_COUNT: u32 = 3;
+/// How many times faster than auto-fire the rapid fire power-up shoots.
+pub const RAPID_FIRE_RATE: u32 = 2;
+pub const GUNFISH_TINT: Color = Color::srgb(1.0, 0.8, 0.25);
+/// Pulses per second of a gunfish's glow.
+const GLOW_RATE: f32 = 1.5;
+
+pub struct PowerUpPlugin;
+
+impl Plugin for PowerUpPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActivePowerUp>()
+            .add_event::<PowerUpCollected>()
+            .add_systems(Startup, setup_power_up_assets)
+            .add_systems(OnExit(GameState::Menu), clear_power_up)
+            .add_systems(OnEnter(GameState::Menu), despawn_power_ups)
+            .add_systems(
+                Update,
+                (
+                    drop_power_ups.after(apply_explosions),
+                    collect_power_ups,
+                    tick_power_up,
+                    glow_gunfish,
+                )
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WeaponPowerUp {
+    /// Shots fan out into `POWER_UP_SPREAD_COUNT` bullets.
+    Spread,
+    /// Holding fire shoots at `RAPID_FIRE_RATE` times the auto-fire rate, for
+    /// half the heat per shot.
+    RapidFire,
+    /// Bullets carry on through the fish they kill.
+    Pierce,
+}
+
+impl WeaponPowerUp {
+    pub const ALL: [Self; 3] = [Self::Spread, Self::RapidFire, Self::Pierce];
+
+    fn color(self) -> Color {
+        match self {
+            Self::Spread => Color::srgb(0.3, 0.9, 1.0),
+            Self::RapidFire => Color::srgb(1.0, 0.5, 0.1),
+            Self::Pierce => Color::srgb(0.9, 0.4, 1.0),
+        }
+    }
+}
+
+/// Marks a gunfish, with the power-up it drops when killed.
+#[derive(Component)]
+pub struct DropsPowerUp(pub WeaponPowerUp);
+
+/// The duck picked up a power-up.
+#[derive(Event)]
+pub struct PowerUpCollected;
+
+/// A bullet fired under the pierce power-up.
+#[derive(Component)]
+pub struct Piercing;
+
+/// A dropped power-up waiting to be picked up.
+#[derive(Component)]
+struct PowerUpPickup(WeaponPowerUp);
+
+/// The power-up in effect, if any, and how long it has left.
+#[derive(Resource, Default)]
+pub struct ActivePowerUp(Option<(WeaponPowerUp, Timer)>);
+
+impl ActivePowerUp {
+    pub fn is(&self, kind: WeaponPowerUp) -> bool {
+        matches!(&self.0, Some((active, _)) if *active == kind)
+    }
+}
+
+#[derive(Resource)]
+struct PowerUpAssets {
+    mesh: Handle<Mesh>,
+    /// One per kind, in the order of `WeaponPowerUp::ALL`.
+    materials: [Handle<ColorMaterial>; 3],
+}
+
+fn setup_power_up_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    commands.insert_resource(PowerUpAssets {
+        mesh: meshes.add(Circle::new(POWER_UP_RADIUS)),
+        materials: WeaponPowerUp::ALL.map(|kind| materials.add(kind.color())),
+    });
+}
+
+fn clear_power_up(mut active: ResMut<ActivePowerUp>) {
+    active.0 = None;
+}
+
+fn drop_power_ups(
+    mut commands: Commands,
+    mut kills: EventReader<EnemyKilled>,
+    assets: Res<PowerUpAssets>,
+) {
+    for kill in kills.read() {
+        let Some(kind) = kill.drop else {
+            continue;
+        };
+        commands.spawn((
+            Mesh2d(assets.mesh.clone()),
+            MeshMaterial2d(assets.materials[kind as usize].clone()),
+            Transform::from_translation(kill.position.extend(0.5)),
+            Velocity(Vec2::new(-POWER_UP_DRIFT_SPEED, 0.0)),
+            BoundsBehavior::Despawn,
+            PowerUpPickup(kind),
+        ));
+    }
+}
+
+fn collect_power_ups(
+    mut commands: Commands,
+    mut active: ResMut<ActivePowerUp>,
+    player_query: Query<(&Transform, &PlayerHitbox), With<IsPlayer>>,
+    pickup_query: Query<(Entity, &Transform, &PowerUpPickup)>,
+    mut collected: EventWriter<PowerUpCollected>,
+) {
+    let Ok((player_transform, hitbox)) = player_query.single() else {
+        return;
+    };
+    for (entity, transform, pickup) in pickup_query.iter() {
+        if transform
+            .translation
+            .truncate()
+            .distance(player_transform.translation.truncate())
+            < hitbox.0 + POWER_UP_RADIUS
+        {
+            commands.entity(entity).despawn();
+            active.0 = Some((
+                pickup.0,
+                Timer::from_seconds(POWER_UP_DURATION, TimerMode::Once),
+            ));
+            collected.write(PowerUpCollected);
+        }
+    }
+}
+
+fn tick_power_up(mut active: ResMut<ActivePowerUp>, time: Res<GameTime>) {
+    let Some((_, timer)) = &mut active.0 else {
+        return;
+    };
+    if timer.tick(time.delta()).finished() {
+        active.0 = None;
+    }
+}
+
+fn glow_gunfish(
+    mut query: Query<&mut Sprite, With<DropsPowerUp>>,
+    reduce_motion: Res<ReduceMotion>,
+    time: Res<Time>,
+) {
+    let glow = if reduce_motion.0 {
+        0.0
+    } else {
+        (time.elapsed_secs() * GLOW_RATE * std::f32::consts::TAU).sin() * 0.5 + 0.5
+    };
+    let color = GUNFISH_TINT.mix(&Color::WHITE, glow);
+    for mut sprite in query.iter_mut() {
+        // Keep the alpha, which the hit flash fades.
+        sprite.color = color.with_alpha(sprite.color.alpha());
+    }
+}
+
+fn despawn_power_ups(mut commands: Commands, query: Query<Entity, With<PowerUpPickup>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    #[test]
+    fn a_killed_gunfish_leaves_its_power_up() {
+        let mut world = World::new();
+        world.init_resource::<Events<EnemyKilled>>();
+        world.insert_resource(PowerUpAssets {
+            mesh: Handle::default(),
+            materials: default(),
+        });
+        world.send_event(EnemyKilled {
+            position: Vec2::new(120.0, -40.0),
+            radius: 10.0,
+            drop: Some(WeaponPowerUp::Pierce),
+            boss: false,
+        });
+        world.send_event(EnemyKilled {
+            position: Vec2::ZERO,
+            radius: 10.0,
+            drop: None,
+            boss: false,
+        });
+        world.run_system_once(drop_power_ups).unwrap();
+
+        let mut pickups = world.query::<(&Transform, &PowerUpPickup)>();
+        let [(transform, pickup)] = pickups.iter(&world).collect::<Vec<_>>()[..] else {
+            panic!("expected one power-up");
+        };
+        assert_eq!(pickup.0, WeaponPowerUp::Pierce);
+        assert_eq!(transform.translation.truncate(), Vec2::new(120.0, -40.0));
+    }
+}
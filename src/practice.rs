@@ -0,0 +1,270 @@
+//! A practice range for trying out weapons and aim, picked from the menu.
+//!
+//! A few dummy fish hang still at the right of the screen and come back
+//! shortly after being destroyed. Nothing else spawns, the player can't be
+//! hurt, and a readout shows damage per second and accuracy. The run carries
+//! on until the player leaves with Escape.
+
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{
+    BulletHit, EnemyAtlas, GameState, RunMode, check_for_collisions, fish,
+    game_time::GameTime,
+    run_stats::RunStats,
+    start_run,
+    transition::{FadeTo, in_transition},
+    tuning::Tuning,
+};
+
+const DUMMY_POSITIONS: [Vec2; 3] = [
+    Vec2::new(250.0, 150.0),
+    Vec2::new(300.0, 0.0),
+    Vec2::new(250.0, -150.0),
+];
+const DUMMY_HP: u32 = 10;
+/// Seconds before a destroyed dummy comes back.
+const DUMMY_RESPAWN_DELAY: f32 = 1.0;
+/// Seconds of hits that damage per second is averaged over.
+const DPS_WINDOW: f32 = 3.0;
+
+pub struct PracticePlugin;
+
+impl Plugin for PracticePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnExit(GameState::Menu),
+            start_practice.after(start_run).run_if(in_practice),
+        )
+        .add_systems(OnEnter(GameState::Menu), despawn_practice)
+        .add_systems(
+            Update,
+            (
+                leave_practice.run_if(not(in_transition)),
+                respawn_dummies,
+                (track_damage.after(check_for_collisions), update_readout).chain(),
+            )
+                .run_if(in_state(GameState::Playing).and(in_practice)),
+        );
+    }
+}
+
+pub fn in_practice(mode: Res<RunMode>) -> bool {
+    matches!(*mode, RunMode::Practice)
+}
+
+/// A practice target, standing in one of the `DUMMY_POSITIONS`.
+#[derive(Component)]
+struct Dummy(usize);
+
+#[derive(Resource)]
+struct PracticeRange {
+    /// Counts down to bringing back each position's dummy while it's gone.
+    respawns: [Timer; DUMMY_POSITIONS.len()],
+    /// When each recent hit landed, by elapsed time, and the damage it did.
+    hits: VecDeque<(f32, u32)>,
+}
+
+#[derive(Component)]
+struct PracticeReadout;
+
+fn dummy(enemy_atlas: &EnemyAtlas, tuning: &Tuning, slot: usize) -> impl Bundle {
+    (
+        fish(
+            enemy_atlas,
+            tuning,
+            DUMMY_POSITIONS[slot],
+            Vec2::ZERO,
+            DUMMY_HP,
+        ),
+        Dummy(slot),
+    )
+}
+
+fn start_practice(mut commands: Commands, enemy_atlas: Res<EnemyAtlas>, tuning: Res<Tuning>) {
+    commands.insert_resource(PracticeRange {
+        respawns: DUMMY_POSITIONS
+            .map(|_| Timer::from_seconds(DUMMY_RESPAWN_DELAY, TimerMode::Once)),
+        hits: VecDeque::new(),
+    });
+    for slot in 0..DUMMY_POSITIONS.len() {
+        commands.spawn(dummy(&enemy_atlas, &tuning, slot));
+    }
+    commands.spawn((
+        Text::default(),
+        TextFont {
+            font_size: 24.0,
+            ..default()
+        },
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(60.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        TextLayout::new_with_justify(JustifyText::Center),
+        PracticeReadout,
+    ));
+}
+
+fn despawn_practice(mut commands: Commands, query: Query<Entity, With<PracticeReadout>>) {
+    commands.remove_resource::<PracticeRange>();
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+fn leave_practice(keyboard_input: Res<ButtonInput<KeyCode>>, mut fades: EventWriter<FadeTo>) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        fades.write(FadeTo(GameState::Menu));
+    }
+}
+
+fn respawn_dummies(
+    mut commands: Commands,
+    mut range: ResMut<PracticeRange>,
+    enemy_atlas: Res<EnemyAtlas>,
+    tuning: Res<Tuning>,
+    dummy_query: Query<&Dummy>,
+    time: Res<GameTime>,
+) {
+    for (slot, timer) in range.respawns.iter_mut().enumerate() {
+        if dummy_query.iter().any(|dummy| dummy.0 == slot) {
+            timer.reset();
+        } else if timer.tick(time.delta()).finished() {
+            commands.spawn(dummy(&enemy_atlas, &tuning, slot));
+            timer.reset();
+        }
+    }
+}
+
+fn track_damage(
+    mut range: ResMut<PracticeRange>,
+    mut hits: EventReader<BulletHit>,
+    time: Res<GameTime>,
+) {
+    let now = time.elapsed_secs();
+    range.hits.extend(hits.read().map(|hit| (now, hit.damage)));
+    while range
+        .hits
+        .front()
+        .is_some_and(|(landed, _)| now - landed > DPS_WINDOW)
+    {
+        range.hits.pop_front();
+    }
+}
+
+fn update_readout(
+    range: Res<PracticeRange>,
+    stats: Res<RunStats>,
+    mut query: Query<&mut Text, With<PracticeReadout>>,
+) {
+    let damage: u32 = range.hits.iter().map(|(_, damage)| damage).sum();
+    let dps = damage as f32 / DPS_WINDOW;
+    for mut text in query.iter_mut() {
+        text.0 = format!(
+            "DPS: {dps:.1}    Accuracy: {:.0}%    Esc to leave",
+            stats.accuracy() * 100.0
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{state::app::StatesPlugin, time::TimeUpdateStrategy};
+
+    use super::*;
+    use crate::{
+        Health, IsPlayer, Knockback, PlayerHit, PlayerHitbox, Score, Velocity,
+        check_for_player_collisions, game_time::GameTimePlugin, particles::Explosion,
+        popups::ScorePopup,
+    };
+
+    /// Positions with a dummy standing in them.
+    fn dummy_slots(app: &mut App) -> Vec<usize> {
+        let mut slots: Vec<_> = app
+            .world_mut()
+            .query::<&Dummy>()
+            .iter(app.world())
+            .map(|dummy| dummy.0)
+            .collect();
+        slots.sort();
+        slots
+    }
+
+    #[test]
+    fn dummies_come_back_and_the_duck_cant_be_hurt() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, GameTimePlugin, PracticePlugin))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                100,
+            )))
+            .insert_state(GameState::Menu)
+            .insert_resource(RunMode::Practice)
+            .insert_resource(EnemyAtlas {
+                image: Handle::default(),
+                layout: Handle::default(),
+            })
+            .insert_resource(Tuning::default())
+            .init_resource::<RunStats>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .add_event::<BulletHit>()
+            .add_event::<FadeTo>()
+            .insert_resource(Score(0))
+            .insert_resource(Knockback(0.0))
+            .add_event::<Explosion>()
+            .add_event::<ScorePopup>()
+            .add_event::<PlayerHit>()
+            // Scheduled the way the game does it.
+            .add_systems(
+                Update,
+                check_for_player_collisions
+                    .run_if(in_state(GameState::Playing).and(not(in_practice))),
+            );
+        // Sat right on top of the middle dummy.
+        let player = app
+            .world_mut()
+            .spawn((
+                Transform::from_translation(DUMMY_POSITIONS[1].extend(0.0)),
+                Velocity::default(),
+                IsPlayer,
+                PlayerHitbox(10.0),
+                Health(1),
+            ))
+            .id();
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Playing);
+        app.update();
+
+        assert_eq!(dummy_slots(&mut app), [0, 1, 2]);
+        let destroyed = app
+            .world_mut()
+            .query::<(Entity, &Dummy)>()
+            .iter(app.world())
+            .find(|(_, dummy)| dummy.0 == 1)
+            .unwrap()
+            .0;
+        app.world_mut().despawn(destroyed);
+
+        // Gone for just short of the respawn delay, then back.
+        for _ in 0..9 {
+            app.update();
+        }
+        assert_eq!(dummy_slots(&mut app), [0, 2]);
+        for _ in 0..3 {
+            app.update();
+        }
+        assert_eq!(dummy_slots(&mut app), [0, 1, 2]);
+
+        assert_eq!(app.world().get::<Health>(player).unwrap().0, 1);
+        assert_eq!(
+            *app.world().resource::<State<GameState>>().get(),
+            GameState::Playing
+        );
+    }
+}
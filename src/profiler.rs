@@ -0,0 +1,256 @@
+//! A toggleable (F5) overlay showing timings for the heaviest systems and
+//! entity counts, and another (F7) graphing recent frame times against the
+//! time a frame gets at 60 fps.
+
+use std::{
+    collections::VecDeque,
+    sync::atomic::{AtomicU64, Ordering},
+    time::{Duration, Instant},
+};
+
+use bevy::prelude::*;
+
+use crate::{IsBullet, IsEnemy, play_area::PlayArea};
+
+/// Frames shown in the frame time graph, one bar each.
+const GRAPH_SAMPLES: usize = 120;
+const GRAPH_BAR_WIDTH: f32 = 2.0;
+/// Height of the graph's bars per millisecond.
+const GRAPH_SCALE: f32 = 3.0;
+const GRAPH_MARGIN: f32 = 12.0;
+const TARGET_FRAME_MS: f32 = 1000.0 / 60.0;
+
+pub struct ProfilerPlugin;
+
+impl Plugin for ProfilerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Profiler>()
+            .init_resource::<FrameGraph>()
+            .add_systems(Startup, setup_profiler_overlay)
+            .add_systems(
+                Update,
+                (
+                    toggle_profiler,
+                    update_profiler_overlay.run_if(|profiler: Res<Profiler>| profiler.visible),
+                    (
+                        record_frame_time,
+                        toggle_frame_graph,
+                        draw_frame_graph.run_if(|graph: Res<FrameGraph>| graph.visible),
+                    )
+                        .chain(),
+                ),
+            );
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum ProfiledSystem {
+    Update,
+    SpawnEnemies,
+    CheckForCollisions,
+}
+
+impl ProfiledSystem {
+    const ALL: [Self; 3] = [Self::Update, Self::SpawnEnemies, Self::CheckForCollisions];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Update => "update",
+            Self::SpawnEnemies => "spawn_enemies",
+            Self::CheckForCollisions => "check_for_collisions",
+        }
+    }
+}
+
+/// Most recent run time of each profiled system. Timings are stored atomically
+/// so profiled systems only need shared access and keep running in parallel.
+#[derive(Resource, Default)]
+pub struct Profiler {
+    visible: bool,
+    timings: [AtomicU64; ProfiledSystem::ALL.len()],
+}
+
+impl Profiler {
+    /// Times the caller until the returned guard is dropped. Does nothing while
+    /// the overlay is hidden.
+    pub fn measure(&self, system: ProfiledSystem) -> Measurement<'_> {
+        Measurement {
+            start: self.visible.then(Instant::now),
+            slot: &self.timings[system as usize],
+        }
+    }
+
+    pub fn timing(&self, system: ProfiledSystem) -> Duration {
+        Duration::from_nanos(self.timings[system as usize].load(Ordering::Relaxed))
+    }
+}
+
+pub struct Measurement<'a> {
+    start: Option<Instant>,
+    slot: &'a AtomicU64,
+}
+
+impl Drop for Measurement<'_> {
+    fn drop(&mut self) {
+        if let Some(start) = self.start {
+            self.slot
+                .store(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Durations of the most recent frames in milliseconds, oldest first. Kept
+/// up to date while hidden so the graph is full as soon as it's shown.
+#[derive(Resource)]
+struct FrameGraph {
+    visible: bool,
+    samples: VecDeque<f32>,
+}
+
+impl Default for FrameGraph {
+    fn default() -> Self {
+        Self {
+            visible: false,
+            samples: VecDeque::with_capacity(GRAPH_SAMPLES),
+        }
+    }
+}
+
+impl FrameGraph {
+    /// Adds the latest frame, dropping the oldest once the graph is full.
+    fn push(&mut self, frame_ms: f32) {
+        if self.samples.len() == GRAPH_SAMPLES {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(frame_ms);
+    }
+}
+
+#[derive(Component)]
+struct ProfilerOverlay;
+
+fn setup_profiler_overlay(mut commands: Commands) {
+    commands.spawn((
+        Text::default(),
+        TextFont {
+            font_size: 14.0,
+            ..default()
+        },
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            right: Val::Px(10.0),
+            ..default()
+        },
+        Visibility::Hidden,
+        ProfilerOverlay,
+    ));
+}
+
+fn toggle_profiler(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut profiler: ResMut<Profiler>,
+    mut query: Query<&mut Visibility, With<ProfilerOverlay>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::F5) {
+        profiler.visible = !profiler.visible;
+        for mut visibility in query.iter_mut() {
+            *visibility = if profiler.visible {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            };
+        }
+    }
+}
+
+fn update_profiler_overlay(
+    profiler: Res<Profiler>,
+    entities: Query<()>,
+    enemies: Query<(), With<IsEnemy>>,
+    bullets: Query<(), With<IsBullet>>,
+    mut query: Query<&mut Text, With<ProfilerOverlay>>,
+) {
+    let mut lines: Vec<String> = ProfiledSystem::ALL
+        .iter()
+        .map(|&system| {
+            format!(
+                "{}: {:.3} ms",
+                system.name(),
+                profiler.timing(system).as_secs_f64() * 1000.0
+            )
+        })
+        .collect();
+    lines.push(format!(
+        "entities: {} (enemies {}, bullets {})",
+        entities.iter().count(),
+        enemies.iter().count(),
+        bullets.iter().count()
+    ));
+
+    if let Ok(mut text) = query.single_mut() {
+        text.0 = lines.join("\n");
+    }
+}
+
+fn record_frame_time(mut graph: ResMut<FrameGraph>, time: Res<Time<Real>>) {
+    graph.push(time.delta_secs() * 1000.0);
+}
+
+fn toggle_frame_graph(keyboard_input: Res<ButtonInput<KeyCode>>, mut graph: ResMut<FrameGraph>) {
+    if keyboard_input.just_pressed(KeyCode::F7) {
+        graph.visible = !graph.visible;
+    }
+}
+
+/// Bars rise from the bottom left corner, newest on the right. Frames that ran
+/// over the 60 fps line are drawn in red.
+fn draw_frame_graph(mut gizmos: Gizmos, graph: Res<FrameGraph>, play_area: Res<PlayArea>) {
+    let origin = Vec2::new(
+        -play_area.half_width + GRAPH_MARGIN,
+        -play_area.half_height + GRAPH_MARGIN,
+    );
+    for (i, &frame_ms) in graph.samples.iter().enumerate() {
+        let x = origin.x + i as f32 * GRAPH_BAR_WIDTH;
+        let color = if frame_ms > TARGET_FRAME_MS {
+            Color::srgb(1.0, 0.25, 0.2)
+        } else {
+            Color::srgb(0.3, 1.0, 0.4)
+        };
+        gizmos.line_2d(
+            Vec2::new(x, origin.y),
+            Vec2::new(x, origin.y + frame_ms * GRAPH_SCALE),
+            color,
+        );
+    }
+
+    let target_y = origin.y + TARGET_FRAME_MS * GRAPH_SCALE;
+    gizmos.line_2d(
+        Vec2::new(origin.x, target_y),
+        Vec2::new(origin.x + GRAPH_SAMPLES as f32 * GRAPH_BAR_WIDTH, target_y),
+        Color::WHITE,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn timings_are_only_taken_while_the_overlay_is_shown() {
+        let mut profiler = Profiler::default();
+        drop(profiler.measure(ProfiledSystem::Update));
+        assert_eq!(profiler.timing(ProfiledSystem::Update), Duration::ZERO);
+
+        profiler.visible = true;
+        {
+            let _measurement = profiler.measure(ProfiledSystem::Update);
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert!(profiler.timing(ProfiledSystem::Update) >= Duration::from_millis(1));
+        assert_eq!(
+            profiler.timing(ProfiledSystem::SpawnEnemies),
+            Duration::ZERO
+        );
+    }
+}
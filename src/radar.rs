@@ -0,0 +1,185 @@
+//! A small radar in the corner of the HUD, so a play area bigger than the view
+//! can't hide what's coming.
+//!
+//! Every fish and mine within `RADAR_RANGE` of the duck shows up as a dot,
+//! scaled down around the duck at the middle. The radar is off by default and
+//! switched on from the settings in the pause menu.
+
+use bevy::prelude::*;
+
+use crate::{GameState, IsEnemy, IsPlayer, Mine, bounds::apply_bounds};
+
+/// Width of the radar on screen.
+const RADAR_SIZE: f32 = 100.0;
+/// Distance in the world from the duck to the radar's edge.
+const RADAR_RANGE: f32 = 800.0;
+const BLIP_SIZE: f32 = 4.0;
+/// Dots available for enemies. Any more in range go undrawn.
+const MAX_BLIPS: usize = 48;
+const BLIP_COLOR: Color = Color::srgb(1.0, 0.3, 0.2);
+
+pub struct RadarPlugin;
+
+impl Plugin for RadarPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Radar>()
+            .add_systems(OnExit(GameState::Menu), spawn_radar)
+            .add_systems(OnEnter(GameState::Menu), despawn_radar)
+            .add_systems(
+                Update,
+                update_radar
+                    .after(apply_bounds)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Whether the radar is shown.
+#[derive(Resource, Default)]
+pub struct Radar(pub bool);
+
+#[derive(Component)]
+struct RadarUi;
+
+#[derive(Component)]
+struct Blip;
+
+/// Where on the radar, from its middle, to plot something `offset` away from
+/// the duck. `None` if it's out of range. Up in the world is up on the radar.
+fn blip_offset(offset: Vec2) -> Option<Vec2> {
+    (offset.length() <= RADAR_RANGE).then(|| offset / RADAR_RANGE * (RADAR_SIZE / 2.0))
+}
+
+fn blip_node(offset: Vec2) -> Node {
+    Node {
+        position_type: PositionType::Absolute,
+        left: Val::Px(RADAR_SIZE / 2.0 + offset.x - BLIP_SIZE / 2.0),
+        top: Val::Px(RADAR_SIZE / 2.0 - offset.y - BLIP_SIZE / 2.0),
+        width: Val::Px(BLIP_SIZE),
+        height: Val::Px(BLIP_SIZE),
+        ..default()
+    }
+}
+
+fn spawn_radar(mut commands: Commands, radar: Res<Radar>) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                right: Val::Px(10.0),
+                bottom: Val::Px(40.0),
+                width: Val::Px(RADAR_SIZE),
+                height: Val::Px(RADAR_SIZE),
+                ..default()
+            },
+            BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.4)),
+            BorderRadius::MAX,
+            if radar.0 {
+                Visibility::Inherited
+            } else {
+                Visibility::Hidden
+            },
+            RadarUi,
+        ))
+        .with_children(|parent| {
+            // The duck, always in the middle.
+            parent.spawn((
+                blip_node(Vec2::ZERO),
+                BackgroundColor(Color::WHITE),
+                BorderRadius::MAX,
+            ));
+            for _ in 0..MAX_BLIPS {
+                parent.spawn((
+                    blip_node(Vec2::ZERO),
+                    BackgroundColor(BLIP_COLOR),
+                    BorderRadius::MAX,
+                    Visibility::Hidden,
+                    Blip,
+                ));
+            }
+        });
+}
+
+#[allow(clippy::type_complexity)]
+fn update_radar(
+    radar: Res<Radar>,
+    mut radar_query: Query<&mut Visibility, (With<RadarUi>, Without<Blip>)>,
+    mut blip_query: Query<(&mut Node, &mut Visibility), With<Blip>>,
+    player_query: Query<&Transform, With<IsPlayer>>,
+    enemy_query: Query<&Transform, Or<(With<IsEnemy>, With<Mine>)>>,
+) {
+    for mut visibility in radar_query.iter_mut() {
+        visibility.set_if_neq(if radar.0 {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        });
+    }
+    let Ok(player_transform) = player_query.single() else {
+        return;
+    };
+    if !radar.0 {
+        return;
+    }
+    let player = player_transform.translation.truncate();
+    let mut offsets = enemy_query
+        .iter()
+        .filter_map(|transform| blip_offset(transform.translation.truncate() - player));
+    for (mut node, mut visibility) in blip_query.iter_mut() {
+        match offsets.next() {
+            Some(offset) => {
+                *node = blip_node(offset);
+                *visibility = Visibility::Inherited;
+            }
+            None => *visibility = Visibility::Hidden,
+        }
+    }
+}
+
+fn despawn_radar(mut commands: Commands, query: Query<Entity, With<RadarUi>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    #[test]
+    fn blips_are_scaled_down_around_the_duck() {
+        assert_eq!(blip_offset(Vec2::ZERO), Some(Vec2::ZERO));
+        assert_eq!(
+            blip_offset(Vec2::new(RADAR_RANGE, 0.0)),
+            Some(Vec2::new(RADAR_SIZE / 2.0, 0.0))
+        );
+        assert_eq!(
+            blip_offset(Vec2::new(-200.0, 400.0)),
+            Some(Vec2::new(-12.5, 25.0))
+        );
+        assert_eq!(blip_offset(Vec2::new(0.0, RADAR_RANGE + 1.0)), None);
+    }
+
+    #[test]
+    fn enemies_in_range_are_plotted() {
+        let mut world = World::new();
+        world.insert_resource(Radar(true));
+        world.run_system_once(spawn_radar).unwrap();
+        world.spawn((Transform::from_xyz(100.0, 100.0, 0.0), IsPlayer));
+        world.spawn((Transform::from_xyz(500.0, -300.0, 0.0), IsEnemy));
+        world.spawn((Transform::from_xyz(100.0, 2_000.0, 0.0), IsEnemy));
+        world.run_system_once(update_radar).unwrap();
+
+        let mut blips = world.query_filtered::<(&Node, &Visibility), With<Blip>>();
+        let shown: Vec<_> = blips
+            .iter(&world)
+            .filter(|(_, visibility)| **visibility == Visibility::Inherited)
+            .map(|(node, _)| (node.left, node.top))
+            .collect();
+        // 400 right and 400 down is a quarter of the radar's range.
+        let expected = blip_node(Vec2::new(25.0, -25.0));
+        assert_eq!(shown, [(expected.left, expected.top)]);
+    }
+}
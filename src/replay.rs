@@ -0,0 +1,399 @@
+//! Records every run's input so it can be played back with `--replay`.
+//!
+//! A replay stores the run's seed and starting difficulty along with the player
+//! input and frame time of every gameplay frame. Playback feeds both back in, so
+//! the run unfolds the same way it was recorded.
+
+use std::{fs, path::Path, time::Duration};
+
+use bevy::{prelude::*, time::TimeUpdateStrategy};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Difficulty, GameState, InputSet, MultiShot, PlayerInput, RunSeed, ScoreMode,
+    aim::read_aim,
+    bounds::BorderDamage,
+    custom_difficulty::{CustomDifficulty, RunDifficulty},
+    despawn_run,
+    modifiers::{Modifier, RunModifiers},
+    play_area::PlayArea,
+    scrolling::CenteredPlayer,
+    start_run,
+    stress::StressTest,
+    tuning::Tuning,
+    waves::WaveData,
+};
+
+const REPLAY_DIR: &str = "replays";
+const REPLAY_VERSION: u32 = 1;
+
+const INPUT_LEFT: u8 = 1 << 0;
+const INPUT_RIGHT: u8 = 1 << 1;
+const INPUT_UP: u8 = 1 << 2;
+const INPUT_DOWN: u8 = 1 << 3;
+const INPUT_FIRE: u8 = 1 << 4;
+const INPUT_FOCUS: u8 = 1 << 5;
+const INPUT_ASSISTED: u8 = 1 << 6;
+
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameState::Menu),
+            start_playback
+                .after(despawn_run)
+                .run_if(resource_exists::<ReplayPlayback>),
+        )
+        // Runs being played back aren't recorded again, and stress tests
+        // aren't worth recording.
+        .add_systems(
+            OnExit(GameState::Menu),
+            start_recording.after(start_run).run_if(
+                not(resource_exists::<ReplayPlayback>).and(not(resource_exists::<StressTest>)),
+            ),
+        )
+        .add_systems(
+            Update,
+            (
+                play_back_replay.run_if(resource_exists::<ReplayPlayback>),
+                record_replay.run_if(resource_exists::<ReplayRecorder>),
+            )
+                .chain()
+                .in_set(InputSet)
+                .after(read_aim)
+                .run_if(in_state(GameState::Playing)),
+        )
+        .add_systems(
+            OnEnter(GameState::GameOver),
+            write_replay.run_if(resource_exists::<ReplayRecorder>),
+        )
+        .add_systems(
+            OnEnter(GameState::Victory),
+            write_replay.run_if(resource_exists::<ReplayRecorder>),
+        )
+        .add_systems(OnEnter(GameState::Menu), stop_recording)
+        .add_systems(OnExit(GameState::GameOver), stop_playback)
+        .add_systems(OnExit(GameState::Victory), stop_playback)
+        .add_systems(
+            OnTransition {
+                exited: GameState::Paused,
+                entered: GameState::Menu,
+            },
+            stop_playback,
+        );
+        // The saved replay ends where the run did; carrying on in endless mode
+        // or retrying a level isn't recorded.
+        for ended in [GameState::Victory, GameState::GameOver] {
+            app.add_systems(
+                OnTransition {
+                    exited: ended,
+                    entered: GameState::Playing,
+                },
+                stop_recording,
+            );
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct Replay {
+    version: u32,
+    pub seed: u64,
+    pub difficulty: f32,
+    #[serde(default)]
+    score_mode: ScoreMode,
+    /// Whether the run was launched with `--border-damage`.
+    #[serde(default)]
+    pub border_damage: bool,
+    /// Whether the run was launched with `--centered`.
+    #[serde(default)]
+    pub centered: bool,
+    /// Whether the run followed the `--waves` schedule.
+    #[serde(default)]
+    pub waves: bool,
+    /// Window size when the run started. Older replays were all 800x600.
+    #[serde(default)]
+    pub window_size: Option<(f32, f32)>,
+    #[serde(default)]
+    pub custom_difficulty: CustomDifficulty,
+    #[serde(default)]
+    pub tuning: Tuning,
+    #[serde(default)]
+    pub modifiers: Vec<Modifier>,
+    /// Bullets per shot when the run started. Older replays all started at one.
+    #[serde(default)]
+    multi_shot: Option<u32>,
+    pub frames: Vec<ReplayFrame>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+pub struct ReplayFrame {
+    pub dt: f64,
+    input: u8,
+    /// Angle of the aim direction in radians, zero being straight ahead.
+    #[serde(default)]
+    aim: f32,
+}
+
+impl ReplayFrame {
+    fn new(dt: f64, input: &PlayerInput) -> Self {
+        let mut bits = 0;
+        for (pressed, bit) in [
+            (input.left, INPUT_LEFT),
+            (input.right, INPUT_RIGHT),
+            (input.up, INPUT_UP),
+            (input.down, INPUT_DOWN),
+            (input.fire, INPUT_FIRE),
+            (input.focus, INPUT_FOCUS),
+            (input.assisted, INPUT_ASSISTED),
+        ] {
+            if pressed {
+                bits |= bit;
+            }
+        }
+        Self {
+            dt,
+            input: bits,
+            aim: input.aim.to_angle(),
+        }
+    }
+
+    pub fn input(&self) -> PlayerInput {
+        PlayerInput {
+            left: self.input & INPUT_LEFT != 0,
+            right: self.input & INPUT_RIGHT != 0,
+            up: self.input & INPUT_UP != 0,
+            down: self.input & INPUT_DOWN != 0,
+            fire: self.input & INPUT_FIRE != 0,
+            focus: self.input & INPUT_FOCUS != 0,
+            assisted: self.input & INPUT_ASSISTED != 0,
+            aim: Vec2::from_angle(self.aim),
+        }
+    }
+}
+
+pub fn load_replay(path: &Path) -> Result<Replay, String> {
+    let contents = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let replay: Replay =
+        ron::from_str(&contents).map_err(|err| format!("unreadable replay ({err})"))?;
+    if replay.version != REPLAY_VERSION {
+        return Err(format!(
+            "replay is version {}, expected {REPLAY_VERSION}",
+            replay.version
+        ));
+    }
+    Ok(replay)
+}
+
+/// A replay being played back in place of live input.
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    replay: Replay,
+    cursor: usize,
+}
+
+impl ReplayPlayback {
+    pub fn new(replay: Replay) -> Self {
+        Self { replay, cursor: 0 }
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+}
+
+/// The replay of the run in progress, from its start until play goes back to
+/// the menu.
+#[derive(Resource)]
+pub struct ReplayRecorder(Replay);
+
+#[allow(clippy::too_many_arguments)]
+fn start_recording(
+    mut commands: Commands,
+    seed: Res<RunSeed>,
+    difficulty: Res<Difficulty>,
+    score_mode: Res<ScoreMode>,
+    border_damage: Option<Res<BorderDamage>>,
+    centered: Option<Res<CenteredPlayer>>,
+    play_area: Res<PlayArea>,
+    run_difficulty: Res<RunDifficulty>,
+    modifiers: Res<RunModifiers>,
+    tuning: Res<Tuning>,
+    wave_data: Option<Res<WaveData>>,
+    multi_shot: Res<MultiShot>,
+) {
+    commands.insert_resource(ReplayRecorder(Replay {
+        version: REPLAY_VERSION,
+        seed: seed.0,
+        difficulty: difficulty.0,
+        score_mode: *score_mode,
+        border_damage: border_damage.is_some(),
+        centered: centered.is_some(),
+        waves: wave_data.is_some(),
+        window_size: Some(play_area.size().into()),
+        custom_difficulty: run_difficulty.0,
+        tuning: tuning.clone(),
+        modifiers: modifiers.0.clone(),
+        multi_shot: Some(multi_shot.0),
+        frames: Vec::new(),
+    }));
+}
+
+/// Skips the menu and starts the recorded run straight away.
+fn start_playback(
+    playback: Res<ReplayPlayback>,
+    mut score_mode: ResMut<ScoreMode>,
+    mut multi_shot: ResMut<MultiShot>,
+    mut strategy: ResMut<TimeUpdateStrategy>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    *score_mode = playback.replay.score_mode;
+    multi_shot.0 = playback.replay.multi_shot.unwrap_or(1);
+    if let Some(frame) = playback.replay.frames.first() {
+        *strategy = TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(frame.dt));
+    }
+    game_state.set(GameState::Playing);
+}
+
+fn play_back_replay(
+    mut commands: Commands,
+    mut playback: ResMut<ReplayPlayback>,
+    mut input: ResMut<PlayerInput>,
+    mut strategy: ResMut<TimeUpdateStrategy>,
+) {
+    let Some(frame) = playback.replay.frames.get(playback.cursor).copied() else {
+        info!("Replay finished");
+        commands.remove_resource::<ReplayPlayback>();
+        *strategy = TimeUpdateStrategy::Automatic;
+        return;
+    };
+    *input = frame.input();
+    playback.cursor += 1;
+
+    // Time for the next frame is decided before it starts, so queue the
+    // recorded delta now.
+    if let Some(next) = playback.replay.frames.get(playback.cursor) {
+        *strategy = TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(next.dt));
+    }
+}
+
+fn stop_playback(mut commands: Commands, mut strategy: ResMut<TimeUpdateStrategy>) {
+    commands.remove_resource::<ReplayPlayback>();
+    *strategy = TimeUpdateStrategy::Automatic;
+}
+
+fn stop_recording(mut commands: Commands) {
+    commands.remove_resource::<ReplayRecorder>();
+}
+
+fn record_replay(
+    mut recorder: ResMut<ReplayRecorder>,
+    input: Res<PlayerInput>,
+    time: Res<Time<Real>>,
+) {
+    // Real time, since playback feeds it back in as such and the game clock
+    // is slowed on top of it the same way again.
+    recorder
+        .0
+        .frames
+        .push(ReplayFrame::new(time.delta_secs_f64(), &input));
+}
+
+fn write_replay(recorder: Res<ReplayRecorder>) {
+    let path = Path::new(REPLAY_DIR).join("latest.ron");
+    let result = fs::create_dir_all(REPLAY_DIR)
+        .map_err(|err| err.to_string())
+        .and_then(|()| ron::ser::to_string(&recorder.0).map_err(|err| err.to_string()))
+        .and_then(|contents| fs::write(&path, contents).map_err(|err| err.to_string()));
+    match result {
+        Ok(()) => info!("Saved replay to {}", path.display()),
+        Err(err) => error!("Failed to save replay to {}: {err}", path.display()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{ecs::system::RunSystemOnce, state::app::StatesPlugin, window::PrimaryWindow};
+
+    use super::*;
+    use crate::display::{DisplayPlugin, Resolution};
+
+    fn empty_replay() -> Replay {
+        Replay {
+            version: REPLAY_VERSION,
+            seed: 1,
+            difficulty: 1.0,
+            score_mode: ScoreMode::Normal,
+            border_damage: false,
+            centered: false,
+            waves: false,
+            window_size: None,
+            custom_difficulty: CustomDifficulty::default(),
+            tuning: Tuning::default(),
+            modifiers: Vec::new(),
+            multi_shot: None,
+            frames: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn frames_keep_the_input_they_recorded() {
+        let input = PlayerInput {
+            left: true,
+            fire: true,
+            focus: true,
+            aim: Vec2::from_angle(0.5),
+            assisted: true,
+            ..default()
+        };
+        let played_back = ReplayFrame::new(0.016, &input).input();
+        assert!(played_back.left && played_back.fire && played_back.focus);
+        assert!(played_back.assisted);
+        assert!(!played_back.right && !played_back.up && !played_back.down);
+        assert!((played_back.aim.to_angle() - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn playback_starts_with_the_recorded_multi_shot() {
+        let mut world = World::new();
+        world.insert_resource(ReplayPlayback::new(Replay {
+            multi_shot: Some(3),
+            ..empty_replay()
+        }));
+        world.init_resource::<MultiShot>();
+        world.insert_resource(ScoreMode::Normal);
+        world.insert_resource(TimeUpdateStrategy::Automatic);
+        world.init_resource::<NextState<GameState>>();
+        world.run_system_once(start_playback).unwrap();
+        assert_eq!(world.resource::<MultiShot>().0, 3);
+    }
+
+    #[test]
+    fn display_changes_wait_for_the_recorded_run_to_end() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin))
+            .init_state::<GameState>()
+            .add_plugins(DisplayPlugin)
+            .add_systems(OnEnter(GameState::Menu), stop_recording);
+        let window = app
+            .world_mut()
+            .spawn((Window::default(), PrimaryWindow))
+            .id();
+        let width = |app: &App| app.world().get::<Window>(window).unwrap().width();
+        let starting_width = width(&app);
+        app.insert_resource(ReplayRecorder(empty_replay()));
+        app.update();
+
+        *app.world_mut().resource_mut::<Resolution>() = Resolution::Medium;
+        app.update();
+        assert_eq!(width(&app), starting_width);
+
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Menu);
+        app.update();
+        assert_eq!(width(&app), Resolution::Medium.size().x);
+        assert!(!app.world().contains_resource::<ReplayRecorder>());
+    }
+}
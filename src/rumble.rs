@@ -0,0 +1,132 @@
+//! Gamepad rumble for getting hit and for nearby blasts.
+
+use std::time::Duration;
+
+use bevy::{
+    input::gamepad::{GamepadRumbleIntensity, GamepadRumbleRequest},
+    prelude::*,
+};
+
+use crate::{GameState, PlayerHit, particles::Explosion};
+
+pub struct RumblePlugin;
+
+impl Plugin for RumblePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(RumbleSettings {
+            enabled: true,
+            hit: Rumble {
+                strong: 1.0,
+                weak: 0.5,
+                duration: 0.2,
+            },
+            explosion: Rumble {
+                strong: 0.0,
+                weak: 0.4,
+                duration: 0.15,
+            },
+        })
+        .add_systems(
+            Update,
+            rumble_on_events.run_if(
+                in_state(GameState::Playing).and(|settings: Res<RumbleSettings>| settings.enabled),
+            ),
+        );
+    }
+}
+
+#[derive(Resource)]
+pub struct RumbleSettings {
+    pub enabled: bool,
+    /// Played when the player takes damage.
+    hit: Rumble,
+    /// Played for explosions with a blast radius.
+    explosion: Rumble,
+}
+
+/// Motor strengths from 0.0 to 1.0, and how long to run them in seconds.
+struct Rumble {
+    strong: f32,
+    weak: f32,
+    duration: f32,
+}
+
+fn rumble_on_events(
+    mut hits: EventReader<PlayerHit>,
+    mut explosions: EventReader<Explosion>,
+    settings: Res<RumbleSettings>,
+    gamepads: Query<Entity, With<Gamepad>>,
+    mut requests: EventWriter<GamepadRumbleRequest>,
+) {
+    let hit = hits.read().count() > 0;
+    let blast = explosions
+        .read()
+        .filter(|explosion| explosion.radius > 0.0)
+        .count()
+        > 0;
+    // A hit and a blast on the same frame only need the stronger of the two.
+    let rumble = if hit {
+        &settings.hit
+    } else if blast {
+        &settings.explosion
+    } else {
+        return;
+    };
+
+    for gamepad in gamepads.iter() {
+        requests.write(GamepadRumbleRequest::Add {
+            gamepad,
+            intensity: GamepadRumbleIntensity {
+                strong_motor: rumble.strong,
+                weak_motor: rumble.weak,
+            },
+            duration: Duration::from_secs_f32(rumble.duration),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::state::app::StatesPlugin;
+
+    use super::*;
+
+    /// Rumble requests sent since the last call, as (strong, weak) strengths.
+    fn drain_rumbles(app: &mut App) -> Vec<(f32, f32)> {
+        app.world_mut()
+            .resource_mut::<Events<GamepadRumbleRequest>>()
+            .drain()
+            .filter_map(|request| match request {
+                GamepadRumbleRequest::Add { intensity, .. } => {
+                    Some((intensity.strong_motor, intensity.weak_motor))
+                }
+                GamepadRumbleRequest::Stop { .. } => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn a_hit_rumbles_connected_pads_unless_rumble_is_off() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, RumblePlugin))
+            .add_event::<PlayerHit>()
+            .add_event::<Explosion>()
+            .add_event::<GamepadRumbleRequest>()
+            .insert_state(GameState::Playing);
+        let hit = |app: &mut App| {
+            app.world_mut().send_event(PlayerHit);
+            app.update();
+            drain_rumbles(app)
+        };
+
+        assert_eq!(hit(&mut app), []);
+
+        app.world_mut().spawn(Gamepad::default());
+        let settings = app.world().resource::<RumbleSettings>();
+        let expected = (settings.hit.strong, settings.hit.weak);
+        assert_eq!(hit(&mut app), [expected]);
+
+        app.world_mut().resource_mut::<RumbleSettings>().enabled = false;
+        assert_eq!(hit(&mut app), []);
+    }
+}
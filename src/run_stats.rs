@@ -0,0 +1,191 @@
+//! Counts kills, shots and hits over a run, and writes them out as a JSON
+//! report in `runs/` when it ends, for looking back over past play.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    BulletHit, Difficulty, EnemyKilled, GameState, IsBullet, RunMode, RunSeed, Score,
+    apply_explosions, combo::Combo, daily::Date, hud::SurvivalTime, practice::in_practice,
+    replay::ReplayPlayback, start_run, tutorial::in_tutorial,
+};
+
+const RUNS_DIR: &str = "runs";
+
+pub struct RunStatsPlugin;
+
+impl Plugin for RunStatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RunStats>()
+            .add_systems(OnExit(GameState::Menu), reset_run_stats.after(start_run))
+            .add_systems(
+                Update,
+                tally_run_stats
+                    .after(apply_explosions)
+                    .run_if(in_state(GameState::Playing)),
+            );
+        for state in [GameState::GameOver, GameState::Victory] {
+            app.add_systems(
+                OnEnter(state),
+                write_run_report.run_if(
+                    not(resource_exists::<ReplayPlayback>)
+                        .and(not(in_tutorial))
+                        .and(not(in_practice)),
+                ),
+            );
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct RunStats {
+    pub kills: u32,
+    /// Bullets fired, counting each one of a spread or multi-shot.
+    pub shots: u32,
+    /// Bullets that struck an enemy or a mine.
+    pub hits: u32,
+    pub best_multiplier: u32,
+}
+
+impl RunStats {
+    /// Fraction of shots that hit, or zero before the first shot.
+    pub fn accuracy(&self) -> f32 {
+        if self.shots == 0 {
+            0.0
+        } else {
+            self.hits as f32 / self.shots as f32
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct RunReport {
+    score: u32,
+    /// Seconds the run lasted.
+    time: f32,
+    kills: u32,
+    shots: u32,
+    hits: u32,
+    accuracy: f32,
+    max_multiplier: u32,
+    seed: u64,
+    /// `Difficulty` reached by the end of the run.
+    difficulty: f32,
+    date: String,
+}
+
+fn reset_run_stats(mut stats: ResMut<RunStats>) {
+    *stats = RunStats {
+        best_multiplier: 1,
+        ..default()
+    };
+}
+
+fn tally_run_stats(
+    mut stats: ResMut<RunStats>,
+    mut kills: EventReader<EnemyKilled>,
+    mut hits: EventReader<BulletHit>,
+    shot_query: Query<(), Added<IsBullet>>,
+    combo: Res<Combo>,
+) {
+    stats.kills += kills.read().count() as u32;
+    stats.hits += hits.read().count() as u32;
+    stats.shots += shot_query.iter().count() as u32;
+    stats.best_multiplier = stats.best_multiplier.max(combo.multiplier);
+}
+
+fn write_run_report(
+    stats: Res<RunStats>,
+    score: Res<Score>,
+    survival_time: Res<SurvivalTime>,
+    run_seed: Res<RunSeed>,
+    difficulty: Res<Difficulty>,
+    mode: Res<RunMode>,
+    combo: Res<Combo>,
+) {
+    let date = match *mode {
+        RunMode::Daily { date, .. } => date,
+        _ => Date::today_utc(),
+    };
+    let report = RunReport {
+        score: score.0,
+        time: survival_time.0,
+        kills: stats.kills,
+        shots: stats.shots,
+        hits: stats.hits,
+        accuracy: stats.accuracy(),
+        max_multiplier: stats.best_multiplier.max(combo.multiplier),
+        seed: run_seed.0,
+        difficulty: difficulty.0,
+        date: date.to_string(),
+    };
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_millis());
+    let path = report_path(Path::new(RUNS_DIR), timestamp);
+    let result = fs::create_dir_all(RUNS_DIR)
+        .map_err(|err| err.to_string())
+        .and_then(|()| serde_json::to_string_pretty(&report).map_err(|err| err.to_string()))
+        .and_then(|contents| fs::write(&path, contents).map_err(|err| err.to_string()));
+    match result {
+        Ok(()) => info!("Saved run report to {}", path.display()),
+        Err(err) => error!("Failed to save run report to {}: {err}", path.display()),
+    }
+}
+
+/// A report file in `dir` named after `timestamp`, in milliseconds, counting up
+/// from it if runs ended in the same millisecond.
+fn report_path(dir: &Path, timestamp: u128) -> PathBuf {
+    let path = dir.join(format!("run-{timestamp}.json"));
+    if !path.exists() {
+        return path;
+    }
+    (1..)
+        .map(|count| dir.join(format!("run-{timestamp}-{count}.json")))
+        .find(|path| !path.exists())
+        .expect("some count is free")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_report_round_trips_through_json() {
+        let report = RunReport {
+            score: 1234,
+            time: 95.5,
+            kills: 40,
+            shots: 120,
+            hits: 60,
+            accuracy: 0.5,
+            max_multiplier: 4,
+            seed: 0xdeadbeef,
+            difficulty: 1.75,
+            date: "2026-10-14".to_string(),
+        };
+        let json = serde_json::to_string_pretty(&report).unwrap();
+        assert_eq!(serde_json::from_str::<RunReport>(&json).unwrap(), report);
+    }
+
+    #[test]
+    fn report_path_counts_up_past_taken_names() {
+        let dir = std::env::temp_dir().join(format!("duckgame-runs-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let first = report_path(&dir, 42);
+        assert_eq!(first, dir.join("run-42.json"));
+        fs::write(&first, "{}").unwrap();
+        let second = report_path(&dir, 42);
+        assert_eq!(second, dir.join("run-42-1.json"));
+        fs::write(&second, "{}").unwrap();
+        assert_eq!(report_path(&dir, 42), dir.join("run-42-2.json"));
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,418 @@
+//! Quick save (F6) and quick load (F9) of a standard run in progress.
+//!
+//! Daily runs can't be saved, since they're meant to be played in one
+//! attempt, and neither can replays being played back. Loading throws away
+//! the replay being recorded, which would no longer match the run.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Difficulty, EnemyAtlas, EnemyHealth, GameRng, GameState, Health, Invincible, IsBullet, IsEnemy,
+    IsPlayer, Mine, MineAssets, RunMode, SEEKER_STRENGTH, Score, SeekPlayerY, Velocity,
+    boss::{Boss, boss},
+    enemy_fire::EnemyBullet,
+    fish, mine,
+    power_ups::{DropsPowerUp, WeaponPowerUp},
+    replay::{ReplayPlayback, ReplayRecorder},
+    tuning::Tuning,
+};
+
+const SAVE_PATH: &str = "savegame.ron";
+/// Bumped whenever the save format changes; saves from other versions are rejected.
+const SAVE_VERSION: u32 = 2;
+
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SaveFile(PathBuf::from(SAVE_PATH)))
+            .add_systems(
+                Update,
+                (save_game, load_game).run_if(
+                    in_state(GameState::Playing)
+                        .and(in_standard_run)
+                        .and(not(resource_exists::<ReplayPlayback>)),
+                ),
+            );
+    }
+}
+
+fn in_standard_run(mode: Res<RunMode>) -> bool {
+    matches!(*mode, RunMode::Standard)
+}
+
+/// Where the quick save is kept.
+#[derive(Resource)]
+struct SaveFile(PathBuf);
+
+#[derive(Serialize, Deserialize)]
+struct SaveGame {
+    version: u32,
+    score: u32,
+    difficulty: f32,
+    rng_state: u64,
+    player: SavedPlayer,
+    enemies: Vec<SavedEnemy>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedPlayer {
+    position: [f32; 2],
+    velocity: [f32; 2],
+    health: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct SavedEnemy {
+    kind: SavedEnemyKind,
+    position: [f32; 2],
+    velocity: [f32; 2],
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum SavedEnemyKind {
+    Fish { hp: u32 },
+    Seeker { hp: u32 },
+    Gunfish { hp: u32, drop: WeaponPowerUp },
+    Boss { hp: u32 },
+    Mine { hp: u32 },
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn save_game(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    save_file: Res<SaveFile>,
+    score: Res<Score>,
+    difficulty: Res<Difficulty>,
+    rng: Res<GameRng>,
+    player_query: Query<(&Transform, &Velocity, &Health), With<IsPlayer>>,
+    enemy_query: Query<
+        (
+            &Transform,
+            &Velocity,
+            &EnemyHealth,
+            Has<SeekPlayerY>,
+            Option<&DropsPowerUp>,
+            Has<Boss>,
+        ),
+        With<IsEnemy>,
+    >,
+    mine_query: Query<(&Transform, &Velocity, &Mine)>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F6) {
+        return;
+    }
+    let Ok((player_transform, player_vel, health)) = player_query.single() else {
+        return;
+    };
+
+    let enemy_entries = enemy_query
+        .iter()
+        .map(|(transform, vel, health, seeker, drop, boss)| SavedEnemy {
+            kind: match (seeker, drop, boss) {
+                (_, _, true) => SavedEnemyKind::Boss { hp: health.0 },
+                (_, Some(drop), _) => SavedEnemyKind::Gunfish {
+                    hp: health.0,
+                    drop: drop.0,
+                },
+                (true, None, _) => SavedEnemyKind::Seeker { hp: health.0 },
+                (false, None, _) => SavedEnemyKind::Fish { hp: health.0 },
+            },
+            position: transform.translation.truncate().to_array(),
+            velocity: vel.0.to_array(),
+        });
+    let mine_entries = mine_query.iter().map(|(transform, vel, mine)| SavedEnemy {
+        kind: SavedEnemyKind::Mine { hp: mine.hp },
+        position: transform.translation.truncate().to_array(),
+        velocity: vel.0.to_array(),
+    });
+    let save = SaveGame {
+        version: SAVE_VERSION,
+        score: score.0,
+        difficulty: difficulty.0,
+        rng_state: rng.0.get_seed(),
+        player: SavedPlayer {
+            position: player_transform.translation.truncate().to_array(),
+            velocity: player_vel.0.to_array(),
+            health: health.0,
+        },
+        enemies: enemy_entries.chain(mine_entries).collect(),
+    };
+
+    let path = &save_file.0;
+    let result = ron::ser::to_string_pretty(&save, ron::ser::PrettyConfig::default())
+        .map_err(|err| err.to_string())
+        .and_then(|contents| fs::write(path, contents).map_err(|err| err.to_string()));
+    match result {
+        Ok(()) => info!("Saved game to {}", path.display()),
+        Err(err) => error!("Failed to save game to {}: {err}", path.display()),
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn load_game(
+    mut commands: Commands,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    save_file: Res<SaveFile>,
+    enemy_atlas: Res<EnemyAtlas>,
+    mine_assets: Res<MineAssets>,
+    tuning: Res<Tuning>,
+    mut score: ResMut<Score>,
+    mut difficulty: ResMut<Difficulty>,
+    mut rng: ResMut<GameRng>,
+    mut player_query: Query<
+        (
+            Entity,
+            &mut Transform,
+            &mut Velocity,
+            &mut Health,
+            &mut Visibility,
+        ),
+        With<IsPlayer>,
+    >,
+    run_query: Query<Entity, Or<(With<IsEnemy>, With<IsBullet>, With<EnemyBullet>, With<Mine>)>>,
+) {
+    if !keyboard_input.just_pressed(KeyCode::F9) {
+        return;
+    }
+    let path = &save_file.0;
+    let save = match read_save(path) {
+        Ok(save) => save,
+        Err(err) => {
+            warn!("Couldn't load {}: {err}", path.display());
+            return;
+        }
+    };
+    let Ok((player, mut transform, mut vel, mut health, mut visibility)) =
+        player_query.single_mut()
+    else {
+        return;
+    };
+
+    for entity in run_query.iter() {
+        commands.entity(entity).despawn();
+    }
+    commands.remove_resource::<ReplayRecorder>();
+
+    score.0 = save.score;
+    difficulty.0 = save.difficulty;
+    rng.0.seed(save.rng_state);
+
+    transform.translation = Vec2::from_array(save.player.position).extend(transform.translation.z);
+    vel.0 = Vec2::from_array(save.player.velocity);
+    health.0 = save.player.health;
+    *visibility = Visibility::Inherited;
+    commands.entity(player).remove::<Invincible>();
+
+    for enemy in save.enemies {
+        let position = Vec2::from_array(enemy.position);
+        let velocity = Vec2::from_array(enemy.velocity);
+        match enemy.kind {
+            SavedEnemyKind::Fish { hp } => {
+                commands.spawn(fish(&enemy_atlas, &tuning, position, velocity, hp));
+            }
+            SavedEnemyKind::Seeker { hp } => {
+                commands.spawn((
+                    fish(&enemy_atlas, &tuning, position, velocity, hp),
+                    SeekPlayerY {
+                        strength: SEEKER_STRENGTH,
+                    },
+                ));
+            }
+            SavedEnemyKind::Gunfish { hp, drop } => {
+                commands.spawn((
+                    fish(&enemy_atlas, &tuning, position, velocity, hp),
+                    DropsPowerUp(drop),
+                ));
+            }
+            SavedEnemyKind::Boss { hp } => {
+                commands.spawn(boss(&enemy_atlas, position, hp));
+            }
+            SavedEnemyKind::Mine { hp } => {
+                commands.spawn(mine(&mine_assets, position, velocity, hp));
+            }
+        }
+    }
+    info!("Loaded game from {}", path.display());
+}
+
+fn read_save(path: &Path) -> Result<SaveGame, String> {
+    let contents = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let save: SaveGame =
+        ron::from_str(&contents).map_err(|err| format!("unreadable save ({err})"))?;
+    if save.version != SAVE_VERSION {
+        return Err(format!(
+            "save is version {}, expected {SAVE_VERSION}",
+            save.version
+        ));
+    }
+    Ok(save)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::state::app::StatesPlugin;
+
+    use super::*;
+
+    fn enemy_atlas() -> EnemyAtlas {
+        EnemyAtlas {
+            image: Handle::default(),
+            layout: Handle::default(),
+        }
+    }
+
+    fn run_app(path: &Path, score: u32) -> App {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, SavePlugin))
+            .insert_state(GameState::Playing)
+            .insert_resource(SaveFile(path.to_path_buf()))
+            .init_resource::<ButtonInput<KeyCode>>()
+            .insert_resource(RunMode::Standard)
+            .insert_resource(Score(score))
+            .insert_resource(Difficulty(1.0))
+            .insert_resource(GameRng(fastrand::Rng::with_seed(1)))
+            .insert_resource(Tuning::default())
+            .insert_resource(enemy_atlas())
+            .insert_resource(MineAssets {
+                mesh: Handle::default(),
+                material: Handle::default(),
+            });
+        app
+    }
+
+    fn press(app: &mut App, key: KeyCode) {
+        let mut input = app.world_mut().resource_mut::<ButtonInput<KeyCode>>();
+        input.press(key);
+        app.update();
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .clear();
+    }
+
+    fn player(app: &App) -> (Vec2, u32) {
+        let world = app.world();
+        let entity = world
+            .iter_entities()
+            .find(|entity| entity.contains::<IsPlayer>())
+            .unwrap();
+        (
+            entity.get::<Transform>().unwrap().translation.truncate(),
+            entity.get::<Health>().unwrap().0,
+        )
+    }
+
+    #[test]
+    fn a_save_loads_back_into_a_fresh_run() {
+        let path = std::env::temp_dir().join(format!("duckgame-save-{}.ron", std::process::id()));
+
+        let mut saved = run_app(&path, 42);
+        {
+            let atlas = enemy_atlas();
+            let tuning = Tuning::default();
+            let mine_assets = MineAssets {
+                mesh: Handle::default(),
+                material: Handle::default(),
+            };
+            let world = saved.world_mut();
+            world.spawn(fish(
+                &atlas,
+                &tuning,
+                Vec2::new(100.0, 10.0),
+                Vec2::NEG_X,
+                2,
+            ));
+            world.spawn((
+                fish(&atlas, &tuning, Vec2::new(120.0, 20.0), Vec2::NEG_X, 1),
+                SeekPlayerY { strength: 1.0 },
+            ));
+            world.spawn((
+                fish(&atlas, &tuning, Vec2::new(140.0, 30.0), Vec2::NEG_X, 3),
+                DropsPowerUp(WeaponPowerUp::Pierce),
+            ));
+            world.spawn(boss(&atlas, Vec2::new(200.0, 0.0), 17));
+            world.spawn(mine(&mine_assets, Vec2::new(50.0, -40.0), Vec2::NEG_X, 2));
+            world.spawn((
+                IsPlayer,
+                Transform::from_xyz(-150.0, 25.0, 0.0),
+                Velocity(Vec2::ZERO),
+                Health(2),
+                Visibility::default(),
+            ));
+        }
+        press(&mut saved, KeyCode::F6);
+
+        let mut loaded = run_app(&path, 0);
+        loaded.world_mut().spawn((
+            IsPlayer,
+            Transform::default(),
+            Velocity(Vec2::ZERO),
+            Health(3),
+            Visibility::Hidden,
+        ));
+        loaded
+            .world_mut()
+            .spawn((Transform::default(), EnemyBullet));
+        press(&mut loaded, KeyCode::F9);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded.world().resource::<Score>().0, 42);
+        assert_eq!(player(&loaded), (Vec2::new(-150.0, 25.0), 2));
+
+        let world = loaded.world_mut();
+        assert_eq!(
+            world
+                .query_filtered::<(), With<EnemyBullet>>()
+                .iter(world)
+                .count(),
+            0
+        );
+        let mut enemies: Vec<_> = world
+            .query::<(
+                &Transform,
+                &EnemyHealth,
+                Has<SeekPlayerY>,
+                Option<&DropsPowerUp>,
+                Has<Boss>,
+            )>()
+            .iter(world)
+            .map(|(transform, health, seeker, drop, boss)| {
+                (
+                    transform.translation.truncate(),
+                    health.0,
+                    seeker,
+                    drop.map(|drop| drop.0),
+                    boss,
+                )
+            })
+            .collect();
+        enemies.sort_by(|a, b| a.0.x.total_cmp(&b.0.x));
+        assert_eq!(
+            enemies,
+            [
+                (Vec2::new(100.0, 10.0), 2, false, None, false),
+                (Vec2::new(120.0, 20.0), 1, true, None, false),
+                (
+                    Vec2::new(140.0, 30.0),
+                    3,
+                    false,
+                    Some(WeaponPowerUp::Pierce),
+                    false
+                ),
+                (Vec2::new(200.0, 0.0), 17, false, None, true),
+            ]
+        );
+        let mines: Vec<_> = world
+            .query::<(&Transform, &Mine)>()
+            .iter(world)
+            .map(|(transform, mine)| (transform.translation.truncate(), mine.hp))
+            .collect();
+        assert_eq!(mines, [(Vec2::new(50.0, -40.0), 2)]);
+    }
+}
@@ -0,0 +1,160 @@
+//! High score tables, kept separately for standard runs and for each day's
+//! daily challenge, and persisted between launches.
+
+use std::fs;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    GameOverText, GameState, RunMode, Score, ScoreMode, daily::Date, debug::cheats_used,
+    modifiers::modifiers_in_play,
+};
+
+const SCORES_PATH: &str = "scores.ron";
+const LEADERBOARD_SIZE: usize = 10;
+const SHOWN_SCORES: usize = 5;
+
+pub struct ScoresPlugin;
+
+impl Plugin for ScoresPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Leaderboards::load());
+        // A run ends in either defeat or, in levels mode, victory.
+        for state in [GameState::GameOver, GameState::Victory] {
+            app.add_systems(
+                OnEnter(state),
+                (
+                    // Sudden death scores aren't comparable with standard ones.
+                    record_score.run_if(
+                        resource_equals(ScoreMode::Normal)
+                            .and(not(cheats_used))
+                            .and(not(modifiers_in_play)),
+                    ),
+                    display_leaderboard,
+                )
+                    .chain(),
+            );
+        }
+    }
+}
+
+#[derive(Resource, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Leaderboards {
+    standard: Vec<u32>,
+    time_attack: Vec<u32>,
+    levels: Vec<u32>,
+    daily: Vec<DailyLeaderboard>,
+    /// The last day a ranked daily challenge was started. Each day only the
+    /// first attempt counts.
+    pub last_daily_attempt: Option<Date>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct DailyLeaderboard {
+    date: Date,
+    scores: Vec<u32>,
+}
+
+impl Leaderboards {
+    fn load() -> Self {
+        let Ok(contents) = fs::read_to_string(SCORES_PATH) else {
+            return Self::default();
+        };
+        ron::from_str(&contents).unwrap_or_else(|err| {
+            warn!("Ignoring unreadable {SCORES_PATH}: {err}");
+            Self::default()
+        })
+    }
+
+    pub fn save(&self) {
+        let result = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|err| err.to_string())
+            .and_then(|contents| fs::write(SCORES_PATH, contents).map_err(|err| err.to_string()));
+        if let Err(err) = result {
+            error!("Failed to save high scores to {SCORES_PATH}: {err}");
+        }
+    }
+
+    fn scores(&self, mode: RunMode) -> &[u32] {
+        match mode {
+            RunMode::Standard => &self.standard,
+            RunMode::TimeAttack => &self.time_attack,
+            RunMode::Levels => &self.levels,
+            RunMode::Tutorial | RunMode::Practice => &[],
+            RunMode::Daily { date, .. } => self
+                .daily
+                .iter()
+                .find(|board| board.date == date)
+                .map(|board| board.scores.as_slice())
+                .unwrap_or_default(),
+        }
+    }
+
+    fn submit(&mut self, mode: RunMode, score: u32) {
+        let scores = match mode {
+            RunMode::Standard => &mut self.standard,
+            RunMode::TimeAttack => &mut self.time_attack,
+            RunMode::Levels => &mut self.levels,
+            RunMode::Daily { ranked: false, .. } | RunMode::Tutorial | RunMode::Practice => {
+                return;
+            }
+            RunMode::Daily { date, .. } => {
+                if let Some(index) = self.daily.iter().position(|board| board.date == date) {
+                    &mut self.daily[index].scores
+                } else {
+                    self.daily.push(DailyLeaderboard {
+                        date,
+                        scores: Vec::new(),
+                    });
+                    &mut self.daily.last_mut().unwrap().scores
+                }
+            }
+        };
+        let rank = scores.partition_point(|&existing| existing >= score);
+        scores.insert(rank, score);
+        scores.truncate(LEADERBOARD_SIZE);
+    }
+}
+
+fn record_score(score: Res<Score>, mode: Res<RunMode>, mut leaderboards: ResMut<Leaderboards>) {
+    leaderboards.submit(*mode, score.0);
+    leaderboards.save();
+}
+
+fn display_leaderboard(
+    mut commands: Commands,
+    mode: Res<RunMode>,
+    leaderboards: Res<Leaderboards>,
+) {
+    let mut lines = vec![match *mode {
+        RunMode::Standard | RunMode::Tutorial | RunMode::Practice => "High Scores".to_string(),
+        RunMode::TimeAttack => "Time Attack".to_string(),
+        RunMode::Levels => "Levels".to_string(),
+        RunMode::Daily { date, .. } => format!("Daily Challenge {date}"),
+    }];
+    lines.extend(
+        leaderboards
+            .scores(*mode)
+            .iter()
+            .take(SHOWN_SCORES)
+            .enumerate()
+            .map(|(rank, score)| format!("{}. {score}", rank + 1)),
+    );
+    if let RunMode::Daily { ranked: false, .. } = *mode {
+        lines.push("Unranked: already attempted today".to_string());
+    }
+    lines.push(String::new());
+    lines.push("Press Enter to return to the menu".to_string());
+
+    commands.spawn((
+        Text2d::new(lines.join("\n")),
+        TextFont {
+            font_size: 22.0,
+            ..default()
+        },
+        Transform::from_xyz(0.0, -140.0, 10.0),
+        GameOverText,
+    ));
+}
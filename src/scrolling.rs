@@ -0,0 +1,96 @@
+//! An endless-runner take on the controls, enabled with `--centered`.
+//!
+//! The duck can only stray a little way either side of the middle of the play
+//! area. Flying any further moves the field instead: every fish, mine, bullet
+//! and pickup scrolls past the other way, so the duck stays put while its
+//! movement still carries it toward or away from enemies. Moving up and down
+//! works as usual. Spawn warnings scroll with the field, and the enemies they
+//! announce come in where the warning has got to.
+//!
+//! The ghost duck isn't shown in this mode, since it replays movement across a
+//! field that doesn't scroll.
+
+use bevy::prelude::*;
+
+use crate::{
+    GameState, IsPlayer, Velocity, bounds::apply_bounds, update, waves::SpawnWarningIndicator,
+};
+
+/// How far either side of the middle the duck can move before the field
+/// scrolls instead.
+const CENTER_BAND: f32 = 40.0;
+
+pub struct ScrollingPlugin;
+
+impl Plugin for ScrollingPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FieldScroll>().add_systems(
+            Update,
+            scroll_field
+                .after(update)
+                .before(apply_bounds)
+                .run_if(in_state(GameState::Playing).and(resource_exists::<CenteredPlayer>)),
+        );
+    }
+}
+
+/// Keeps the duck near the middle and scrolls the field instead. Only present
+/// when enabled at launch.
+#[derive(Resource)]
+pub struct CenteredPlayer;
+
+/// How far the field has scrolled along x since launch, summing every shift.
+#[derive(Resource, Default)]
+pub struct FieldScroll(pub f32);
+
+#[allow(clippy::type_complexity)]
+fn scroll_field(
+    mut player_query: Query<&mut Transform, With<IsPlayer>>,
+    mut field_query: Query<
+        &mut Transform,
+        (
+            Or<(With<Velocity>, With<SpawnWarningIndicator>)>,
+            Without<IsPlayer>,
+        ),
+    >,
+    mut scroll: ResMut<FieldScroll>,
+) {
+    let Ok(mut player_transform) = player_query.single_mut() else {
+        return;
+    };
+    let x = player_transform.translation.x;
+    let excess = x - x.clamp(-CENTER_BAND, CENTER_BAND);
+    if excess == 0.0 {
+        return;
+    }
+    player_transform.translation.x -= excess;
+    for mut transform in field_query.iter_mut() {
+        transform.translation.x -= excess;
+    }
+    scroll.0 -= excess;
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    #[test]
+    fn straying_past_the_band_scrolls_the_field() {
+        let mut world = World::new();
+        world.init_resource::<FieldScroll>();
+        let player = world
+            .spawn((Transform::from_xyz(CENTER_BAND + 30.0, 50.0, 0.0), IsPlayer))
+            .id();
+        let fish = world
+            .spawn((Transform::from_xyz(200.0, 10.0, 0.0), Velocity::default()))
+            .id();
+        world.run_system_once(scroll_field).unwrap();
+
+        let position = |entity| world.get::<Transform>(entity).unwrap().translation;
+        assert_eq!(position(player), Vec3::new(CENTER_BAND, 50.0, 0.0));
+        assert_eq!(position(fish), Vec3::new(170.0, 10.0, 0.0));
+        assert_eq!(world.resource::<FieldScroll>().0, -30.0);
+    }
+}
@@ -0,0 +1,301 @@
+//! Player settings that persist between launches.
+//!
+//! Everything lives in one `Settings` file. At startup it's applied to the
+//! resources that own each setting, and from then on any change to those is
+//! written back shortly after it's made. Fields missing from the file, such as
+//! settings added since it was written, take their defaults.
+
+use std::fs;
+
+use bevy::{audio::Volume, prelude::*, window::PrimaryWindow};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    aim::{AimAssist, AimLine, AimMode, MouseFire},
+    camera::CameraZoom,
+    custom_difficulty::CustomDifficulty,
+    display::{DisplayMode, FixedWindowSize, Resolution, configure_window},
+    radar::Radar,
+    rumble::RumbleSettings,
+    transition::TransitionDuration,
+};
+
+const SETTINGS_PATH: &str = "settings.ron";
+/// Time to wait after the last change before writing, so a burst of toggles
+/// is written once.
+const SAVE_DELAY: f32 = 0.5;
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        let settings = Settings::load();
+        app.insert_resource(AutoFire(settings.auto_fire))
+            .insert_resource(ReduceMotion(settings.reduce_motion))
+            .insert_resource(Captions(settings.captions))
+            .insert_resource(settings)
+            .init_resource::<SettingsState>()
+            .add_systems(Startup, apply_settings)
+            .add_systems(
+                Update,
+                (toggle_auto_fire, track_settings, save_settings).chain(),
+            )
+            .add_systems(Last, flush_settings);
+    }
+}
+
+/// Fire continuously while set, rather than once per press. Toggled with F.
+#[derive(Resource)]
+pub struct AutoFire(pub bool);
+
+/// Tones down flashing, blinking and particle motion for players sensitive to
+/// it. Purely cosmetic; gameplay is unchanged.
+#[derive(Resource)]
+pub struct ReduceMotion(pub bool);
+
+/// Shows captions for important sounds.
+#[derive(Resource)]
+pub struct Captions(pub bool);
+
+/// The settings as last saved, or about to be.
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Volume to play at when not muted, from 0.0 to 1.0.
+    pub volume: f32,
+    pub muted: bool,
+    pub rumble: bool,
+    pub aim_mode: AimMode,
+    pub aim_assist: bool,
+    pub mouse_fire: MouseFire,
+    pub aim_line: bool,
+    pub auto_fire: bool,
+    pub resolution: Resolution,
+    pub display_mode: DisplayMode,
+    pub reduce_motion: bool,
+    pub captions: bool,
+    pub radar: bool,
+    /// How far the camera is zoomed in, as a `CameraZoom`.
+    pub zoom: f32,
+    pub custom_difficulty: CustomDifficulty,
+    /// Seconds each half of a fade between screens takes.
+    pub transition_duration: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            muted: false,
+            rumble: true,
+            aim_mode: AimMode::default(),
+            aim_assist: true,
+            mouse_fire: MouseFire::default(),
+            aim_line: false,
+            auto_fire: false,
+            resolution: Resolution::default(),
+            display_mode: DisplayMode::default(),
+            reduce_motion: false,
+            captions: false,
+            radar: false,
+            zoom: 1.0,
+            custom_difficulty: CustomDifficulty::default(),
+            transition_duration: TransitionDuration::default().0,
+        }
+    }
+}
+
+impl Settings {
+    fn load() -> Self {
+        let Ok(contents) = fs::read_to_string(SETTINGS_PATH) else {
+            return Self::default();
+        };
+        ron::from_str(&contents).unwrap_or_else(|err| {
+            warn!("Ignoring unreadable {SETTINGS_PATH}: {err}");
+            Self::default()
+        })
+    }
+
+    fn save(&self) {
+        let result = ron::ser::to_string_pretty(self, ron::ser::PrettyConfig::default())
+            .map_err(|err| err.to_string())
+            .and_then(|contents| fs::write(SETTINGS_PATH, contents).map_err(|err| err.to_string()));
+        if let Err(err) = result {
+            error!("Failed to save settings to {SETTINGS_PATH}: {err}");
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct SettingsState {
+    /// Counts down to writing changed settings.
+    pending_save: Option<Timer>,
+    /// Muted for this launch only by `--mute`, until the player unmutes.
+    launch_muted: bool,
+}
+
+#[allow(clippy::too_many_arguments)]
+fn apply_settings(
+    settings: Res<Settings>,
+    mut state: ResMut<SettingsState>,
+    mut global_volume: ResMut<GlobalVolume>,
+    mut rumble: ResMut<RumbleSettings>,
+    mut aim_mode: ResMut<AimMode>,
+    mut aim_assist: ResMut<AimAssist>,
+    mut mouse_fire: ResMut<MouseFire>,
+    mut aim_line: ResMut<AimLine>,
+    mut radar: ResMut<Radar>,
+    mut resolution: ResMut<Resolution>,
+    mut display_mode: ResMut<DisplayMode>,
+    mut zoom: ResMut<CameraZoom>,
+    mut custom_difficulty: ResMut<CustomDifficulty>,
+    mut transition_duration: ResMut<TransitionDuration>,
+    mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+    fixed_window_size: Option<Res<FixedWindowSize>>,
+) {
+    // `--mute` has already silenced this launch, so leave it that way.
+    if global_volume.volume.to_linear() == 0.0 {
+        state.launch_muted = !settings.muted;
+    } else {
+        let volume = if settings.muted { 0.0 } else { settings.volume };
+        global_volume.volume = Volume::Linear(volume);
+    }
+    rumble.enabled = settings.rumble;
+    *aim_mode = settings.aim_mode;
+    aim_assist.enabled = settings.aim_assist;
+    *mouse_fire = settings.mouse_fire;
+    aim_line.0 = settings.aim_line;
+    radar.0 = settings.radar;
+    *resolution = settings.resolution;
+    *display_mode = settings.display_mode;
+    zoom.0 = settings.zoom;
+    *custom_difficulty = settings.custom_difficulty;
+    transition_duration.0 = settings.transition_duration;
+    if let (None, Ok(mut window)) = (fixed_window_size, window_query.single_mut()) {
+        configure_window(&mut window, *resolution, *display_mode);
+    }
+}
+
+fn toggle_auto_fire(keyboard_input: Res<ButtonInput<KeyCode>>, mut auto_fire: ResMut<AutoFire>) {
+    if keyboard_input.just_pressed(KeyCode::KeyF) {
+        auto_fire.0 = !auto_fire.0;
+    }
+}
+
+/// Picks up changes to any setting and schedules a save.
+#[allow(clippy::too_many_arguments)]
+fn track_settings(
+    mut settings: ResMut<Settings>,
+    mut state: ResMut<SettingsState>,
+    global_volume: Res<GlobalVolume>,
+    rumble: Res<RumbleSettings>,
+    // Grouped to stay within the number of parameters a system can take.
+    (aim_mode, aim_assist, mouse_fire, aim_line): (
+        Res<AimMode>,
+        Res<AimAssist>,
+        Res<MouseFire>,
+        Res<AimLine>,
+    ),
+    auto_fire: Res<AutoFire>,
+    resolution: Res<Resolution>,
+    display_mode: Res<DisplayMode>,
+    reduce_motion: Res<ReduceMotion>,
+    captions: Res<Captions>,
+    radar: Res<Radar>,
+    zoom: Res<CameraZoom>,
+    custom_difficulty: Res<CustomDifficulty>,
+    transition_duration: Res<TransitionDuration>,
+) {
+    let volume = global_volume.volume.to_linear();
+    let muted = volume == 0.0;
+    state.launch_muted &= muted;
+    let current = Settings {
+        // Muting keeps the volume to come back to.
+        volume: if muted { settings.volume } else { volume },
+        muted: muted && !state.launch_muted,
+        rumble: rumble.enabled,
+        aim_mode: *aim_mode,
+        aim_assist: aim_assist.enabled,
+        mouse_fire: *mouse_fire,
+        aim_line: aim_line.0,
+        auto_fire: auto_fire.0,
+        resolution: *resolution,
+        display_mode: *display_mode,
+        reduce_motion: reduce_motion.0,
+        captions: captions.0,
+        radar: radar.0,
+        zoom: zoom.0,
+        custom_difficulty: *custom_difficulty,
+        transition_duration: transition_duration.0,
+    };
+    if current != *settings {
+        *settings = current;
+        state.pending_save = Some(Timer::from_seconds(SAVE_DELAY, TimerMode::Once));
+    }
+}
+
+fn save_settings(settings: Res<Settings>, mut state: ResMut<SettingsState>, time: Res<Time>) {
+    let Some(timer) = &mut state.pending_save else {
+        return;
+    };
+    if timer.tick(time.delta()).finished() {
+        settings.save();
+        state.pending_save = None;
+    }
+}
+
+/// Writes a change still waiting on `SAVE_DELAY` when the game closes.
+fn flush_settings(
+    mut exits: EventReader<AppExit>,
+    settings: Res<Settings>,
+    mut state: ResMut<SettingsState>,
+) {
+    if exits.read().count() > 0 && state.pending_save.take().is_some() {
+        settings.save();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aim::FireButton;
+
+    #[test]
+    fn settings_survive_being_written_and_read_back() {
+        let settings = Settings {
+            volume: 0.3,
+            muted: true,
+            aim_mode: AimMode::Mouse,
+            aim_assist: false,
+            mouse_fire: MouseFire {
+                button: FireButton::Right,
+                hold: true,
+            },
+            resolution: Resolution::Large,
+            display_mode: DisplayMode::Borderless,
+            radar: true,
+            zoom: 1.5,
+            transition_duration: 0.0,
+            ..default()
+        };
+        let contents =
+            ron::ser::to_string_pretty(&settings, ron::ser::PrettyConfig::default()).unwrap();
+        assert_eq!(ron::from_str::<Settings>(&contents).unwrap(), settings);
+    }
+
+    #[test]
+    fn settings_missing_from_an_older_file_take_their_defaults() {
+        // Written before radar, zoom and the rest existed.
+        let settings: Settings =
+            ron::from_str("(volume: 0.5, muted: true, rumble: false)").unwrap();
+        assert_eq!(
+            settings,
+            Settings {
+                volume: 0.5,
+                muted: true,
+                rumble: false,
+                ..default()
+            }
+        );
+    }
+}
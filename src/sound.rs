@@ -0,0 +1,287 @@
+//! Music and sound effects.
+//!
+//! The music is two looping layers, a calm one and an intense one, crossfaded
+//! by how far `Difficulty` has climbed. Sound effects are played at slightly
+//! varied speeds so repeats don't sound mechanical, and panned toward where
+//! they happened on screen. A critical hit rings out over the usual pop.
+
+use std::f32::consts::FRAC_PI_2;
+
+use bevy::{audio::Volume, prelude::*};
+
+use crate::{
+    BulletHit, Difficulty, EnemyKilled, GameRng, GameState, apply_explosions, play_area::PlayArea,
+    setup,
+};
+
+/// `Difficulty` at which the intense layer has fully taken over.
+const FULL_INTENSITY_DIFFICULTY: f32 = 2.5;
+/// How quickly the intensity follows its target, in full fades per second.
+const MUSIC_FADE_RATE: f32 = 0.25;
+/// Sound effects play at up to this much faster or slower than normal.
+const PITCH_VARIATION: f32 = 0.08;
+/// Distance between the listener's ears. Panned sounds are placed between
+/// them, so a pan of 1.0 plays right at the right ear.
+const EAR_GAP: f32 = 2.0;
+
+pub struct SoundPlugin;
+
+impl Plugin for SoundPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MusicIntensity>()
+            .add_systems(
+                Startup,
+                (load_sounds, start_music, add_listener.after(setup)),
+            )
+            .add_systems(Update, (update_music_intensity, crossfade_music).chain())
+            .add_systems(
+                Update,
+                (play_kill_sounds, play_crit_sounds)
+                    .chain()
+                    .after(apply_explosions)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+#[derive(Resource)]
+pub struct SoundAssets {
+    pub shot: Handle<AudioSource>,
+    pub pop: Handle<AudioSource>,
+    pub crit: Handle<AudioSource>,
+    pub streak: Handle<AudioSource>,
+    pub boss_sting: Handle<AudioSource>,
+}
+
+#[derive(Component)]
+enum MusicLayer {
+    Calm,
+    Intense,
+}
+
+/// How much of the intense layer is playing, from 0.0 to 1.0.
+#[derive(Resource, Default)]
+struct MusicIntensity(f32);
+
+fn load_sounds(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(SoundAssets {
+        shot: asset_server.load("audio/shot.ogg"),
+        pop: asset_server.load("audio/pop.ogg"),
+        crit: asset_server.load("audio/crit.ogg"),
+        streak: asset_server.load("audio/streak.ogg"),
+        boss_sting: asset_server.load("audio/boss_sting.ogg"),
+    });
+}
+
+/// A one-shot sound effect at a randomly varied speed. The variation is drawn
+/// from `GameRng`, so callers must run in a fixed order with its other users.
+pub fn sound_effect(
+    sound: &Handle<AudioSource>,
+    rng: &mut GameRng,
+) -> (AudioPlayer, PlaybackSettings) {
+    let speed = 1.0 + (rng.0.f32() * 2.0 - 1.0) * PITCH_VARIATION;
+    (
+        AudioPlayer::new(sound.clone()),
+        PlaybackSettings::DESPAWN.with_speed(speed),
+    )
+}
+
+/// Like `sound_effect`, but panned from -1.0 (left) to 1.0 (right).
+pub fn panned_sound_effect(
+    sound: &Handle<AudioSource>,
+    rng: &mut GameRng,
+    pan: f32,
+) -> impl Bundle {
+    let (player, settings) = sound_effect(sound, rng);
+    (
+        player,
+        settings.with_spatial(true),
+        Transform::from_xyz(pan * EAR_GAP / 2.0, 0.0, 0.0),
+    )
+}
+
+/// How far right of center `x` is, from -1.0 at the left edge to 1.0 at the right.
+pub fn pan(x: f32, play_area: &PlayArea) -> f32 {
+    (x / play_area.half_width).clamp(-1.0, 1.0)
+}
+
+fn add_listener(mut commands: Commands, camera_query: Query<Entity, With<Camera2d>>) {
+    for camera in camera_query.iter() {
+        commands
+            .entity(camera)
+            .insert(SpatialListener::new(EAR_GAP));
+    }
+}
+
+fn start_music(mut commands: Commands, asset_server: Res<AssetServer>) {
+    for (layer, path) in [
+        (MusicLayer::Calm, "audio/music_calm.ogg"),
+        (MusicLayer::Intense, "audio/music_intense.ogg"),
+    ] {
+        commands.spawn((
+            AudioPlayer::new(asset_server.load(path)),
+            PlaybackSettings::LOOP.with_volume(Volume::Linear(0.0)),
+            layer,
+        ));
+    }
+}
+
+/// How much of the intense layer `difficulty` calls for.
+fn intensity_target(difficulty: f32) -> f32 {
+    ((difficulty - 1.0) / (FULL_INTENSITY_DIFFICULTY - 1.0)).clamp(0.0, 1.0)
+}
+
+/// Eases the intensity toward what the current difficulty calls for. Outside
+/// of a run the music settles back to the calm layer.
+fn update_music_intensity(
+    mut intensity: ResMut<MusicIntensity>,
+    difficulty: Res<Difficulty>,
+    game_state: Res<State<GameState>>,
+    time: Res<Time>,
+) {
+    let target = if *game_state.get() == GameState::Playing {
+        intensity_target(difficulty.0)
+    } else {
+        0.0
+    };
+    let step = MUSIC_FADE_RATE * time.delta_secs();
+    intensity.0 += (target - intensity.0).clamp(-step, step);
+}
+
+fn crossfade_music(
+    intensity: Res<MusicIntensity>,
+    global_volume: Res<GlobalVolume>,
+    mut query: Query<(&MusicLayer, &mut AudioSink)>,
+) {
+    // Equal-power crossfade, so the mix doesn't dip in loudness halfway.
+    let angle = intensity.0 * FRAC_PI_2;
+    for (layer, mut sink) in query.iter_mut() {
+        let volume = match layer {
+            MusicLayer::Calm => angle.cos(),
+            MusicLayer::Intense => angle.sin(),
+        };
+        sink.set_volume(Volume::Linear(volume * global_volume.volume.to_linear()));
+    }
+}
+
+fn play_kill_sounds(
+    mut commands: Commands,
+    mut kills: EventReader<EnemyKilled>,
+    sounds: Res<SoundAssets>,
+    play_area: Res<PlayArea>,
+    mut rng: ResMut<GameRng>,
+) {
+    for kill in kills.read() {
+        commands.spawn(panned_sound_effect(
+            &sounds.pop,
+            &mut rng,
+            pan(kill.position.x, &play_area),
+        ));
+    }
+}
+
+fn play_crit_sounds(
+    mut commands: Commands,
+    mut hits: EventReader<BulletHit>,
+    sounds: Res<SoundAssets>,
+    play_area: Res<PlayArea>,
+    mut rng: ResMut<GameRng>,
+) {
+    for hit in hits.read().filter(|hit| hit.crit) {
+        commands.spawn(panned_sound_effect(
+            &sounds.crit,
+            &mut rng,
+            pan(hit.position.x, &play_area),
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    #[test]
+    fn the_intense_layer_rises_with_difficulty() {
+        let targets = [1.0, 1.5, 2.0, 2.5].map(intensity_target);
+        assert!(targets.is_sorted());
+        assert_eq!(targets[0], 0.0);
+        assert_eq!(targets[3], 1.0);
+        assert_eq!(intensity_target(4.0), 1.0);
+    }
+
+    #[test]
+    fn the_intensity_fades_rather_than_jumps() {
+        let mut world = World::new();
+        world.init_resource::<MusicIntensity>();
+        world.insert_resource(Difficulty(FULL_INTENSITY_DIFFICULTY));
+        world.insert_resource(State::new(GameState::Playing));
+        let mut time = Time::<()>::default();
+        time.advance_by(Duration::from_secs(1));
+        world.insert_resource(time);
+        world.run_system_once(update_music_intensity).unwrap();
+
+        assert_eq!(world.resource::<MusicIntensity>().0, MUSIC_FADE_RATE);
+    }
+
+    #[test]
+    fn repeated_effects_vary_subtly_in_speed() {
+        let mut rng = GameRng(fastrand::Rng::with_seed(3));
+        let speeds: Vec<f32> = (0..20)
+            .map(|_| sound_effect(&Handle::default(), &mut rng).1.speed)
+            .collect();
+        assert!(
+            speeds
+                .iter()
+                .all(|speed| (speed - 1.0).abs() <= PITCH_VARIATION)
+        );
+        assert!(speeds.windows(2).any(|pair| pair[0] != pair[1]));
+    }
+
+    #[test]
+    fn sounds_on_the_right_pan_right() {
+        let play_area = PlayArea {
+            half_width: 400.0,
+            half_height: 300.0,
+        };
+        assert!(pan(150.0, &play_area) > 0.0);
+        assert!(pan(-150.0, &play_area) < 0.0);
+        assert_eq!(pan(0.0, &play_area), 0.0);
+        assert_eq!(pan(1000.0, &play_area), 1.0);
+    }
+
+    #[test]
+    fn only_critical_hits_ring_out() {
+        let mut world = World::new();
+        world.init_resource::<Events<BulletHit>>();
+        world.insert_resource(SoundAssets {
+            shot: Handle::default(),
+            pop: Handle::default(),
+            crit: Handle::default(),
+            streak: Handle::default(),
+            boss_sting: Handle::default(),
+        });
+        world.insert_resource(PlayArea {
+            half_width: 400.0,
+            half_height: 300.0,
+        });
+        world.insert_resource(GameRng(fastrand::Rng::with_seed(1)));
+        for crit in [false, true, false] {
+            world.send_event(BulletHit {
+                position: Vec2::new(200.0, 0.0),
+                damage: 1,
+                crit,
+            });
+        }
+        world.run_system_once(play_crit_sounds).unwrap();
+
+        let mut sounds = world.query::<(&AudioPlayer, &Transform)>();
+        let [(_, transform)] = sounds.iter(&world).collect::<Vec<_>>()[..] else {
+            panic!("expected one crit sound");
+        };
+        assert!(transform.translation.x > 0.0);
+    }
+}
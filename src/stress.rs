@@ -0,0 +1,183 @@
+//! A benchmark mode for profiling the collision and rendering paths under
+//! load, started with `--stress <n>`.
+//!
+//! Every run keeps `n` fish and `n` of the player's bullets on the field,
+//! topping them up as they're destroyed or fly off, and logs frame times once a
+//! second. The player can't be hurt and the run isn't recorded, so the load
+//! carries on until the game is quit. F5's profiler breaks down where the time
+//! goes.
+
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+
+use crate::{
+    BulletAssets, EnemyAtlas, FISH_HP, GameState, IsBullet, IsEnemy, bullet, fish,
+    play_area::PlayArea, tuning::Tuning,
+};
+
+const REPORT_INTERVAL: f32 = 1.0;
+
+pub struct StressPlugin;
+
+impl Plugin for StressPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (top_up_stress_load, report_frame_times).run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+#[derive(Resource)]
+pub struct StressTest {
+    /// Fish, and separately bullets, kept on the field.
+    count: usize,
+    report: Timer,
+    /// Frame times since the last report, in seconds.
+    frames: Vec<f32>,
+}
+
+impl StressTest {
+    pub fn new(count: usize) -> Self {
+        Self {
+            count,
+            report: Timer::from_seconds(REPORT_INTERVAL, TimerMode::Repeating),
+            frames: Vec::new(),
+        }
+    }
+}
+
+/// Draws from `fastrand` rather than `GameRng`, since these runs are never
+/// replayed.
+#[allow(clippy::too_many_arguments)]
+fn top_up_stress_load(
+    mut commands: Commands,
+    stress: Res<StressTest>,
+    enemy_atlas: Res<EnemyAtlas>,
+    bullet_assets: Res<BulletAssets>,
+    play_area: Res<PlayArea>,
+    tuning: Res<Tuning>,
+    enemy_query: Query<(), With<IsEnemy>>,
+    bullet_query: Query<(), With<IsBullet>>,
+) {
+    let random_position = || {
+        Vec2::new(
+            (fastrand::f32() * 2.0 - 1.0) * play_area.half_width,
+            (fastrand::f32() * 2.0 - 1.0) * play_area.half_height,
+        )
+    };
+    for _ in enemy_query.iter().count()..stress.count {
+        let velocity = Vec2::new(-tuning.fish_speed.0, 0.0);
+        commands.spawn(fish(
+            &enemy_atlas,
+            &tuning,
+            random_position(),
+            velocity,
+            FISH_HP,
+        ));
+    }
+    for _ in bullet_query.iter().count()..stress.count {
+        let direction = Vec2::from_angle(fastrand::f32() * TAU);
+        commands.spawn(bullet(
+            &bullet_assets,
+            &tuning,
+            random_position(),
+            direction,
+        ));
+    }
+}
+
+fn report_frame_times(
+    mut stress: ResMut<StressTest>,
+    enemy_query: Query<(), With<IsEnemy>>,
+    bullet_query: Query<(), With<IsBullet>>,
+    time: Res<Time<Real>>,
+) {
+    stress.frames.push(time.delta_secs());
+    if !stress.report.tick(time.delta()).just_finished() {
+        return;
+    }
+    let frames = std::mem::take(&mut stress.frames);
+    let average = frames.iter().sum::<f32>() / frames.len() as f32;
+    let worst = frames.iter().copied().fold(0.0, f32::max);
+    info!(
+        "Stress test: {} enemies, {} bullets, {:.2} ms average frame, {:.2} ms worst",
+        enemy_query.iter().count(),
+        bullet_query.iter().count(),
+        average * 1000.0,
+        worst * 1000.0,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::{
+        BulletHit, CRIT_BONUS_POINTS, CRIT_MULTIPLIER, CritConfig, EnemyKilled, FISH_POINTS,
+        GameRng, RunModifiers, Score, check_for_collisions,
+        combo::Combo,
+        particles::Explosion,
+        popups::{DamagePopup, ScorePopup},
+        profiler::Profiler,
+    };
+
+    const COUNT: usize = 200;
+
+    fn count<F: bevy::ecs::query::QueryFilter>(world: &mut World) -> usize {
+        world.query_filtered::<(), F>().iter(world).count()
+    }
+
+    #[test]
+    fn the_field_is_kept_full_and_collisions_still_resolve() {
+        let mut world = World::new();
+        world.insert_resource(StressTest::new(COUNT));
+        world.insert_resource(EnemyAtlas {
+            image: Handle::default(),
+            layout: Handle::default(),
+        });
+        world.insert_resource(BulletAssets {
+            mesh: Handle::default(),
+            material: Handle::default(),
+        });
+        // Small enough that plenty of bullets land on a fish.
+        world.insert_resource(PlayArea {
+            half_width: 100.0,
+            half_height: 100.0,
+        });
+        world.insert_resource(Score(0));
+        world.insert_resource(GameRng(fastrand::Rng::with_seed(0)));
+        world.insert_resource(CritConfig {
+            chance: 0.0,
+            multiplier: CRIT_MULTIPLIER,
+            bonus_points: CRIT_BONUS_POINTS,
+        });
+        world.init_resource::<Tuning>();
+        world.init_resource::<Combo>();
+        world.init_resource::<RunModifiers>();
+        world.init_resource::<Profiler>();
+        world.init_resource::<Events<Explosion>>();
+        world.init_resource::<Events<ScorePopup>>();
+        world.init_resource::<Events<DamagePopup>>();
+        world.init_resource::<Events<EnemyKilled>>();
+        world.init_resource::<Events<BulletHit>>();
+
+        world.run_system_once(top_up_stress_load).unwrap();
+        assert_eq!(count::<With<IsEnemy>>(&mut world), COUNT);
+        assert_eq!(count::<With<IsBullet>>(&mut world), COUNT);
+
+        world.run_system_once(check_for_collisions).unwrap();
+        let hits = world.resource::<Events<BulletHit>>().len();
+        assert!(hits > 0);
+        // Every fish dies to one bullet, and every bullet stops at one fish.
+        assert_eq!(world.resource::<Score>().0, hits as u32 * FISH_POINTS);
+        assert_eq!(count::<With<IsEnemy>>(&mut world), COUNT - hits);
+        assert_eq!(count::<With<IsBullet>>(&mut world), COUNT - hits);
+
+        world.run_system_once(top_up_stress_load).unwrap();
+        assert_eq!(count::<With<IsEnemy>>(&mut world), COUNT);
+        assert_eq!(count::<With<IsBullet>>(&mut world), COUNT);
+    }
+}
@@ -0,0 +1,102 @@
+//! Time attack: score as much as possible before the clock runs out.
+
+use bevy::prelude::*;
+
+use crate::{GameState, RunMode, game_time::GameTime, start_run};
+
+const TIME_ATTACK_DURATION: f32 = 60.0;
+
+pub struct TimeAttackPlugin;
+
+impl Plugin for TimeAttackPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnExit(GameState::Menu),
+            start_time_attack.after(start_run).run_if(in_time_attack),
+        )
+        .add_systems(OnEnter(GameState::Menu), despawn_time_attack)
+        .add_systems(
+            Update,
+            tick_time_attack
+                .run_if(in_state(GameState::Playing).and(resource_exists::<TimeAttack>)),
+        );
+    }
+}
+
+fn in_time_attack(mode: Res<RunMode>) -> bool {
+    matches!(*mode, RunMode::TimeAttack)
+}
+
+/// Time left in a time attack run. Only present during one.
+#[derive(Resource)]
+pub struct TimeAttack {
+    remaining: Timer,
+}
+
+impl TimeAttack {
+    pub fn remaining_secs(&self) -> f32 {
+        self.remaining.remaining_secs()
+    }
+}
+
+fn start_time_attack(mut commands: Commands) {
+    commands.insert_resource(TimeAttack {
+        remaining: Timer::from_seconds(TIME_ATTACK_DURATION, TimerMode::Once),
+    });
+}
+
+fn despawn_time_attack(mut commands: Commands) {
+    commands.remove_resource::<TimeAttack>();
+}
+
+fn tick_time_attack(
+    mut time_attack: ResMut<TimeAttack>,
+    mut game_state: ResMut<NextState<GameState>>,
+    time: Res<GameTime>,
+) {
+    if time_attack.remaining.tick(time.delta()).finished() {
+        game_state.set(GameState::GameOver);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{state::app::StatesPlugin, time::TimeUpdateStrategy};
+
+    use super::*;
+    use crate::game_time::GameTimePlugin;
+
+    #[test]
+    fn the_run_ends_when_the_clock_runs_out() {
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            StatesPlugin,
+            GameTimePlugin,
+            TimeAttackPlugin,
+        ))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+            100,
+        )))
+        .insert_state(GameState::Playing)
+        .insert_resource(TimeAttack {
+            remaining: Timer::from_seconds(0.5, TimerMode::Once),
+        });
+        let state = |app: &App| app.world().resource::<State<GameState>>().get().clone();
+        let remaining = |app: &App| app.world().resource::<TimeAttack>().remaining_secs();
+
+        for _ in 0..10 {
+            app.update();
+            if remaining(&app) == 0.0 {
+                break;
+            }
+            assert_eq!(state(&app), GameState::Playing);
+        }
+        assert_eq!(remaining(&app), 0.0);
+        // The state changes as the next frame starts.
+        app.update();
+        assert_eq!(state(&app), GameState::GameOver);
+    }
+}
@@ -0,0 +1,174 @@
+//! Fades to black and back when moving between screens.
+//!
+//! Writing a `FadeTo` starts a fade: the screen darkens, the state switches
+//! once it's fully black, and the new screen fades in. Menu input is ignored
+//! until it's done, so nothing can be picked twice or mid-fade.
+
+use bevy::prelude::*;
+
+use crate::GameState;
+
+pub struct TransitionPlugin;
+
+impl Plugin for TransitionPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<FadeTo>()
+            .init_resource::<TransitionDuration>()
+            .add_systems(
+                Update,
+                (start_fade, run_fade.run_if(resource_exists::<Fade>)).chain(),
+            );
+    }
+}
+
+/// Asks for a fade into the given state. Ignored while a fade is under way.
+#[derive(Event)]
+pub struct FadeTo(pub GameState);
+
+/// Seconds each half of a fade takes. Zero switches states instantly.
+#[derive(Resource)]
+pub struct TransitionDuration(pub f32);
+
+impl Default for TransitionDuration {
+    fn default() -> Self {
+        Self(0.25)
+    }
+}
+
+/// The fade under way. Only present during one.
+#[derive(Resource)]
+pub struct Fade {
+    target: GameState,
+    timer: Timer,
+    /// Set once the state has switched and the overlay is fading back out.
+    switched: bool,
+}
+
+#[derive(Component)]
+struct FadeOverlay;
+
+/// Menu input waits while this is true.
+pub fn in_transition(fade: Option<Res<Fade>>) -> bool {
+    fade.is_some()
+}
+
+fn start_fade(
+    mut commands: Commands,
+    mut requests: EventReader<FadeTo>,
+    fade: Option<Res<Fade>>,
+    duration: Res<TransitionDuration>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    let Some(FadeTo(target)) = requests.read().last() else {
+        return;
+    };
+    if fade.is_some() {
+        return;
+    }
+    if duration.0 <= 0.0 {
+        game_state.set(target.clone());
+        return;
+    }
+
+    commands.spawn((
+        Node {
+            position_type: PositionType::Absolute,
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            ..default()
+        },
+        BackgroundColor(Color::BLACK.with_alpha(0.0)),
+        // Above every other UI element.
+        GlobalZIndex(i32::MAX),
+        FadeOverlay,
+    ));
+    commands.insert_resource(Fade {
+        target: target.clone(),
+        timer: Timer::from_seconds(duration.0, TimerMode::Once),
+        switched: false,
+    });
+}
+
+fn run_fade(
+    mut commands: Commands,
+    mut fade: ResMut<Fade>,
+    mut overlay_query: Query<(Entity, &mut BackgroundColor), With<FadeOverlay>>,
+    mut game_state: ResMut<NextState<GameState>>,
+    time: Res<Time>,
+) {
+    fade.timer.tick(time.delta());
+    let alpha = if fade.switched {
+        fade.timer.fraction_remaining()
+    } else {
+        fade.timer.fraction()
+    };
+    for (_, mut color) in overlay_query.iter_mut() {
+        color.0.set_alpha(alpha);
+    }
+    if !fade.timer.finished() {
+        return;
+    }
+
+    if fade.switched {
+        for (entity, _) in overlay_query.iter() {
+            commands.entity(entity).despawn();
+        }
+        commands.remove_resource::<Fade>();
+    } else {
+        game_state.set(fade.target.clone());
+        fade.switched = true;
+        fade.timer.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::{state::app::StatesPlugin, time::TimeUpdateStrategy};
+
+    use super::*;
+
+    #[test]
+    fn the_screen_goes_black_before_the_state_switches_then_clears() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, TransitionPlugin))
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_millis(
+                50,
+            )))
+            .insert_state(GameState::Menu);
+        app.update();
+        app.world_mut().send_event(FadeTo(GameState::Playing));
+
+        // (state, overlay alpha) after each frame, until the fade is over.
+        let mut frames = Vec::new();
+        for _ in 0..40 {
+            app.update();
+            let state = app.world().resource::<State<GameState>>().get().clone();
+            let alpha = app
+                .world_mut()
+                .query_filtered::<&BackgroundColor, With<FadeOverlay>>()
+                .iter(app.world())
+                .next()
+                .map(|color| color.0.alpha());
+            frames.push((state, alpha));
+            if !app.world().contains_resource::<Fade>() {
+                break;
+            }
+        }
+
+        let switch = frames
+            .iter()
+            .position(|(state, _)| *state == GameState::Playing)
+            .expect("the state never switched");
+        let before: Vec<f32> = frames[..switch].iter().filter_map(|(_, a)| *a).collect();
+        let after: Vec<f32> = frames[switch..].iter().filter_map(|(_, a)| *a).collect();
+        assert!(before.windows(2).all(|pair| pair[0] <= pair[1]));
+        assert_eq!(before.last(), Some(&1.0));
+        assert!(after.windows(2).all(|pair| pair[0] >= pair[1]));
+        assert!(after.iter().any(|&alpha| alpha < 1.0));
+        let (state, alpha) = frames.last().unwrap();
+        assert_eq!((state, *alpha), (&GameState::Playing, None));
+        assert!(!app.world().contains_resource::<Fade>());
+    }
+}
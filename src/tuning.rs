@@ -0,0 +1,481 @@
+//! Gameplay tunables, read from `tuning.ron` in the assets folder at startup so the feel of
+//! the game can be adjusted without recompiling.
+//!
+//! Anything the file leaves out keeps its built-in value, and a missing file
+//! changes nothing. Replays carry the tuning they were recorded under and play
+//! back with it.
+//!
+//! The file is watched while the game runs. Edits take effect straight away
+//! between runs, but one made during a recorded run waits for the run to end,
+//! since its replay carries only the tuning it started with. An edit that
+//! doesn't parse or validate is rejected, keeping the last good values.
+
+use std::{
+    fs, mem,
+    time::{Duration, SystemTime},
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    BULLET_RADIUS, BodyRadius, BulletLifetime, FISH_RADIUS, FishKind, IsEnemy, IsPlayer,
+    MINE_RADIUS, MineAssets, PLAYER_HITBOX_RADIUS, PLAYER_SPEED, PlayerHitbox, asset_file,
+    boss::Boss,
+    game_time::GameTime,
+    grade::GradeConfig,
+    replay::{ReplayPlayback, ReplayRecorder},
+};
+
+const TUNING_FILE: &str = "tuning.ron";
+/// Time between checks of the file for changes.
+const RELOAD_POLL_INTERVAL: f32 = 1.0;
+
+pub struct TuningPlugin;
+
+impl Plugin for TuningPlugin {
+    fn build(&self, app: &mut App) {
+        // Loaded here rather than in `main` so a rejected file is logged.
+        // Replays have already put in the tuning they were recorded under.
+        if !app.world().contains_resource::<Tuning>() {
+            app.insert_resource(Tuning::load());
+        }
+        app.insert_resource(TuningWatcher {
+            poll: Timer::from_seconds(RELOAD_POLL_INTERVAL, TimerMode::Repeating),
+            modified: file_modified(),
+        })
+        .add_systems(
+            Update,
+            (
+                reload_tuning.run_if(not(resource_exists::<ReplayPlayback>)),
+                apply_pending_tuning.run_if(
+                    resource_exists::<PendingTuning>.and(not(resource_exists::<ReplayRecorder>)),
+                ),
+                apply_tuning.run_if(resource_changed::<Tuning>.and(not(resource_added::<Tuning>))),
+            )
+                .chain(),
+        );
+    }
+}
+
+#[derive(Resource, Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Tuning {
+    /// How hard the movement keys push the player.
+    pub player_speed: f32,
+    pub player_hitbox_radius: f32,
+    /// Speed the player has to be moving at for clipping a fish to count as a
+    /// close call instead of a hit. Full speed is about 200.
+    pub close_call_speed: f32,
+    /// Seconds between attempts to spawn a fish.
+    pub enemy_spawn_interval: f32,
+    /// Seconds between attempts to spawn a mine.
+    pub mine_spawn_interval: f32,
+    /// `Difficulty` at which every attempt brings in a fish. The chance grows
+    /// in proportion up to there.
+    pub full_spawn_difficulty: f32,
+    pub bullet_speed: f32,
+    /// Seconds before an overheat clears within which a press of fire is
+    /// kept and fired as soon as the gun is free.
+    pub fire_buffer: f32,
+    /// Seconds or distance the duck's bullets last, for short-range weapons.
+    /// Without one they fly until they leave the play area.
+    pub bullet_lifetime: Option<BulletLifetime>,
+    /// How much closer than an enemy's `BodyRadius` a bullet has to get to hit it.
+    pub bullet_radius: f32,
+    pub fish_radius: f32,
+    pub mine_radius: f32,
+    /// Slowest and fastest a randomly spawned fish swims.
+    pub fish_speed: (f32, f32),
+    /// Slowest and fastest a randomly spawned mine drifts in.
+    pub mine_speed: (f32, f32),
+    /// How common each kind of randomly spawned fish is.
+    pub fish_weights: FishWeights,
+    /// How quickly the camera catches up with the duck when the play area is
+    /// bigger than the view. Higher is snappier.
+    pub camera_follow_rate: f32,
+    /// How far the duck can move from the middle of the view, across and up,
+    /// before the camera follows.
+    pub camera_dead_zone: (f32, f32),
+    /// How the end-of-run grade is worked out.
+    pub grades: GradeConfig,
+}
+
+impl Default for Tuning {
+    fn default() -> Self {
+        Self {
+            player_speed: PLAYER_SPEED,
+            player_hitbox_radius: PLAYER_HITBOX_RADIUS,
+            close_call_speed: 180.0,
+            enemy_spawn_interval: 0.25,
+            mine_spawn_interval: 2.0,
+            full_spawn_difficulty: 3.0,
+            bullet_speed: 500.0,
+            fire_buffer: 0.1,
+            bullet_lifetime: None,
+            bullet_radius: BULLET_RADIUS,
+            fish_radius: FISH_RADIUS,
+            mine_radius: MINE_RADIUS,
+            fish_speed: (10.0, 40.0),
+            mine_speed: (15.0, 25.0),
+            fish_weights: FishWeights::default(),
+            camera_follow_rate: 5.0,
+            camera_dead_zone: (80.0, 60.0),
+            grades: GradeConfig::default(),
+        }
+    }
+}
+
+impl Tuning {
+    pub fn load() -> Self {
+        let Ok(contents) = fs::read_to_string(asset_file(TUNING_FILE)) else {
+            return Self::default();
+        };
+        Self::parse(&contents).unwrap_or_else(|err| {
+            warn!("Ignoring {TUNING_FILE} ({err}), using the built-in tuning");
+            Self::default()
+        })
+    }
+
+    fn parse(contents: &str) -> Result<Self, String> {
+        let tuning: Self = ron::from_str(contents).map_err(|err| err.to_string())?;
+        tuning.validate()?;
+        Ok(tuning)
+    }
+
+    /// Rejects values the game can't run with.
+    fn validate(&self) -> Result<(), String> {
+        for (name, value) in [
+            ("enemy_spawn_interval", self.enemy_spawn_interval),
+            ("mine_spawn_interval", self.mine_spawn_interval),
+            ("full_spawn_difficulty", self.full_spawn_difficulty),
+            ("camera_follow_rate", self.camera_follow_rate),
+            ("close_call_speed", self.close_call_speed),
+            ("player_speed", self.player_speed),
+            ("bullet_speed", self.bullet_speed),
+        ] {
+            if !(value.is_finite() && value > 0.0) {
+                return Err(format!("{name} must be above zero"));
+            }
+        }
+        for (name, value) in [
+            ("player_hitbox_radius", self.player_hitbox_radius),
+            ("bullet_radius", self.bullet_radius),
+            ("fish_radius", self.fish_radius),
+            ("mine_radius", self.mine_radius),
+        ] {
+            if !(value.is_finite() && value >= 0.0) {
+                return Err(format!("{name} can't be negative"));
+            }
+        }
+        for (name, (min, max)) in [
+            ("fish_speed", self.fish_speed),
+            ("mine_speed", self.mine_speed),
+        ] {
+            if !(min.is_finite() && max.is_finite() && min <= max) {
+                return Err(format!("{name} must be a range from slowest to fastest"));
+            }
+        }
+        if !(self.fire_buffer.is_finite() && self.fire_buffer >= 0.0) {
+            return Err("fire_buffer can't be negative".to_string());
+        }
+        if self
+            .bullet_lifetime
+            .map(|(BulletLifetime::Time(limit) | BulletLifetime::Distance(limit))| limit)
+            .is_some_and(|limit| !(limit.is_finite() && limit > 0.0))
+        {
+            return Err("bullet_lifetime must be above zero".to_string());
+        }
+        let (across, up) = self.camera_dead_zone;
+        if !(across >= 0.0 && up >= 0.0) {
+            return Err("camera_dead_zone can't be negative".to_string());
+        }
+        self.fish_weights.validate()?;
+        self.grades.validate()
+    }
+}
+
+/// How often each kind of fish turns up among random spawns and the wave
+/// schedule's plain fish, relative to the others. Each is a weight at no
+/// difficulty and how much it changes with every point of `Difficulty`, never
+/// dropping below zero.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FishWeights {
+    pub plain: (f32, f32),
+    pub seeker: (f32, f32),
+    pub gunfish: (f32, f32),
+}
+
+impl Default for FishWeights {
+    fn default() -> Self {
+        Self {
+            plain: (0.82, 0.0),
+            seeker: (0.15, 0.0),
+            gunfish: (0.03, 0.0),
+        }
+    }
+}
+
+impl FishWeights {
+    fn validate(&self) -> Result<(), String> {
+        let mut total = 0.0;
+        for (name, (weight, growth)) in [
+            ("plain", self.plain),
+            ("seeker", self.seeker),
+            ("gunfish", self.gunfish),
+        ] {
+            if !(weight.is_finite() && weight >= 0.0 && growth.is_finite()) {
+                return Err(format!("fish_weights.{name} can't start negative"));
+            }
+            total += weight;
+        }
+        if total <= 0.0 {
+            return Err("fish_weights can't all start at zero".to_string());
+        }
+        Ok(())
+    }
+
+    /// Each kind's weight at `difficulty`, in the order a roll runs through
+    /// them.
+    fn at(&self, difficulty: f32) -> [(FishKind, f32); 3] {
+        [
+            (FishKind::Seeker, self.seeker),
+            (FishKind::Gunfish, self.gunfish),
+            (FishKind::Plain, self.plain),
+        ]
+        .map(|(kind, (weight, growth))| (kind, (weight + growth * difficulty).max(0.0)))
+    }
+
+    /// The kind a `roll` from 0.0 to 1.0 lands on at `difficulty`.
+    pub fn pick(&self, difficulty: f32, roll: f32) -> FishKind {
+        let weights = self.at(difficulty);
+        let target = roll * weights.iter().map(|(_, weight)| weight).sum::<f32>();
+        let mut reached = 0.0;
+        for (kind, weight) in weights {
+            reached += weight;
+            if target < reached {
+                return kind;
+            }
+        }
+        // Every weight has dropped to zero.
+        FishKind::Plain
+    }
+}
+
+/// Tuning reloaded during a recorded run, waiting for the run to end.
+#[derive(Resource)]
+struct PendingTuning(Tuning);
+
+/// When the tuning file was last seen to change.
+#[derive(Resource)]
+struct TuningWatcher {
+    poll: Timer,
+    modified: Option<SystemTime>,
+}
+
+fn file_modified() -> Option<SystemTime> {
+    fs::metadata(asset_file(TUNING_FILE))
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+/// Works like `on_timer`, but follows the interval as the tuning changes.
+fn spawn_due(timer: &mut Option<Timer>, interval: f32, time: &GameTime) -> bool {
+    let duration = Duration::from_secs_f32(interval);
+    let timer = timer.get_or_insert_with(|| Timer::new(duration, TimerMode::Repeating));
+    if timer.duration() != duration {
+        timer.set_duration(duration);
+    }
+    timer.tick(time.delta()).just_finished()
+}
+
+/// Whether it's time for the next attempt at spawning a fish.
+pub fn enemy_spawn_due(
+    mut timer: Local<Option<Timer>>,
+    tuning: Res<Tuning>,
+    time: Res<GameTime>,
+) -> bool {
+    spawn_due(&mut timer, tuning.enemy_spawn_interval, &time)
+}
+
+/// Whether it's time for the next attempt at spawning a mine.
+pub fn mine_spawn_due(
+    mut timer: Local<Option<Timer>>,
+    tuning: Res<Tuning>,
+    time: Res<GameTime>,
+) -> bool {
+    spawn_due(&mut timer, tuning.mine_spawn_interval, &time)
+}
+
+fn reload_tuning(
+    mut commands: Commands,
+    mut tuning: ResMut<Tuning>,
+    mut watcher: ResMut<TuningWatcher>,
+    recorder: Option<Res<ReplayRecorder>>,
+    time: Res<Time>,
+) {
+    if !watcher.poll.tick(time.delta()).just_finished() {
+        return;
+    }
+    let modified = file_modified();
+    if modified == watcher.modified {
+        return;
+    }
+    watcher.modified = modified;
+
+    let result = fs::read_to_string(asset_file(TUNING_FILE))
+        .map_err(|err| err.to_string())
+        .and_then(|contents| Tuning::parse(&contents));
+    match result {
+        Ok(reloaded) if recorder.is_some() => {
+            info!("Reloaded {TUNING_FILE}, applying it once this run ends");
+            commands.insert_resource(PendingTuning(reloaded));
+        }
+        Ok(reloaded) => {
+            commands.remove_resource::<PendingTuning>();
+            if reloaded != *tuning {
+                *tuning = reloaded;
+                info!("Reloaded {TUNING_FILE}");
+            }
+        }
+        Err(err) => error!("Rejected {TUNING_FILE} ({err}), keeping the current tuning"),
+    }
+}
+
+fn apply_pending_tuning(
+    mut commands: Commands,
+    mut pending: ResMut<PendingTuning>,
+    mut tuning: ResMut<Tuning>,
+) {
+    commands.remove_resource::<PendingTuning>();
+    let pending = mem::take(&mut pending.0);
+    if pending != *tuning {
+        *tuning = pending;
+        info!("Applied the {TUNING_FILE} reloaded during the last run");
+    }
+}
+
+/// Brings what's already in play up to date with reloaded tuning. Everything
+/// else reads it as it goes.
+fn apply_tuning(
+    tuning: Res<Tuning>,
+    mine_assets: Res<MineAssets>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut player_query: Query<&mut PlayerHitbox, With<IsPlayer>>,
+    mut fish_query: Query<&mut BodyRadius, (With<IsEnemy>, Without<Boss>)>,
+) {
+    meshes.insert(&mine_assets.mesh, Circle::new(tuning.mine_radius).into());
+    for mut hitbox in player_query.iter_mut() {
+        hitbox.0 = tuning.player_hitbox_radius;
+    }
+    for mut radius in fish_query.iter_mut() {
+        radius.0 = tuning.fish_radius;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BulletAssets, Velocity, bullet};
+
+    fn tuning_app() -> App {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .init_resource::<Tuning>()
+            .add_systems(
+                Update,
+                apply_pending_tuning.run_if(
+                    resource_exists::<PendingTuning>.and(not(resource_exists::<ReplayRecorder>)),
+                ),
+            );
+        app
+    }
+
+    fn faster() -> Tuning {
+        Tuning {
+            player_speed: PLAYER_SPEED * 2.0,
+            ..default()
+        }
+    }
+
+    #[test]
+    fn pending_tuning_applies_once_no_run_is_recorded() {
+        let mut app = tuning_app();
+        app.insert_resource(PendingTuning(faster()));
+        app.update();
+        assert_eq!(*app.world().resource::<Tuning>(), faster());
+        assert!(!app.world().contains_resource::<PendingTuning>());
+    }
+
+    fn share_of(weights: &FishWeights, difficulty: f32, kind: FishKind) -> f32 {
+        let mut rng = fastrand::Rng::with_seed(7);
+        let picks = 1000;
+        let count = (0..picks)
+            .filter(|_| weights.pick(difficulty, rng.f32()) == kind)
+            .count();
+        count as f32 / picks as f32
+    }
+
+    #[test]
+    fn the_heaviest_weight_picks_most_fish() {
+        let weights = FishWeights {
+            plain: (0.1, 0.0),
+            seeker: (0.1, 0.0),
+            gunfish: (5.0, 0.0),
+        };
+        assert!(share_of(&weights, 1.0, FishKind::Gunfish) > 0.9);
+    }
+
+    #[test]
+    fn weights_grow_with_difficulty() {
+        let weights = FishWeights {
+            plain: (1.0, 0.0),
+            seeker: (0.0, 1.0),
+            gunfish: (0.5, -1.0),
+        };
+        assert_eq!(share_of(&weights, 0.0, FishKind::Seeker), 0.0);
+        assert!(share_of(&weights, 20.0, FishKind::Seeker) > 0.9);
+        assert_eq!(share_of(&weights, 20.0, FishKind::Gunfish), 0.0);
+    }
+
+    #[test]
+    fn a_partial_file_keeps_the_built_in_values() {
+        let tuning = Tuning::parse("(player_speed: 12.0)").unwrap();
+        assert_eq!(tuning.player_speed, 12.0);
+        assert_eq!(tuning.bullet_speed, Tuning::default().bullet_speed);
+        assert!(Tuning::parse("(enemy_spawn_interval: 0.0)").is_err());
+    }
+
+    #[test]
+    fn speeds_and_radii_the_game_cant_use_are_rejected() {
+        for contents in [
+            "(bullet_speed: 0.0)",
+            "(player_speed: -50.0)",
+            "(bullet_radius: -1.0)",
+            "(fish_radius: -1.0)",
+            "(mine_radius: -1.0)",
+            "(player_hitbox_radius: -1.0)",
+        ] {
+            assert!(Tuning::parse(contents).is_err(), "accepted {contents}");
+        }
+        assert!(Tuning::parse("(bullet_radius: 0.0)").is_ok());
+    }
+
+    #[test]
+    fn bullets_fly_at_the_tuned_speed() {
+        let tuning = Tuning::parse("(bullet_speed: 900.0)").unwrap();
+        let assets = BulletAssets {
+            mesh: Handle::default(),
+            material: Handle::default(),
+        };
+        let mut world = World::new();
+        let shot = world
+            .spawn(bullet(&assets, &tuning, Vec2::ZERO, Vec2::Y))
+            .id();
+        assert_eq!(
+            world.get::<Velocity>(shot).unwrap().0,
+            Vec2::new(0.0, 900.0)
+        );
+    }
+}
@@ -0,0 +1,211 @@
+//! A guided first run that teaches the controls one step at a time.
+
+use bevy::prelude::*;
+
+use crate::{
+    EnemyAtlas, FISH_HP, GameState, InputSet, IsEnemy, PlayerInput, RunMode, fish,
+    game_time::GameTime,
+    start_run,
+    transition::{FadeTo, in_transition},
+    tuning::Tuning,
+};
+
+const TARGET_POSITION: Vec2 = Vec2::new(250.0, 0.0);
+const OUTRO_DURATION: f32 = 2.0;
+
+pub struct TutorialPlugin;
+
+impl Plugin for TutorialPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnExit(GameState::Menu),
+            start_tutorial.after(start_run).run_if(in_tutorial),
+        )
+        .add_systems(OnEnter(GameState::Menu), despawn_tutorial)
+        .add_systems(
+            Update,
+            (
+                advance_tutorial.after(InputSet).run_if(not(in_transition)),
+                update_tutorial_prompt,
+            )
+                .chain()
+                .run_if(in_state(GameState::Playing).and(in_tutorial)),
+        );
+    }
+}
+
+pub fn in_tutorial(mode: Res<RunMode>) -> bool {
+    matches!(*mode, RunMode::Tutorial)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TutorialStep {
+    Move,
+    Shoot,
+    DestroyFish,
+    Done,
+}
+
+impl TutorialStep {
+    fn prompt(self) -> &'static str {
+        match self {
+            Self::Move => "Use WASD to move",
+            Self::Shoot => "Press Space to shoot",
+            Self::DestroyFish => "Destroy the fish",
+            Self::Done => "Well done!",
+        }
+    }
+}
+
+#[derive(Resource)]
+struct Tutorial {
+    step: TutorialStep,
+    outro: Timer,
+}
+
+#[derive(Component)]
+struct TutorialPrompt;
+
+fn start_tutorial(mut commands: Commands) {
+    commands.insert_resource(Tutorial {
+        step: TutorialStep::Move,
+        outro: Timer::from_seconds(OUTRO_DURATION, TimerMode::Once),
+    });
+    commands.spawn((
+        Text::new(TutorialStep::Move.prompt()),
+        TextFont {
+            font_size: 30.0,
+            ..default()
+        },
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(60.0),
+            width: Val::Percent(100.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        },
+        TextLayout::new_with_justify(JustifyText::Center),
+        TutorialPrompt,
+    ));
+}
+
+fn despawn_tutorial(mut commands: Commands, query: Query<Entity, With<TutorialPrompt>>) {
+    commands.remove_resource::<Tutorial>();
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn advance_tutorial(
+    mut commands: Commands,
+    mut tutorial: ResMut<Tutorial>,
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    input: Res<PlayerInput>,
+    enemy_atlas: Res<EnemyAtlas>,
+    tuning: Res<Tuning>,
+    enemy_query: Query<(), With<IsEnemy>>,
+    mut fades: EventWriter<FadeTo>,
+    time: Res<GameTime>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Escape) {
+        fades.write(FadeTo(GameState::Menu));
+        return;
+    }
+
+    match tutorial.step {
+        TutorialStep::Move => {
+            if input.left || input.right || input.up || input.down {
+                tutorial.step = TutorialStep::Shoot;
+            }
+        }
+        TutorialStep::Shoot => {
+            if input.fire {
+                // A single stationary fish that can't hurt the player.
+                commands.spawn(fish(
+                    &enemy_atlas,
+                    &tuning,
+                    TARGET_POSITION,
+                    Vec2::ZERO,
+                    FISH_HP,
+                ));
+                tutorial.step = TutorialStep::DestroyFish;
+            }
+        }
+        TutorialStep::DestroyFish => {
+            if enemy_query.is_empty() {
+                tutorial.step = TutorialStep::Done;
+            }
+        }
+        TutorialStep::Done => {
+            if tutorial.outro.tick(time.delta()).finished() {
+                fades.write(FadeTo(GameState::Menu));
+            }
+        }
+    }
+}
+
+fn update_tutorial_prompt(
+    tutorial: Res<Tutorial>,
+    mut query: Query<&mut Text, With<TutorialPrompt>>,
+) {
+    if tutorial.is_changed() {
+        for mut text in query.iter_mut() {
+            text.0 = tutorial.step.prompt().to_string();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    #[test]
+    fn the_steps_advance_in_order_as_the_player_acts() {
+        let mut world = World::new();
+        world.insert_resource(Tutorial {
+            step: TutorialStep::Move,
+            outro: Timer::from_seconds(OUTRO_DURATION, TimerMode::Once),
+        });
+        world.init_resource::<ButtonInput<KeyCode>>();
+        world.init_resource::<PlayerInput>();
+        world.insert_resource(EnemyAtlas {
+            image: Handle::default(),
+            layout: Handle::default(),
+        });
+        world.init_resource::<Tuning>();
+        world.init_resource::<GameTime>();
+        world.init_resource::<Events<FadeTo>>();
+        let step = |world: &mut World| {
+            world.run_system_once(advance_tutorial).unwrap();
+            world.resource::<Tutorial>().step
+        };
+
+        // Shooting before moving doesn't skip ahead.
+        world.resource_mut::<PlayerInput>().fire = true;
+        assert_eq!(step(&mut world), TutorialStep::Move);
+        *world.resource_mut::<PlayerInput>() = PlayerInput {
+            right: true,
+            ..default()
+        };
+        assert_eq!(step(&mut world), TutorialStep::Shoot);
+
+        *world.resource_mut::<PlayerInput>() = PlayerInput {
+            fire: true,
+            ..default()
+        };
+        assert_eq!(step(&mut world), TutorialStep::DestroyFish);
+        let fish: Vec<Entity> = world
+            .query_filtered::<Entity, With<IsEnemy>>()
+            .iter(&world)
+            .collect();
+        assert_eq!(fish.len(), 1);
+        assert_eq!(step(&mut world), TutorialStep::DestroyFish);
+
+        world.despawn(fish[0]);
+        assert_eq!(step(&mut world), TutorialStep::Done);
+        assert!(world.resource::<Events<FadeTo>>().is_empty());
+    }
+}
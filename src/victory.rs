@@ -0,0 +1,180 @@
+//! The screen shown after clearing every level, with the run's final stats.
+//!
+//! From here the run can end, or carry on in endless mode: the levels are
+//! dropped and the usual ramping spawns take over, keeping the score. The wave
+//! schedule and the next boss pick up from there rather than from the start.
+
+use bevy::prelude::*;
+
+use crate::{
+    GameOverText, GameState, RunMode, Score, format_thousands,
+    hud::{SurvivalTime, format_run_time},
+    levels::LEVEL_COUNT,
+    particles::Explosion,
+    play_area::PlayArea,
+    replay::ReplayPlayback,
+    transition::{FadeTo, in_transition},
+};
+
+const FIREWORK_INTERVAL: f32 = 0.35;
+
+pub struct VictoryPlugin;
+
+impl Plugin for VictoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::Victory), display_victory_text)
+            .add_systems(
+                Update,
+                (
+                    launch_fireworks,
+                    continue_endless
+                        .run_if(not(resource_exists::<ReplayPlayback>).and(not(in_transition))),
+                )
+                    .run_if(in_state(GameState::Victory)),
+            );
+    }
+}
+
+fn display_victory_text(
+    mut commands: Commands,
+    score: Res<Score>,
+    survival_time: Res<SurvivalTime>,
+) {
+    commands.spawn((
+        Text2d::new("You Win!"),
+        TextFont {
+            font_size: 60.0,
+            ..default()
+        },
+        TextColor(Color::srgb(1.0, 0.85, 0.2)),
+        Transform::from_xyz(0.0, 200.0, 10.0),
+        GameOverText,
+    ));
+    let stats = [
+        format!("Final Score: {}", format_thousands(score.0)),
+        format!("Time: {}", format_run_time(survival_time.0)),
+        format!("Levels Cleared: {LEVEL_COUNT}/{LEVEL_COUNT}"),
+        String::new(),
+        "Press C to keep going in endless mode".to_string(),
+    ];
+    commands.spawn((
+        Text2d::new(stats.join("\n")),
+        TextFont {
+            font_size: 24.0,
+            ..default()
+        },
+        Transform::from_xyz(0.0, 80.0, 10.0),
+        GameOverText,
+    ));
+}
+
+/// Sets off purely cosmetic bursts at random spots around the screen.
+fn launch_fireworks(
+    mut explosions: EventWriter<Explosion>,
+    play_area: Res<PlayArea>,
+    mut timer: Local<Option<Timer>>,
+    time: Res<Time>,
+) {
+    let timer =
+        timer.get_or_insert_with(|| Timer::from_seconds(FIREWORK_INTERVAL, TimerMode::Repeating));
+    for _ in 0..timer.tick(time.delta()).times_finished_this_tick() {
+        explosions.write(Explosion {
+            position: Vec2::new(
+                (fastrand::f32() * 2.0 - 1.0) * play_area.half_width,
+                (fastrand::f32() * 2.0 - 1.0) * play_area.half_height,
+            ),
+            radius: 0.0,
+        });
+    }
+}
+
+/// Picks the run back up as a standard one, so it's scored as such when it
+/// finally ends.
+fn continue_endless(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    mut mode: ResMut<RunMode>,
+    mut fades: EventWriter<FadeTo>,
+) {
+    if keyboard_input.just_pressed(KeyCode::KeyC) {
+        *mode = RunMode::Standard;
+        fades.write(FadeTo(GameState::Playing));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::state::app::StatesPlugin;
+
+    use super::*;
+    use crate::{
+        CritConfig, Difficulty, Health, IsPlayer, MultiShot, SpreadConfig,
+        levels::{LevelProgress, LevelsPlugin},
+    };
+
+    #[test]
+    fn clearing_every_level_shows_the_final_stats() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin, LevelsPlugin, VictoryPlugin))
+            .insert_state(GameState::Menu)
+            .add_event::<Explosion>()
+            .add_event::<FadeTo>()
+            .init_resource::<ButtonInput<KeyCode>>()
+            .insert_resource(RunMode::Levels)
+            .insert_resource(Score(4_321))
+            .insert_resource(SurvivalTime(75.5))
+            .insert_resource(Difficulty(1.0))
+            .init_resource::<MultiShot>()
+            .init_resource::<SpreadConfig>()
+            .insert_resource(CritConfig {
+                chance: 0.0,
+                multiplier: 2,
+                bonus_points: 0,
+            })
+            .insert_resource(PlayArea {
+                half_width: 400.0,
+                half_height: 300.0,
+            });
+        app.world_mut().spawn((IsPlayer, Health(3)));
+        app.world_mut()
+            .resource_mut::<NextState<GameState>>()
+            .set(GameState::Playing);
+
+        // Every level's enemies are dealt with as soon as it starts.
+        for _ in 0..10 {
+            app.update();
+            if let Some(mut progress) = app.world_mut().get_resource_mut::<LevelProgress>() {
+                progress.remaining = 0;
+            }
+        }
+        assert_eq!(
+            *app.world().resource::<State<GameState>>().get(),
+            GameState::Victory
+        );
+        let mut texts = app
+            .world_mut()
+            .query_filtered::<&Text2d, With<GameOverText>>();
+        let stats = texts
+            .iter(app.world())
+            .map(|text| text.0.clone())
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert!(stats.contains("Final Score: 4,321"), "{stats}");
+        assert!(stats.contains("Time: 01:15.50"), "{stats}");
+        assert!(stats.contains("Levels Cleared: 4/4"), "{stats}");
+
+        app.world_mut()
+            .resource_mut::<ButtonInput<KeyCode>>()
+            .press(KeyCode::KeyC);
+        app.update();
+        assert!(matches!(
+            *app.world().resource::<RunMode>(),
+            RunMode::Standard
+        ));
+        let fades = app.world().resource::<Events<FadeTo>>();
+        assert!(
+            fades
+                .iter_current_update_events()
+                .any(|fade| fade.0 == GameState::Playing)
+        );
+    }
+}
@@ -0,0 +1,586 @@
+//! Scripted enemy waves read from `waves.ron` in the assets folder.
+//!
+//! Launching with `--waves` has the file's schedule replace the random enemy
+//! and mine spawners. When it's missing or malformed, the random spawners stay
+//! in charge. Scripted fish still count toward `MaxEnemies`, and any that would
+//! go past it are left out, as are any that would come in on top of the duck
+//! just as the run starts. The custom difficulty's spawn rate plays the
+//! schedule faster or slower, and it eases in over the run's first
+//! `SPAWN_WARMUP` seconds like the random spawners do.
+//!
+//! Besides mines, the schedule can name seekers and gunfish. Any other fish is
+//! rolled from the tuning's `fish_weights`, as random spawns are.
+//!
+//! Each spawn is announced by a `SpawnWarning` shortly before it happens,
+//! shown as an indicator where the enemies will come in.
+
+use std::{collections::VecDeque, fs};
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+use serde::Deserialize;
+
+use crate::{
+    Difficulty, EnemyAtlas, FISH_HP, FishKind, GameRng, GameState, IsEnemy, IsPlayer, MINE_HP,
+    MaxEnemies, MineAssets, SPAWN_WARMUP, asset_file,
+    boss::{in_boss_death, in_boss_intro},
+    bounds::BoundsBehavior,
+    check_for_collisions, crowds_safe_start,
+    custom_difficulty::RunDifficulty,
+    fish,
+    game_time::GameTime,
+    hud::SurvivalTime,
+    insert_fish_kind,
+    levels::in_levels,
+    mine,
+    play_area::PlayArea,
+    player_position,
+    practice::in_practice,
+    scrolling::FieldScroll,
+    spawn_mines, spawn_warmup, start_run,
+    tuning::Tuning,
+    tutorial::in_tutorial,
+};
+
+const WAVES_FILE: &str = "waves.ron";
+/// Time between a spawn's warning and the spawn itself.
+const SPAWN_WARNING_LEAD: f32 = 1.0;
+/// How far inside the edge a warning is shown.
+const SPAWN_WARNING_INSET: f32 = 24.0;
+
+pub struct WavesPlugin;
+
+impl Plugin for WavesPlugin {
+    fn build(&self, app: &mut App) {
+        if app.world().contains_resource::<ScriptedWaves>()
+            && let Some(wave_data) = WaveData::load()
+        {
+            app.insert_resource(wave_data);
+        }
+        app.init_resource::<WaveClock>()
+            .add_event::<SpawnWarning>()
+            .add_systems(OnExit(GameState::Menu), reset_wave_clock.after(start_run))
+            .add_systems(OnEnter(GameState::Menu), despawn_spawn_warnings)
+            // Carrying on past victory in endless mode.
+            .add_systems(
+                OnTransition {
+                    exited: GameState::Victory,
+                    entered: GameState::Playing,
+                },
+                carry_wave_clock.run_if(resource_exists::<WaveData>),
+            )
+            .add_systems(
+                Update,
+                (
+                    // In its place among the spawners drawing from `GameRng`.
+                    spawn_waves
+                        .after(spawn_mines)
+                        .before(check_for_collisions)
+                        .run_if(
+                            resource_exists::<WaveData>
+                                .and(not(in_tutorial))
+                                .and(not(in_practice))
+                                .and(not(in_levels))
+                                .and(not(in_boss_intro))
+                                .and(not(in_boss_death)),
+                        ),
+                    (show_spawn_warnings, fade_spawn_warnings).chain(),
+                )
+                    .chain()
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Present when launched with `--waves`, or playing back a run that was.
+#[derive(Resource)]
+pub struct ScriptedWaves;
+
+/// The spawn schedule, sorted by time.
+#[derive(Resource, Debug, Deserialize)]
+pub struct WaveData {
+    spawns: Vec<SpawnPattern>,
+    /// Seconds after which the schedule starts over. Plays once when unset.
+    #[serde(default)]
+    repeat_after: Option<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SpawnPattern {
+    /// Seconds into the schedule.
+    at: f32,
+    kind: EnemyKind,
+    edge: Edge,
+    /// Where along the edge to enter, from -1.0 to 1.0.
+    #[serde(default)]
+    offset: f32,
+    #[serde(default)]
+    formation: Formation,
+    /// Entry speed at a `Difficulty` of 1.0, scaling up with it.
+    speed: f32,
+    /// Overrides the usual `BoundsPolicy` for enemies.
+    #[serde(default)]
+    bounds: Option<BoundsBehavior>,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize)]
+enum EnemyKind {
+    /// A fish of whichever kind the tuning's `fish_weights` roll.
+    Fish,
+    /// A fish that steers toward the duck's height.
+    Seeker,
+    /// A fish that drops a power-up.
+    Gunfish,
+    Mine,
+}
+
+impl EnemyKind {
+    /// `None` for a mine. `roll` picks the kind of a plain `Fish`.
+    fn fish_kind(self, roll: impl FnOnce() -> FishKind) -> Option<FishKind> {
+        match self {
+            Self::Fish => Some(roll()),
+            Self::Seeker => Some(FishKind::Seeker),
+            Self::Gunfish => Some(FishKind::Gunfish),
+            Self::Mine => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub enum Edge {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+impl Edge {
+    /// Points into the play area, and along the edge.
+    fn axes(self) -> (Vec2, Vec2) {
+        match self {
+            Self::Left => (Vec2::X, Vec2::Y),
+            Self::Right => (Vec2::NEG_X, Vec2::Y),
+            Self::Top => (Vec2::NEG_Y, Vec2::X),
+            Self::Bottom => (Vec2::Y, Vec2::X),
+        }
+    }
+}
+
+/// Enemies are about to come in at `position` on `edge`.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct SpawnWarning {
+    pub edge: Edge,
+    pub position: Vec2,
+}
+
+/// Shows where a `SpawnWarning` said enemies will come in, fading until they
+/// arrive.
+#[derive(Component)]
+pub struct SpawnWarningIndicator(Timer);
+
+#[derive(Debug, Default, Clone, Copy, Deserialize)]
+enum Formation {
+    #[default]
+    Single,
+    /// Side by side along the edge.
+    Line { count: u32, spacing: f32 },
+    /// A leader with the rest trailing back on alternating sides.
+    Vee { count: u32, spacing: f32 },
+}
+
+impl Formation {
+    fn positions(self, origin: Vec2, inward: Vec2, along: Vec2) -> Vec<Vec2> {
+        match self {
+            Self::Single => vec![origin],
+            Self::Line { count, spacing } => {
+                let middle = (count as f32 - 1.0) / 2.0;
+                (0..count)
+                    .map(|i| origin + along * (i as f32 - middle) * spacing)
+                    .collect()
+            }
+            Self::Vee { count, spacing } => (0..count)
+                .map(|i| {
+                    let rank = i.div_ceil(2) as f32;
+                    let side = if i % 2 == 1 { 1.0 } else { -1.0 };
+                    origin + along * side * rank * spacing - inward * rank * spacing * 0.5
+                })
+                .collect(),
+        }
+    }
+}
+
+impl SpawnPattern {
+    /// Where on the edge the spawn is centered.
+    fn origin(&self, play_area: &PlayArea) -> Vec2 {
+        let half_size = Vec2::new(play_area.half_width, play_area.half_height);
+        let (inward, along) = self.edge.axes();
+        -inward * half_size + along * half_size * self.offset.clamp(-1.0, 1.0)
+    }
+}
+
+impl WaveData {
+    fn load() -> Option<Self> {
+        let contents = match fs::read_to_string(asset_file(WAVES_FILE)) {
+            Ok(contents) => contents,
+            Err(err) => {
+                warn!("Couldn't read {WAVES_FILE} ({err}), using random spawns");
+                return None;
+            }
+        };
+        Self::parse(&contents)
+            .inspect_err(|err| warn!("Ignoring {WAVES_FILE} ({err}), using random spawns"))
+            .ok()
+    }
+
+    fn parse(contents: &str) -> Result<Self, String> {
+        let mut wave_data: Self = ron::from_str(contents).map_err(|err| err.to_string())?;
+        wave_data.spawns.sort_by(|a, b| a.at.total_cmp(&b.at));
+
+        let last = wave_data.spawns.last().map_or(0.0, |spawn| spawn.at);
+        if wave_data
+            .repeat_after
+            .is_some_and(|repeat_after| repeat_after <= last)
+        {
+            return Err("repeat_after must come after the last spawn".to_string());
+        }
+        Ok(wave_data)
+    }
+}
+
+/// Progress through the wave schedule for the current run.
+#[derive(Resource, Default)]
+struct WaveClock {
+    elapsed: f32,
+    next: usize,
+    /// The next spawn to warn about.
+    next_warning: usize,
+    /// Set once warnings have gone on to the schedule's next repeat.
+    warning_wrapped: bool,
+    /// `FieldScroll` as each spawn warned about, but still to come, was
+    /// warned about. Oldest first.
+    warned_scroll: VecDeque<f32>,
+}
+
+impl WaveClock {
+    /// Moves the clock to `elapsed` into the run without spawning anything
+    /// scheduled before then.
+    fn skip_to(&mut self, elapsed: f32, wave_data: &WaveData) {
+        let elapsed = match wave_data.repeat_after {
+            Some(repeat_after) => elapsed % repeat_after,
+            None => elapsed,
+        };
+        let next = wave_data
+            .spawns
+            .iter()
+            .position(|spawn| spawn.at > elapsed)
+            .unwrap_or(wave_data.spawns.len());
+        *self = WaveClock {
+            elapsed,
+            next,
+            next_warning: next,
+            ..default()
+        };
+    }
+
+    /// How far the field has scrolled since the next spawn was warned about.
+    fn scroll_since_warning(&mut self, scroll: f32) -> f32 {
+        self.warned_scroll
+            .pop_front()
+            .map_or(0.0, |warned| scroll - warned)
+    }
+}
+
+/// What scripted enemies are made from.
+#[derive(SystemParam)]
+struct WaveAssets<'w> {
+    enemy_atlas: Res<'w, EnemyAtlas>,
+    mine_assets: Res<'w, MineAssets>,
+    tuning: Res<'w, Tuning>,
+}
+
+fn reset_wave_clock(mut clock: ResMut<WaveClock>) {
+    *clock = WaveClock::default();
+}
+
+/// The schedule doesn't run during a levels run. Picks it up as far in as a
+/// standard run of the same length would be, rather than from the opening
+/// waves. The warmup holds the clock back by half its length.
+fn carry_wave_clock(
+    mut clock: ResMut<WaveClock>,
+    wave_data: Res<WaveData>,
+    survival_time: Res<SurvivalTime>,
+    run_difficulty: Res<RunDifficulty>,
+) {
+    let elapsed = (survival_time.0 - SPAWN_WARMUP / 2.0).max(0.0);
+    clock.skip_to(elapsed * run_difficulty.0.spawn_rate, &wave_data);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_waves(
+    mut commands: Commands,
+    wave_data: Res<WaveData>,
+    mut clock: ResMut<WaveClock>,
+    assets: WaveAssets,
+    play_area: Res<PlayArea>,
+    difficulty: Res<Difficulty>,
+    run_difficulty: Res<RunDifficulty>,
+    max_enemies: Res<MaxEnemies>,
+    enemy_query: Query<(), With<IsEnemy>>,
+    mut warnings: EventWriter<SpawnWarning>,
+    time: Res<GameTime>,
+    survival_time: Res<SurvivalTime>,
+    mut rng: ResMut<GameRng>,
+    player_query: Query<&Transform, With<IsPlayer>>,
+    scroll: Res<FieldScroll>,
+) {
+    let custom = &run_difficulty.0;
+    clock.elapsed += time.delta_secs() * custom.spawn_rate * spawn_warmup(survival_time.0);
+    if let Some(repeat_after) = wave_data
+        .repeat_after
+        .filter(|&repeat_after| clock.elapsed >= repeat_after)
+    {
+        clock.elapsed -= repeat_after;
+        // Anything skipped over in a long frame won't come now.
+        let skipped = (wave_data.spawns.len() - clock.next).min(clock.warned_scroll.len());
+        clock.warned_scroll.drain(..skipped);
+        clock.next = 0;
+        if clock.warning_wrapped {
+            clock.warning_wrapped = false;
+        } else {
+            clock.next_warning = 0;
+        }
+    }
+
+    // Warnings look ahead, into the next repeat if need be.
+    let look_ahead = clock.elapsed + SPAWN_WARNING_LEAD;
+    loop {
+        let Some(spawn) = wave_data.spawns.get(clock.next_warning) else {
+            match wave_data.repeat_after {
+                Some(_) if !clock.warning_wrapped => {
+                    clock.next_warning = 0;
+                    clock.warning_wrapped = true;
+                    continue;
+                }
+                _ => break,
+            }
+        };
+        let at = match (clock.warning_wrapped, wave_data.repeat_after) {
+            (true, Some(repeat_after)) => spawn.at + repeat_after,
+            _ => spawn.at,
+        };
+        if at > look_ahead {
+            break;
+        }
+        clock.next_warning += 1;
+        clock.warned_scroll.push_back(scroll.0);
+        warnings.write(SpawnWarning {
+            edge: spawn.edge,
+            position: spawn.origin(&play_area),
+        });
+    }
+
+    let mut room = max_enemies
+        .at(difficulty.0)
+        .saturating_sub(enemy_query.iter().count());
+    while let Some(spawn) = wave_data.spawns.get(clock.next) {
+        if spawn.at > clock.elapsed {
+            break;
+        }
+        clock.next += 1;
+
+        let (inward, along) = spawn.edge.axes();
+        // Comes in where its warning has scrolled to.
+        let origin = spawn.origin(&play_area) + Vec2::X * clock.scroll_since_warning(scroll.0);
+        let velocity = inward * spawn.speed * difficulty.0 * custom.enemy_speed;
+
+        let player = player_position(&player_query);
+        for position in spawn.formation.positions(origin, inward, along) {
+            if crowds_safe_start(position, player, survival_time.0) {
+                continue;
+            }
+            let kind = spawn
+                .kind
+                .fish_kind(|| assets.tuning.fish_weights.pick(difficulty.0, rng.0.f32()));
+            let mut entity = match kind {
+                Some(_) if room == 0 => continue,
+                Some(kind) => {
+                    room -= 1;
+                    let mut entity = commands.spawn(fish(
+                        &assets.enemy_atlas,
+                        &assets.tuning,
+                        position,
+                        velocity,
+                        FISH_HP * custom.enemy_health,
+                    ));
+                    insert_fish_kind(&mut entity, kind, &mut rng.0);
+                    entity
+                }
+                None => commands.spawn(mine(
+                    &assets.mine_assets,
+                    position,
+                    velocity,
+                    MINE_HP * custom.enemy_health,
+                )),
+            };
+            if let Some(bounds) = spawn.bounds {
+                entity.insert(bounds);
+            }
+        }
+    }
+}
+
+fn show_spawn_warnings(mut commands: Commands, mut warnings: EventReader<SpawnWarning>) {
+    for warning in warnings.read() {
+        let (inward, _) = warning.edge.axes();
+        // Points out toward where the enemies are coming from.
+        let arrow = match warning.edge {
+            Edge::Left => "<",
+            Edge::Right => ">",
+            Edge::Top => "^",
+            Edge::Bottom => "v",
+        };
+        commands.spawn((
+            Text2d::new(format!("!{arrow}")),
+            TextFont {
+                font_size: 32.0,
+                ..default()
+            },
+            TextColor(Color::srgb(1.0, 0.2, 0.1)),
+            Transform::from_translation(
+                (warning.position + inward * SPAWN_WARNING_INSET).extend(8.0),
+            ),
+            SpawnWarningIndicator(Timer::from_seconds(SPAWN_WARNING_LEAD, TimerMode::Once)),
+        ));
+    }
+}
+
+fn fade_spawn_warnings(
+    mut commands: Commands,
+    mut query: Query<(Entity, &mut SpawnWarningIndicator, &mut TextColor)>,
+    time: Res<Time>,
+) {
+    for (entity, mut indicator, mut color) in query.iter_mut() {
+        if indicator.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn();
+        } else {
+            color.0.set_alpha(indicator.0.fraction_remaining());
+        }
+    }
+}
+
+fn despawn_spawn_warnings(
+    mut commands: Commands,
+    query: Query<Entity, With<SpawnWarningIndicator>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_schedule_is_read_in_time_order() {
+        let wave_data = WaveData::parse(
+            "(
+                spawns: [
+                    (at: 4.0, kind: Mine, edge: Top, speed: 20.0),
+                    (at: 1.0, kind: Fish, edge: Right, offset: 0.5, speed: 30.0,
+                        formation: Line(count: 3, spacing: 80.0)),
+                ],
+                repeat_after: Some(10.0),
+            )",
+        )
+        .unwrap();
+        assert_eq!(wave_data.repeat_after, Some(10.0));
+        let [first, second] = &wave_data.spawns[..] else {
+            panic!("expected two spawns");
+        };
+        assert_eq!(first.at, 1.0);
+        assert!(matches!(first.kind, EnemyKind::Fish));
+        assert_eq!(first.edge, Edge::Right);
+        assert!(matches!(
+            first.formation,
+            Formation::Line {
+                count: 3,
+                spacing: 80.0
+            }
+        ));
+        assert_eq!(second.at, 4.0);
+        assert!(matches!(second.kind, EnemyKind::Mine));
+    }
+
+    #[test]
+    fn the_shipped_schedule_names_every_kind() {
+        let contents =
+            fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/assets/waves.ron")).unwrap();
+        let wave_data = WaveData::parse(&contents).unwrap();
+        for kind in [FishKind::Plain, FishKind::Seeker, FishKind::Gunfish] {
+            assert!(
+                wave_data
+                    .spawns
+                    .iter()
+                    .any(|spawn| spawn.kind.fish_kind(|| FishKind::Plain) == Some(kind))
+            );
+        }
+    }
+
+    #[test]
+    fn a_malformed_schedule_is_rejected() {
+        assert!(WaveData::parse("(spawns: [(at: 1.0, kind: Shark)])").is_err());
+        assert!(
+            WaveData::parse(
+                "(spawns: [(at: 5.0, kind: Fish, edge: Left, speed: 1.0)], repeat_after: Some(2.0))"
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn skipping_ahead_passes_over_earlier_spawns() {
+        let wave_data = WaveData::parse(
+            "(
+                spawns: [
+                    (at: 1.0, kind: Fish, edge: Left, speed: 1.0),
+                    (at: 3.0, kind: Fish, edge: Left, speed: 1.0),
+                    (at: 6.0, kind: Fish, edge: Left, speed: 1.0),
+                ],
+                repeat_after: Some(10.0),
+            )",
+        )
+        .unwrap();
+        let mut clock = WaveClock::default();
+        clock.skip_to(24.0, &wave_data);
+        assert_eq!(clock.elapsed, 4.0);
+        assert_eq!(clock.next, 2);
+        assert_eq!(clock.next_warning, 2);
+
+        let once =
+            WaveData::parse("(spawns: [(at: 1.0, kind: Mine, edge: Top, speed: 1.0)])").unwrap();
+        clock.skip_to(24.0, &once);
+        assert_eq!(clock.next, 1);
+    }
+
+    #[test]
+    fn spawns_come_in_where_their_warnings_scrolled_to() {
+        let mut clock = WaveClock::default();
+        clock.warned_scroll.extend([0.0, -20.0]);
+        assert_eq!(clock.scroll_since_warning(-50.0), -50.0);
+        assert_eq!(clock.scroll_since_warning(-50.0), -30.0);
+        assert_eq!(clock.scroll_since_warning(-50.0), 0.0);
+    }
+
+    #[test]
+    fn a_line_is_centered_on_its_origin() {
+        let positions = Formation::Line {
+            count: 3,
+            spacing: 10.0,
+        }
+        .positions(Vec2::ZERO, Vec2::X, Vec2::Y);
+        assert_eq!(
+            positions,
+            [Vec2::new(0.0, -10.0), Vec2::ZERO, Vec2::new(0.0, 10.0)]
+        );
+    }
+}